@@ -0,0 +1,180 @@
+//! Shared helpers for building BibLaTeX entries from the metadata translators collect.
+
+use biblatex::{Bibliography, Entry};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+
+/// A field as a string, whether it was written as a string, a number or a boolean.
+pub(crate) fn scalar(v: &Value, key: &str) -> Option<String> {
+    v.get(key).and_then(scalar_value)
+}
+
+pub(crate) fn scalar_value(v: &Value) -> Option<String> {
+    let s = match v {
+        Value::String(s) => normalize_ws(s),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        _ => return None,
+    };
+    (!s.is_empty()).then_some(s)
+}
+
+/// Turn "Jane Doe <jane@example.org> (https://jane.dev)" into "Jane Doe".
+pub(crate) fn person_name(s: &str) -> Option<String> {
+    let name = s.split(['<', '(']).next().unwrap_or(s);
+    let name = normalize_ws(name);
+    (!name.is_empty()).then(|| escape_braces(&name))
+}
+
+pub(crate) fn normalize_ws(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut prev_space = false;
+    for ch in s.chars() {
+        if ch.is_whitespace() {
+            if !prev_space {
+                out.push(' ');
+                prev_space = true;
+            }
+        } else {
+            out.push(ch);
+            prev_space = false;
+        }
+    }
+    out.trim().to_string()
+}
+
+pub(crate) fn escape_braces(s: &str) -> String {
+    s.replace('{', "\\{").replace('}', "\\}")
+}
+
+/// Escape free text, such as a pasted reference, for a field value: besides braces, a backslash
+/// is written out so that it cannot start a TeX command.
+pub(crate) fn escape_text(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\textbackslash{}"),
+            '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Plain text from an HTML description, as Zenodo and Figshare return them.
+pub(crate) fn html_to_text(html: &str) -> String {
+    static TAG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]*>").unwrap());
+    let text = TAG_RE.replace_all(html, " ");
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    normalize_ws(&text)
+}
+
+/// The number of a month written as a number or an English name ("Dec", "December").
+pub(crate) fn month_number(m: &str) -> Option<u32> {
+    if let Ok(n) = m.parse::<u32>() {
+        return (1..=12).contains(&n).then_some(n);
+    }
+    let idx = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ]
+    .iter()
+    .position(|p| m.to_ascii_lowercase().starts_with(p))?;
+    Some(idx as u32 + 1)
+}
+
+/// A citation key in the usual "family, year, first title word" shape (e.g.,
+/// "massalin1987superoptimizer"), from names in BibLaTeX form.
+pub(crate) fn citation_key(authors: &[String], year: Option<&str>, title: Option<&str>) -> String {
+    let family = authors
+        .first()
+        .map(|n| match n.split_once(',') {
+            Some((family, _)) => family,
+            None => n.rsplit(' ').next().unwrap_or(n),
+        })
+        .unwrap_or_default();
+    let word = title
+        .and_then(|t| {
+            t.split(|c: char| !c.is_alphanumeric())
+                .find(|w| w.chars().count() > 3)
+        })
+        .unwrap_or_default();
+    let key: String = format!("{family}{}{word}", year.unwrap_or_default())
+        .to_lowercase()
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect();
+    if key.is_empty() {
+        "ref".to_string()
+    } else {
+        key
+    }
+}
+
+/// Values are expected to be escaped already, since author lists carry intentional braces.
+pub(crate) fn write_entry(ty: &str, key: &str, fields: Vec<(&str, String)>) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("@{ty}{{{key},\n"));
+    for (k, v) in fields {
+        out.push_str(&format!("    {k} = {{{v}}},\n"));
+    }
+    out.push_str("}\n");
+    out
+}
+
+pub(crate) fn parse_single(bib: &str) -> anyhow::Result<Entry> {
+    let bib =
+        Bibliography::parse(bib).map_err(|e| anyhow::anyhow!("failed to parse BibLaTeX: {e}"))?;
+    bib.into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("empty bibliography"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html_descriptions_become_plain_text() {
+        assert_eq!(
+            html_to_text("<p>Data &amp; code\n for <em>the</em> paper</p>"),
+            "Data & code for the paper"
+        );
+    }
+
+    #[test]
+    fn month_numbers_and_names() {
+        assert_eq!(month_number("12"), Some(12));
+        assert_eq!(month_number("Sep"), Some(9));
+        assert_eq!(month_number("March"), Some(3));
+        assert_eq!(month_number("13"), None);
+        assert_eq!(month_number("Spring"), None);
+    }
+
+    #[test]
+    fn written_entries_parse_back() {
+        let bib = write_entry(
+            "misc",
+            "k",
+            vec![
+                ("title", escape_braces("Sets {a, b}")),
+                (
+                    "author",
+                    person_name("Jane Doe <jane@example.org>").unwrap(),
+                ),
+            ],
+        );
+        let out = parse_single(&bib).unwrap().to_biblatex_string();
+        assert!(out.contains("author = {Jane Doe}"), "{out}");
+        assert!(out.starts_with("@misc{k,"), "{out}");
+    }
+}
@@ -0,0 +1,408 @@
+//! Software citation metadata: `CITATION.cff` files and repository/package fallbacks.
+
+use biblatex::Entry;
+use serde_json::Value;
+
+use crate::bibtex::{escape_braces, parse_single, scalar, scalar_value, write_entry};
+
+/// What we know about a piece of software, whether from `CITATION.cff` or from its host.
+#[derive(Debug, Default, Clone)]
+pub struct SoftwareMeta {
+    pub title: Option<String>,
+    /// Names already in BibLaTeX form ("Last, First" or "{Organisation}").
+    pub authors: Vec<String>,
+    pub version: Option<String>,
+    /// ISO date (YYYY-MM-DD or a prefix of it).
+    pub date: Option<String>,
+    pub url: Option<String>,
+    pub repository: Option<String>,
+    pub license: Option<String>,
+    pub abstract_: Option<String>,
+    pub keywords: Vec<String>,
+    pub doi: Option<String>,
+}
+
+impl SoftwareMeta {
+    /// Fill every field left empty in `self` from `other`.
+    pub fn or(mut self, other: SoftwareMeta) -> SoftwareMeta {
+        self.title = self.title.or(other.title);
+        if self.authors.is_empty() {
+            self.authors = other.authors;
+        }
+        self.version = self.version.or(other.version);
+        self.date = self.date.or(other.date);
+        self.url = self.url.or(other.url);
+        self.repository = self.repository.or(other.repository);
+        self.license = self.license.or(other.license);
+        self.abstract_ = self.abstract_.or(other.abstract_);
+        if self.keywords.is_empty() {
+            self.keywords = other.keywords;
+        }
+        self.doi = self.doi.or(other.doi);
+        self
+    }
+}
+
+/// Build an `@software` entry from collected metadata.
+pub fn software_entry(meta: &SoftwareMeta, key: &str) -> anyhow::Result<Entry> {
+    let title = meta
+        .title
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("no title found for software {key}"))?;
+    let mut fields: Vec<(&str, String)> = vec![("title", escape_braces(title))];
+    if !meta.authors.is_empty() {
+        fields.push(("author", meta.authors.join(" and ")));
+    }
+    let plain = [
+        ("version", &meta.version),
+        ("date", &meta.date),
+        ("doi", &meta.doi),
+        ("url", &meta.url),
+        ("repository", &meta.repository),
+        ("license", &meta.license),
+        ("abstract", &meta.abstract_),
+    ];
+    for (k, v) in plain {
+        if let Some(v) = v {
+            fields.push((k, escape_braces(v)));
+        }
+    }
+    if !meta.keywords.is_empty() {
+        fields.push(("keywords", escape_braces(&meta.keywords.join(", "))));
+    }
+    parse_single(&write_entry("software", key, fields))
+}
+
+/// Parse a `CITATION.cff` document into an entry.
+///
+/// The `preferred-citation`, when present, wins: a repository whose authors ask to be cited
+/// through a paper gets that paper. Otherwise the file describes the software itself, and any
+/// field it leaves out is taken from `fallback` (e.g., the latest release of the repository).
+pub fn entry_from_cff(text: &str, fallback: SoftwareMeta, key: &str) -> anyhow::Result<Entry> {
+    let cff: Value = serde_yaml_ng::from_str(text)
+        .map_err(|e| anyhow::anyhow!("failed to parse CITATION.cff: {e}"))?;
+    if !cff.is_object() {
+        return Err(anyhow::anyhow!("CITATION.cff is not a mapping"));
+    }
+    if let Some(pref) = cff.get("preferred-citation").filter(|p| p.is_object()) {
+        return preferred_citation_entry(pref, key);
+    }
+    software_entry(&cff_software_meta(&cff).or(fallback), key)
+}
+
+/// The title a `CITATION.cff` document gives the software, if it parses and has one.
+pub fn cff_title(text: &str) -> Option<String> {
+    let cff: Value = serde_yaml_ng::from_str(text).ok()?;
+    scalar(&cff, "title")
+}
+
+/// Read the top-level software description of a `CITATION.cff` document.
+fn cff_software_meta(cff: &Value) -> SoftwareMeta {
+    SoftwareMeta {
+        title: scalar(cff, "title"),
+        authors: cff_people(cff.get("authors")),
+        version: scalar(cff, "version"),
+        date: scalar(cff, "date-released"),
+        url: scalar(cff, "url").or_else(|| scalar(cff, "repository-code")),
+        repository: scalar(cff, "repository-code"),
+        license: cff_license(cff),
+        abstract_: scalar(cff, "abstract"),
+        keywords: string_list(cff.get("keywords")),
+        doi: scalar(cff, "doi").or_else(|| cff_identifier(cff, "doi")),
+    }
+}
+
+/// Map a CFF reference `type` to a BibLaTeX entry type.
+fn entry_type(cff_type: &str) -> &'static str {
+    match cff_type {
+        "article" | "article-journal" | "magazine-article" | "newspaper-article" => "article",
+        "book" => "book",
+        "conference-paper" | "conference" => "inproceedings",
+        "proceedings" => "proceedings",
+        "report" => "report",
+        "thesis" => "thesis",
+        "manual" => "manual",
+        "unpublished" => "unpublished",
+        "chapter" => "incollection",
+        "dataset" | "data" | "database" => "dataset",
+        "website" | "blog" => "online",
+        t if t.starts_with("software") => "software",
+        _ => "misc",
+    }
+}
+
+fn preferred_citation_entry(pref: &Value, key: &str) -> anyhow::Result<Entry> {
+    let ty = entry_type(scalar(pref, "type").as_deref().unwrap_or("generic"));
+    let title = scalar(pref, "title")
+        .ok_or_else(|| anyhow::anyhow!("preferred-citation in CITATION.cff has no title"))?;
+    let mut fields: Vec<(&str, String)> = vec![("title", escape_braces(&title))];
+
+    let authors = cff_people(pref.get("authors"));
+    if !authors.is_empty() {
+        fields.push(("author", authors.join(" and ")));
+    }
+    let editors = cff_people(pref.get("editors"));
+    if !editors.is_empty() {
+        fields.push(("editor", editors.join(" and ")));
+    }
+
+    // Containers: the journal for articles, the proceedings or book title otherwise.
+    let container = match ty {
+        "article" => scalar(pref, "journal").map(|j| ("journaltitle", j)),
+        "inproceedings" | "incollection" => scalar(pref, "collection-title")
+            .or_else(|| pref.get("conference").and_then(|c| scalar(c, "name")))
+            .map(|b| ("booktitle", b)),
+        _ => None,
+    };
+    fields.extend(container.map(|(k, v)| (k, escape_braces(&v))));
+    if let Some(event) = pref.get("conference").and_then(|c| scalar(c, "name"))
+        && ty == "inproceedings"
+    {
+        fields.push(("eventtitle", escape_braces(&event)));
+    }
+
+    if let Some(date) = scalar(pref, "date-published") {
+        fields.push(("date", date));
+    } else if let Some(year) = scalar(pref, "year") {
+        fields.push(("year", year));
+        if let Some(month) = scalar(pref, "month") {
+            fields.push(("month", month));
+        }
+    }
+
+    let plain = [
+        ("volume", "volume"),
+        ("number", "issue"),
+        ("edition", "edition"),
+        ("type", "thesis-type"),
+        ("isbn", "isbn"),
+        ("issn", "issn"),
+        ("doi", "doi"),
+        ("url", "url"),
+        ("abstract", "abstract"),
+        ("note", "notes"),
+    ];
+    for (field, cff_key) in plain {
+        if let Some(v) = scalar(pref, cff_key) {
+            fields.push((field, escape_braces(&v)));
+        }
+    }
+    match (scalar(pref, "start"), scalar(pref, "end")) {
+        (Some(s), Some(e)) => fields.push(("pages", format!("{s}--{e}"))),
+        (Some(s), None) => fields.push(("pages", s)),
+        _ => {
+            if let Some(p) = scalar(pref, "pages") {
+                fields.push(("pagetotal", p));
+            }
+        }
+    }
+    for (field, cff_key) in [
+        ("publisher", "publisher"),
+        ("institution", "institution"),
+        ("location", "location"),
+    ] {
+        if let Some(name) = pref.get(cff_key).and_then(|v| scalar(v, "name")) {
+            fields.push((field, escape_braces(&name)));
+        }
+    }
+    let keywords = string_list(pref.get("keywords"));
+    if !keywords.is_empty() {
+        fields.push(("keywords", escape_braces(&keywords.join(", "))));
+    }
+
+    parse_single(&write_entry(ty, key, fields))
+}
+
+/// Format CFF persons and entities as BibLaTeX names. Entities are braced so that BibLaTeX
+/// does not split "The Rust Project Developers" into first and last names.
+fn cff_people(v: Option<&Value>) -> Vec<String> {
+    let Some(list) = v.and_then(Value::as_array) else {
+        return Vec::new();
+    };
+    list.iter()
+        .filter_map(|p| {
+            let family = scalar(p, "family-names");
+            let given = scalar(p, "given-names");
+            match (family, given) {
+                (Some(family), given) => {
+                    let last = match scalar(p, "name-particle") {
+                        Some(particle) => format!("{particle} {family}"),
+                        None => family,
+                    };
+                    let mut name = escape_braces(&last);
+                    if let Some(suffix) = scalar(p, "name-suffix") {
+                        name.push_str(&format!(", {}", escape_braces(&suffix)));
+                    }
+                    if let Some(given) = given {
+                        name.push_str(&format!(", {}", escape_braces(&given)));
+                    }
+                    Some(name)
+                }
+                (None, Some(given)) => Some(format!("{{{}}}", escape_braces(&given))),
+                (None, None) => scalar(p, "name")
+                    .or_else(|| scalar(p, "alias"))
+                    .map(|n| format!("{{{}}}", escape_braces(&n))),
+            }
+        })
+        .collect()
+}
+
+fn cff_license(cff: &Value) -> Option<String> {
+    match cff.get("license") {
+        Some(Value::Array(ls)) => {
+            let ls: Vec<String> = ls.iter().filter_map(scalar_value).collect();
+            (!ls.is_empty()).then(|| ls.join(" OR "))
+        }
+        Some(v) => scalar_value(v),
+        None => None,
+    }
+}
+
+fn cff_identifier(cff: &Value, ty: &str) -> Option<String> {
+    cff.get("identifiers")?
+        .as_array()?
+        .iter()
+        .find(|i| i.get("type").and_then(Value::as_str) == Some(ty))
+        .and_then(|i| scalar(i, "value"))
+}
+
+fn string_list(v: Option<&Value>) -> Vec<String> {
+    v.and_then(Value::as_array)
+        .map(|a| a.iter().filter_map(scalar_value).collect())
+        .unwrap_or_default()
+}
+
+/// Normalise the many ways manifests spell a repository to a browsable HTTPS URL.
+pub(crate) fn repository_url(s: &str) -> String {
+    let s = s.trim();
+    let s = s.strip_prefix("git+").unwrap_or(s);
+    let s = if let Some(rest) = s.strip_prefix("git@") {
+        // git@github.com:owner/repo.git
+        format!("https://{}", rest.replacen(':', "/", 1))
+    } else if let Some(rest) = s.strip_prefix("github:") {
+        format!("https://github.com/{rest}")
+    } else if let Some(rest) = s.strip_prefix("gitlab:") {
+        format!("https://gitlab.com/{rest}")
+    } else if !s.contains("://") && s.split('/').count() == 2 {
+        // npm shorthand: "owner/repo" means GitHub.
+        format!("https://github.com/{s}")
+    } else {
+        s.replacen("git://", "https://", 1)
+            .replacen("ssh://git@", "https://", 1)
+    };
+    s.strip_suffix(".git").unwrap_or(&s).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CFF: &str = r#"
+cff-version: 1.2.0
+message: "If you use this software, please cite it as below."
+title: "ripgrep"
+version: 14.1.0
+date-released: 2024-01-06
+authors:
+  - family-names: Gallant
+    given-names: Andrew
+  - name: "The ripgrep Developers"
+license: [MIT, Unlicense]
+repository-code: "https://github.com/BurntSushi/ripgrep"
+keywords: [search, regex]
+identifiers:
+  - type: doi
+    value: 10.5281/zenodo.1234567
+"#;
+
+    #[test]
+    fn cff_becomes_software_entry() {
+        let entry =
+            entry_from_cff(CFF, SoftwareMeta::default(), "github:BurntSushi/ripgrep").unwrap();
+        let out = entry.to_biblatex_string();
+        assert!(
+            out.starts_with("@software{github:BurntSushi/ripgrep,"),
+            "{out}"
+        );
+        assert!(
+            out.contains("author = {Gallant, Andrew and {The ripgrep Developers}}"),
+            "{out}"
+        );
+        assert!(out.contains("version = {14.1.0}"), "{out}");
+        assert!(out.contains("date = {2024-01-06}"), "{out}");
+        assert!(out.contains("license = {MIT OR Unlicense}"), "{out}");
+        assert!(out.contains("doi = {10.5281/zenodo.1234567}"), "{out}");
+        assert!(
+            out.contains("url = {https://github.com/BurntSushi/ripgrep}"),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn cff_missing_fields_come_from_fallback() {
+        let cff = "cff-version: 1.2.0\ntitle: tool\nauthors:\n  - name: Acme\n";
+        let fallback = SoftwareMeta {
+            version: Some("v2.0.0".into()),
+            date: Some("2024-05-01".into()),
+            url: Some("https://gitlab.com/acme/tool".into()),
+            ..Default::default()
+        };
+        let out = entry_from_cff(cff, fallback, "gitlab:acme/tool")
+            .unwrap()
+            .to_biblatex_string();
+        assert!(out.contains("version = {v2.0.0}"), "{out}");
+        assert!(out.contains("date = {2024-05-01}"), "{out}");
+        assert!(
+            out.contains("url = {https://gitlab.com/acme/tool}"),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn preferred_citation_wins() {
+        let cff = r#"
+cff-version: 1.2.0
+title: tool
+authors:
+  - name: Acme
+preferred-citation:
+  type: conference-paper
+  title: "Tool: A Paper"
+  authors:
+    - family-names: Berg
+      given-names: Jan
+      name-particle: van den
+  collection-title: "Proceedings of the Example Conference"
+  year: 2023
+  start: 10
+  end: 20
+  doi: 10.1145/1234567
+"#;
+        let out = entry_from_cff(cff, SoftwareMeta::default(), "k")
+            .unwrap()
+            .to_biblatex_string();
+        assert!(out.starts_with("@inproceedings{k,"), "{out}");
+        assert!(out.contains("author = {van den Berg, Jan}"), "{out}");
+        assert!(
+            out.contains("booktitle = {Proceedings of the Example Conference}"),
+            "{out}"
+        );
+        assert!(
+            out.contains("pages = {10--20}") || out.contains("pages = {10–20}"),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn repository_urls_are_normalised() {
+        for (input, want) in [
+            ("git+https://github.com/a/b.git", "https://github.com/a/b"),
+            ("git@github.com:a/b.git", "https://github.com/a/b"),
+            ("github:a/b", "https://github.com/a/b"),
+            ("a/b", "https://github.com/a/b"),
+            ("https://gitlab.com/g/s/p", "https://gitlab.com/g/s/p"),
+        ] {
+            assert_eq!(repository_url(input), want, "input {input}");
+        }
+    }
+}
@@ -2,6 +2,8 @@ use std::{fs, path::PathBuf, str::FromStr};
 
 use clap::{Parser, Subcommand};
 
+use crate::{identifier::arxiv, pdf, project, resolver};
+
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub struct Cli {
@@ -15,25 +17,123 @@ pub enum Command {
     Fetch {
         #[arg(value_name = "SRC")]
         from: Vec<Source>,
+        /// Use the published version's metadata for preprints that have one (e.g., arXiv papers
+        /// with a journal DOI), keeping the preprint as `eprint`
+        #[arg(long)]
+        prefer_published: bool,
+        /// Keep at most N items from a source that lists several (e.g., a DBLP person page);
+        /// on a terminal, you are asked before any are dropped
+        #[arg(long, value_name = "N", default_value_t = resolver::DEFAULT_MAX_ITEMS)]
+        max_items: usize,
+        /// Keep every item from sources that list several, without asking
+        #[arg(long, conflicts_with = "max_items")]
+        all: bool,
+        /// Search for inputs that are not identifiers (e.g., titles or reference strings), and
+        /// take the best match when it is a confident one
+        #[arg(long)]
+        search: bool,
+        /// Choose among the items of multi-item sources and the candidates of unsure searches on
+        /// the terminal (ignored when the output is not a terminal)
+        #[arg(long, short)]
+        interactive: bool,
     },
     /// Pull the files related to the given citation items
     Pull {
         #[arg(value_name = "SRC")]
         from: Vec<Source>,
+        /// Download and unpack the source files of arXiv papers (the e-print), and list the
+        /// bibliography files they use
+        #[arg(long)]
+        source: bool,
+        /// Directory to unpack into; each paper gets its own subdirectory
+        #[arg(long, short, value_name = "DIR", default_value = ".")]
+        output: PathBuf,
+        /// Resolve the entries of the bibliographies found again and print them
+        #[arg(long, requires = "source")]
+        resolve: bool,
+    },
+    /// Import the references of a bibliography (`.bib`, `.bbl`, or a TeX file with a
+    /// `thebibliography` environment), resolving them again through their identifiers
+    Import {
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+    },
+    /// Search for a work by its title, and print it when the best match is a confident one, or
+    /// the candidates otherwise
+    Search {
+        #[arg(value_name = "TITLE")]
+        query: String,
+        /// Year the work appeared, to rank candidates by
+        #[arg(long, value_name = "YEAR")]
+        year: Option<String>,
+        /// Family name of an author, to rank candidates by; repeat for several
+        #[arg(long = "author", short = 'a', value_name = "NAME")]
+        authors: Vec<String>,
+        /// Number of candidates to fetch and rank
+        #[arg(long, value_name = "N", default_value_t = 10)]
+        rows: usize,
+        /// Choose among the candidates on the terminal (ignored when the output is not a terminal)
+        #[arg(long, short)]
+        interactive: bool,
+    },
+    /// Parse a free-text reference (e.g., one copied from a paper) and print it as BibLaTeX
+    ParseRef {
+        #[arg(value_name = "TEXT")]
+        text: String,
+        /// Look the reference up online when the parse is unsure, and print the match instead
+        #[arg(long)]
+        confirm: bool,
+    },
+    /// arXiv-specific queries
+    Arxiv {
+        #[command(subcommand)]
+        command: ArxivCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ArxivCommand {
+    /// List every version of a paper with its submission date and comment
+    Versions {
+        #[arg(value_name = "ID")]
+        id: String,
+    },
+    /// Search arXiv and print an entry for each matching paper
+    Search {
+        /// Search terms, optionally with field prefixes (e.g., `au:devlin ti:bert`)
+        #[arg(value_name = "QUERY")]
+        query: Option<String>,
+        /// Only papers in this category (e.g., `cs.CL`); repeat to allow several
+        #[arg(long = "category", short = 'c', value_name = "CAT")]
+        categories: Vec<String>,
+        /// Only papers submitted on or after this date (`YYYY`, `YYYY-MM` or `YYYY-MM-DD`)
+        #[arg(long, value_name = "DATE")]
+        since: Option<String>,
+        /// Only papers submitted on or before this date
+        #[arg(long, value_name = "DATE")]
+        until: Option<String>,
+        /// Maximum number of papers to print
+        #[arg(long, value_name = "N", default_value_t = arxiv::DEFAULT_MAX_RESULTS)]
+        max: usize,
     },
 }
 
 #[derive(Clone, Debug)]
 /// Defines where we can get citation items from, which can either be
 ///
-/// - a single identifier, or
-/// - a bibliography file.
+/// - a single identifier,
+/// - a bibliography file,
+/// - a local software project (a directory or a metadata file such as `CITATION.cff`), or
+/// - a PDF file.
 ///
-/// The latter will be treated as a list of the former.
+/// A bibliography file will be treated as a list of identifiers; a project is cited as
+/// `@software` from its metadata, without touching the network; a PDF is cited through the
+/// identifiers it carries.
 pub enum Source {
     Identifier(String),
-    #[allow(dead_code)]
     File(PathBuf),
+    Project(PathBuf),
+    Pdf(PathBuf),
 }
 
 impl FromStr for Source {
@@ -45,9 +145,15 @@ impl FromStr for Source {
         // into a list of items, and then also parsing a single identifier into a citation item,
         // thus aking them uniform.
 
-        // Is this a path?
+        // Is this a path? Project directories, metadata files and PDFs are told apart by name.
         if let Ok(path) = fs::canonicalize(s) {
-            Ok(Source::File(path))
+            if project::is_project(&path) {
+                Ok(Source::Project(path))
+            } else if pdf::is_pdf(&path) {
+                Ok(Source::Pdf(path))
+            } else {
+                Ok(Source::File(path))
+            }
         }
         // No? Must be an identifier then!
         else {
@@ -75,6 +181,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn from_str_identifies_pdfs() {
+        let dir = tempfile::tempdir().expect("tmp dir");
+        let pdf = dir.path().join("Paper.PDF");
+        std::fs::write(&pdf, "%PDF-1.4\n").unwrap();
+        match Source::from_str(pdf.to_str().unwrap()).expect("parse") {
+            Source::Pdf(got) => assert_eq!(got, std::fs::canonicalize(&pdf).unwrap()),
+            other => panic!("expected PDF source, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn from_str_identifies_project_sources() {
+        let dir = tempfile::tempdir().expect("tmp dir");
+        let cff = dir.path().join("CITATION.cff");
+        std::fs::write(&cff, "cff-version: 1.2.0\ntitle: tool\n").unwrap();
+        for p in [dir.path(), cff.as_path()] {
+            match Source::from_str(p.to_str().unwrap()).expect("parse") {
+                Source::Project(got) => assert_eq!(got, std::fs::canonicalize(p).unwrap()),
+                other => panic!("expected project source, got {other:?}"),
+            }
+        }
+    }
+
     #[test]
     fn from_str_falls_back_to_identifier() {
         proptest::proptest!(|(s in "[A-Za-z0-9._-]{1,32}")| {
@@ -83,7 +213,7 @@ mod tests {
             let src = Source::from_str(&s).expect("parse");
             match src {
                 Source::Identifier(id) => proptest::prop_assert_eq!(id, s),
-                Source::File(_) => proptest::prop_assert!(false, "should not be a file"),
+                _ => proptest::prop_assert!(false, "should not be a file"),
             }
         })
     }
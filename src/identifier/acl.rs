@@ -0,0 +1,185 @@
+use anyhow::Context;
+use biblatex::{Bibliography, Chunk, Entry, Spanned};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{identifier::Identifier, resolver::IdFamily};
+
+/// An ACL Anthology paper ID (new-style `2020.acl-main.1` or legacy `P19-1001`) or URL.
+pub struct AclAnthology<'a> {
+    id: &'a str,
+}
+
+static NEWSTYLE_RE: Lazy<Regex> = Lazy::new(|| {
+    // YYYY.venue(-volume)*.paper, e.g. 2020.acl-main.1 or 2023.findings-emnlp.123
+    Regex::new(r"^\d{4}\.[a-z0-9]+(?:-[a-z0-9]+)*\.\d+$").unwrap()
+});
+static LEGACY_RE: Lazy<Regex> = Lazy::new(|| {
+    // Letter for the venue, two-digit year, four-digit paper number, e.g. P19-1001
+    Regex::new(r"^[A-Z]\d{2}-\d{4}$").unwrap()
+});
+
+impl<'a> Identifier<'a> for AclAnthology<'a> {
+    fn parse(identifier: &'a str) -> Option<Box<Self>> {
+        let mut s = identifier.trim();
+        if let Some(rest) = s.strip_prefix("acl:").or_else(|| s.strip_prefix("ACL:")) {
+            s = rest.trim_start();
+        }
+
+        // URLs:
+        //  - https://aclanthology.org/2020.acl-main.1/ (also .pdf, .bib)
+        //  - https://www.aclweb.org/anthology/P19-1001/ (also .pdf)
+        if let Some((host, path)) = s
+            .strip_prefix("http://")
+            .or_else(|| s.strip_prefix("https://"))
+            .and_then(|rest| rest.split_once('/'))
+        {
+            let host = host.to_ascii_lowercase();
+            let path = path.split(['?', '#']).next().unwrap_or(path);
+            let path = match host.as_str() {
+                "aclanthology.org" | "www.aclanthology.org" => path,
+                "aclweb.org" | "www.aclweb.org" => path.strip_prefix("anthology/")?,
+                _ => return None,
+            };
+            let path = path.trim_matches('/');
+            s = [".pdf", ".bib", ".xml"]
+                .iter()
+                .find_map(|ext| path.strip_suffix(ext))
+                .unwrap_or(path);
+        }
+
+        (NEWSTYLE_RE.is_match(s) || LEGACY_RE.is_match(s)).then(|| Box::new(AclAnthology { id: s }))
+    }
+
+    fn resolve(&self) -> anyhow::Result<Entry> {
+        let url = format!("https://aclanthology.org/{}.bib", self.id);
+        let cfg = ureq::Agent::config_builder()
+            .timeout_connect(Some(std::time::Duration::from_secs(5)))
+            .timeout_global(Some(std::time::Duration::from_secs(15)))
+            .build();
+        let agent = ureq::Agent::new_with_config(cfg);
+        let body: String = agent
+            .get(&url)
+            .header(
+                "User-Agent",
+                "Mozilla/5.0 (compatible; bib/0.1; +https://aclanthology.org)",
+            )
+            .call()
+            .with_context(|| format!("failed request for ACL Anthology id {}", self.id))?
+            .into_body()
+            .read_to_string()
+            .context("failed to read ACL Anthology response body")?;
+        normalise_anthology_bib(&body, self.id)
+    }
+}
+
+impl IdFamily for AclAnthology<'_> {
+    type For<'a> = AclAnthology<'a>;
+}
+
+/// Parse the Anthology's `.bib` export and pin its URL and PDF link to the canonical ones.
+///
+/// The export is already well-formed BibTeX; BibLaTeX output takes care of renaming `journal`
+/// and `address`. The `pdf` field keeps the direct PDF link so `pull` can fetch the file.
+fn normalise_anthology_bib(body: &str, id: &str) -> anyhow::Result<Entry> {
+    let bib = Bibliography::parse(body)
+        .map_err(|e| anyhow::anyhow!("failed to parse ACL Anthology BibTeX: {e}"))?;
+    let mut entry = bib
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("empty bibliography from ACL Anthology for {id}"))?;
+
+    let set = |entry: &mut Entry, key: &str, value: String| {
+        entry.set(key, vec![Spanned::zero(Chunk::Normal(value))]);
+    };
+    set(&mut entry, "url", format!("https://aclanthology.org/{id}/"));
+    set(
+        &mut entry,
+        "pdf",
+        format!("https://aclanthology.org/{id}.pdf"),
+    );
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Option<&str> {
+        <AclAnthology<'_> as Identifier<'_>>::parse(s).map(|a| a.id)
+    }
+
+    #[test]
+    fn parse_new_style_and_legacy_ids() {
+        assert_eq!(parse("2020.acl-main.1"), Some("2020.acl-main.1"));
+        assert_eq!(
+            parse("2023.findings-emnlp.123"),
+            Some("2023.findings-emnlp.123")
+        );
+        assert_eq!(parse("P19-1001"), Some("P19-1001"));
+        assert_eq!(parse("acl:W19-5301"), Some("W19-5301"));
+    }
+
+    #[test]
+    fn parse_urls() {
+        for (input, id) in [
+            (
+                "https://aclanthology.org/2020.acl-main.1/",
+                "2020.acl-main.1",
+            ),
+            (
+                "https://aclanthology.org/2020.acl-main.1.pdf",
+                "2020.acl-main.1",
+            ),
+            ("https://aclanthology.org/N19-1423.bib", "N19-1423"),
+            ("https://www.aclweb.org/anthology/P19-1001/", "P19-1001"),
+            ("https://www.aclweb.org/anthology/P19-1001.pdf", "P19-1001"),
+        ] {
+            assert_eq!(parse(input), Some(id), "input {input}");
+        }
+    }
+
+    #[test]
+    fn parse_rejects_non_anthology() {
+        for bad in [
+            "p19-1001",
+            "P19-100",
+            "2020.acl-main",
+            "https://aclanthology.org/events/acl-2020/",
+            "https://example.com/P19-1001",
+        ] {
+            assert!(parse(bad).is_none(), "should reject {bad}");
+        }
+    }
+
+    #[test]
+    fn normalise_keeps_anthology_url_and_pdf() {
+        let body = r#"@inproceedings{devlin-etal-2019-bert,
+    title = "{BERT}: Pre-training of Deep Bidirectional Transformers for Language Understanding",
+    author = "Devlin, Jacob  and
+      Chang, Ming-Wei",
+    booktitle = "Proceedings of the 2019 Conference of the North {A}merican Chapter of the Association for Computational Linguistics",
+    month = jun,
+    year = "2019",
+    address = "Minneapolis, Minnesota",
+    publisher = "Association for Computational Linguistics",
+    url = "https://aclanthology.org/N19-1423",
+    doi = "10.18653/v1/N19-1423",
+    pages = "4171--4186",
+}
+"#;
+        let entry = normalise_anthology_bib(body, "N19-1423").unwrap();
+        assert_eq!(entry.key, "devlin-etal-2019-bert");
+        let out = entry.to_biblatex_string();
+        assert!(
+            out.contains("url = {https://aclanthology.org/N19-1423/}"),
+            "{out}"
+        );
+        assert!(
+            out.contains("pdf = {https://aclanthology.org/N19-1423.pdf}"),
+            "{out}"
+        );
+        assert!(out.contains("location = "), "{out}");
+        assert!(entry.get("doi").is_some());
+    }
+}
@@ -0,0 +1,244 @@
+use anyhow::Context;
+use biblatex::{Bibliography, Chunk, Entry, Spanned};
+use once_cell::sync::Lazy;
+use percent_encoding::percent_decode_str;
+use regex::Regex;
+
+use crate::{identifier::Identifier, resolver::IdFamily};
+
+/// Default ADS API root; override with `BIB_ADS_BASE_URL` (e.g., for a mirror or a mock).
+const DEFAULT_BASE_URL: &str = "https://api.adsabs.harvard.edu";
+
+/// A NASA ADS bibcode (e.g., `2019ApJ...882L..12P`) or an ADS abstract URL.
+pub struct Ads {
+    bibcode: String,
+}
+
+static BIBCODE_RE: Lazy<Regex> = Lazy::new(|| {
+    // YYYY JJJJJ VVVV M PPPP A: year, journal, volume, qualifier, page, first-author initial.
+    // Padding uses '.', and journals such as A&A carry an '&'.
+    Regex::new(r"^\d{4}[A-Za-z0-9&.]{14}[A-Za-z.]$").unwrap()
+});
+
+impl<'a> Identifier<'a> for Ads {
+    fn parse(identifier: &'a str) -> Option<Box<Self>> {
+        let s = identifier.trim();
+
+        let prefixed = ["ads:", "ADS:", "bibcode:"]
+            .iter()
+            .find_map(|p| s.strip_prefix(p))
+            .map(str::trim_start);
+
+        let candidate = if let Some(b) = prefixed {
+            b.to_string()
+        } else if let Some((host, path)) = s
+            .strip_prefix("http://")
+            .or_else(|| s.strip_prefix("https://"))
+            .and_then(|rest| rest.split_once('/'))
+        {
+            // URLs:
+            //  - https://ui.adsabs.harvard.edu/abs/2019ApJ...882L..12P/abstract
+            //  - https://adsabs.harvard.edu/abs/2019ApJ...882L..12P
+            if !matches!(
+                host.to_ascii_lowercase().as_str(),
+                "ui.adsabs.harvard.edu" | "adsabs.harvard.edu" | "www.adsabs.harvard.edu"
+            ) {
+                return None;
+            }
+            let path = path.split(['?', '#']).next().unwrap_or(path);
+            let raw = path.strip_prefix("abs/")?.split('/').next()?;
+            percent_decode_str(raw).decode_utf8().ok()?.into_owned()
+        } else {
+            // Bare bibcodes always carry padding dots or an '&'; requiring one keeps plain
+            // 19-character words from being mistaken for bibcodes.
+            if !s.contains(['.', '&']) {
+                return None;
+            }
+            s.to_string()
+        };
+
+        BIBCODE_RE
+            .is_match(&candidate)
+            .then(|| Box::new(Ads { bibcode: candidate }))
+    }
+
+    fn resolve(&self) -> anyhow::Result<Entry> {
+        let token = std::env::var("ADS_API_TOKEN").map_err(|_| {
+            anyhow::anyhow!(
+                "ADS requires an API token; set ADS_API_TOKEN to resolve {}",
+                self.bibcode
+            )
+        })?;
+        let base = std::env::var("BIB_ADS_BASE_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.into());
+        let url = format!("{}/v1/export/bibtex", base.trim_end_matches('/'));
+        let payload = serde_json::json!({ "bibcode": [self.bibcode] }).to_string();
+
+        let cfg = ureq::Agent::config_builder()
+            .timeout_connect(Some(std::time::Duration::from_secs(5)))
+            .timeout_global(Some(std::time::Duration::from_secs(15)))
+            .build();
+        let agent = ureq::Agent::new_with_config(cfg);
+        let body: String = agent
+            .post(&url)
+            .header(
+                "User-Agent",
+                "Mozilla/5.0 (compatible; bib/0.1; +https://ui.adsabs.harvard.edu)",
+            )
+            .header("Authorization", &format!("Bearer {token}"))
+            .header("Content-Type", "application/json")
+            .send(payload)
+            .with_context(|| format!("failed ADS request for bibcode {}", self.bibcode))?
+            .into_body()
+            .read_to_string()
+            .context("failed to read ADS response body")?;
+        let v: serde_json::Value =
+            serde_json::from_str(&body).context("invalid JSON from ADS export API")?;
+        let export = v
+            .get("export")
+            .and_then(|e| e.as_str())
+            .ok_or_else(|| anyhow::anyhow!("ADS export response has no BibTeX"))?;
+        parse_ads_bibtex(export, &self.bibcode)
+    }
+}
+
+impl IdFamily for Ads {
+    type For<'a> = Ads;
+}
+
+/// AASTeX journal macros that ADS uses in `journal`, expanded to full titles.
+const JOURNAL_MACROS: &[(&str, &str)] = &[
+    ("\\aap", "Astronomy and Astrophysics"),
+    ("\\aaps", "Astronomy and Astrophysics Supplement Series"),
+    ("\\aj", "The Astronomical Journal"),
+    ("\\apj", "The Astrophysical Journal"),
+    ("\\apjl", "The Astrophysical Journal Letters"),
+    ("\\apjs", "The Astrophysical Journal Supplement Series"),
+    ("\\araa", "Annual Review of Astronomy and Astrophysics"),
+    (
+        "\\mnras",
+        "Monthly Notices of the Royal Astronomical Society",
+    ),
+    ("\\nat", "Nature"),
+    ("\\natas", "Nature Astronomy"),
+    (
+        "\\pasp",
+        "Publications of the Astronomical Society of the Pacific",
+    ),
+    (
+        "\\pasj",
+        "Publications of the Astronomical Society of Japan",
+    ),
+    ("\\prd", "Physical Review D"),
+    ("\\prl", "Physical Review Letters"),
+    ("\\jcap", "Journal of Cosmology and Astroparticle Physics"),
+    ("\\icarus", "Icarus"),
+    ("\\solphys", "Solar Physics"),
+    ("\\ssr", "Space Science Reviews"),
+];
+
+/// Parse an ADS BibTeX export, keep the bibcode in its own field and drop ADS bookkeeping.
+fn parse_ads_bibtex(body: &str, bibcode: &str) -> anyhow::Result<Entry> {
+    let bib = Bibliography::parse(body)
+        .map_err(|e| anyhow::anyhow!("failed to parse ADS BibTeX: {e}"))?;
+    let mut entry = bib
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("no ADS record found for bibcode {bibcode}"))?;
+
+    let set = |entry: &mut Entry, key: &str, value: String| {
+        entry.set(key, vec![Spanned::zero(Chunk::Normal(value))]);
+    };
+
+    if let Some(journal) = entry
+        .get("journal")
+        .map(|j| j.iter().map(|c| c.v.get()).collect::<String>())
+    {
+        let journal = journal.trim();
+        if let Some((_, full)) = JOURNAL_MACROS.iter().find(|(m, _)| *m == journal) {
+            set(&mut entry, "journal", full.to_string());
+        }
+    }
+
+    let had_adsurl = entry.remove("adsurl").is_some();
+    entry.remove("adsnote");
+    if entry.get("url").is_none() && had_adsurl {
+        set(
+            &mut entry,
+            "url",
+            format!("https://ui.adsabs.harvard.edu/abs/{bibcode}"),
+        );
+    }
+    set(&mut entry, "bibcode", bibcode.to_string());
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Option<String> {
+        <Ads as Identifier<'_>>::parse(s).map(|a| a.bibcode)
+    }
+
+    #[test]
+    fn parse_bibcodes_and_urls() {
+        for (input, bibcode) in [
+            ("2019ApJ...882L..12P", "2019ApJ...882L..12P"),
+            ("ads:2016PhRvL.116f1102A", "2016PhRvL.116f1102A"),
+            ("bibcode: 2020A&A...641A...6P", "2020A&A...641A...6P"),
+            (
+                "https://ui.adsabs.harvard.edu/abs/2019ApJ...882L..12P/abstract",
+                "2019ApJ...882L..12P",
+            ),
+            (
+                "https://ui.adsabs.harvard.edu/abs/2020A%26A...641A...6P",
+                "2020A&A...641A...6P",
+            ),
+        ] {
+            assert_eq!(parse(input).as_deref(), Some(bibcode), "input {input}");
+        }
+    }
+
+    #[test]
+    fn parse_rejects_non_bibcodes() {
+        for bad in [
+            "2019ApJ...882L..12",
+            "ABCDEFGHIJKLMNOPQRS",
+            "2019abcdefghijklmnO",
+            "https://ui.adsabs.harvard.edu/search/q=foo",
+            "https://example.com/abs/2019ApJ...882L..12P",
+        ] {
+            assert!(parse(bad).is_none(), "should reject {bad}");
+        }
+    }
+
+    #[test]
+    fn ads_bibtex_expands_journal_and_keeps_bibcode() {
+        let body = r#"@ARTICLE{2019ApJ...882L..12P,
+       author = {{Paper}, A. and {Author}, B.},
+        title = "{A Letter}",
+      journal = {\apjl},
+         year = 2019,
+        month = sep,
+       volume = {882},
+       number = {2},
+          eid = {L12},
+          doi = {10.3847/2041-8213/ab3800},
+       adsurl = {https://ui.adsabs.harvard.edu/abs/2019ApJ...882L..12P},
+      adsnote = {Provided by the SAO/NASA Astrophysics Data System}
+}
+"#;
+        let entry = parse_ads_bibtex(body, "2019ApJ...882L..12P").unwrap();
+        let out = entry.to_biblatex_string();
+        assert!(
+            out.contains("journaltitle = {The Astrophysical Journal Letters}"),
+            "{out}"
+        );
+        assert!(out.contains("bibcode = {2019ApJ...882L..12P}"), "{out}");
+        assert!(
+            out.contains("url = {https://ui.adsabs.harvard.edu/abs/2019ApJ...882L..12P}"),
+            "{out}"
+        );
+        assert!(entry.get("adsnote").is_none() && entry.get("adsurl").is_none());
+    }
+}
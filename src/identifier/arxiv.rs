@@ -1,11 +1,18 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
 use anyhow::Context;
 use biblatex::{Bibliography, Entry};
 use once_cell::sync::Lazy;
+use owo_colors::OwoColorize;
 use quick_xml::Reader;
 use quick_xml::events::{BytesStart, Event};
 use regex::Regex;
 
-use crate::{identifier::Identifier, resolver::IdFamily};
+use crate::{
+    identifier::{Identifier, doi::Doi},
+    resolver::{self, IdFamily},
+};
 
 /// An arXiv identifier or URL, normalised to its canonical ID and optional version.
 pub struct Arxiv<'a> {
@@ -56,8 +63,9 @@ impl<'a> Identifier<'a> for Arxiv<'a> {
                 } else if comps.starts_with("find/")
                     || comps.starts_with("list/")
                     || comps.starts_with("search/")
+                    || comps.starts_with("a/")
                 {
-                    // Multi-item page: out of scope for core Identifier; signal unparseable here.
+                    // Multi-item page: handled by `ArxivQuery`.
                     return None;
                 } else {
                     // Unknown arXiv path
@@ -101,8 +109,12 @@ impl<'a> Identifier<'a> for Arxiv<'a> {
     }
 
     fn resolve(&self) -> anyhow::Result<Entry> {
-        let atom = fetch_atom(self.canonical_id)?;
-        let meta = parse_atom_entry(&atom, self.canonical_id)?;
+        let query = self.query_id();
+        let prefetched = PREFETCHED.lock().unwrap().get(&query).cloned();
+        let meta = match prefetched {
+            Some(meta) => meta,
+            None => parse_atom_entry(&fetch_atom(&query, 1)?, self.canonical_id)?,
+        };
 
         // Construct BibLaTeX string and parse into an Entry to avoid depending on biblatex internals.
         let bib = build_biblatex(&meta, self.canonical_id, self.version, self.legacy);
@@ -112,6 +124,20 @@ impl<'a> Identifier<'a> for Arxiv<'a> {
             bib.iter().next().cloned().ok_or_else(|| {
                 anyhow::anyhow!("empty bibliography from constructed arXiv record")
             })?;
+
+        // Opt-in: cite the published version, falling back to the preprint if its DOI fails.
+        if resolver::options().prefer_published
+            && let Some(doi) = meta.published_doi.as_deref()
+        {
+            match <Doi<'_> as Identifier<'_>>::parse(doi).map(|d| d.resolve()) {
+                Some(Ok(published)) => return Ok(merge_published(published, &entry)),
+                Some(Err(e)) => eprintln!(
+                    "{} arXiv:{query}: published version {doi} failed to resolve ({e}); kept the preprint",
+                    "!".yellow().bold()
+                ),
+                None => {}
+            }
+        }
         Ok(entry)
     }
 }
@@ -120,26 +146,423 @@ impl IdFamily for Arxiv<'_> {
     type For<'a> = Arxiv<'a>;
 }
 
+impl Arxiv<'_> {
+    /// The ID as sent to the API: versioned when the input pinned a version.
+    fn query_id(&self) -> String {
+        match self.version {
+            Some(v) => format!("{}v{}", self.canonical_id, v),
+            None => self.canonical_id.to_string(),
+        }
+    }
+}
+
+/// An arXiv listing, search or author page, standing for every paper it shows:
+///  - https://arxiv.org/list/cs.CL/recent (also `/new`, `/pastweek`, `/2401`, `/2024-01`, `/2024`)
+///  - https://arxiv.org/search/?query=bert&searchtype=title
+///  - https://arxiv.org/a/devlin_j_1
+///  - https://arxiv.org/find/cs/1/au:+Devlin_J/0/1/0/all/0/1 (legacy search)
+pub struct ArxivQuery {
+    target: QueryTarget,
+}
+
+enum QueryTarget {
+    /// An API `search_query`, and whether to list the newest submissions first.
+    Search { query: String, newest_first: bool },
+    /// An author identifier, listed through its Atom feed.
+    Author(String),
+}
+
+/// Most papers `bib arxiv search` prints unless told otherwise.
+pub const DEFAULT_MAX_RESULTS: usize = 50;
+
+/// Filters for `bib arxiv search`.
+#[derive(Default)]
+pub struct SearchFilters {
+    /// Categories, any of which a paper may be in (e.g., "cs.CL").
+    pub categories: Vec<String>,
+    /// Earliest submission date: `YYYY`, `YYYY-MM` or `YYYY-MM-DD`.
+    pub since: Option<String>,
+    /// Latest submission date, in the same forms.
+    pub until: Option<String>,
+}
+
+static CATEGORY_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-z-]+(?:\.[A-Za-z-]+)?$").unwrap());
+
+impl<'a> Identifier<'a> for ArxivQuery {
+    fn parse(identifier: &'a str) -> Option<Box<Self>> {
+        let url = url::Url::parse(identifier.trim()).ok()?;
+        let host = url.host_str()?.to_ascii_lowercase();
+        if host != "arxiv.org" && host != "www.arxiv.org" && host != "export.arxiv.org" {
+            return None;
+        }
+        let segments: Vec<&str> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+        let target = match segments.as_slice() {
+            ["list", category, rest @ ..] if CATEGORY_RE.is_match(category) => {
+                let cat = format!("cat:{category}");
+                let query = match rest {
+                    [] | ["recent" | "new" | "pastweek"] => cat,
+                    [period] => {
+                        let (from, to) = listing_period(period)?;
+                        format!("{cat} AND submittedDate:[{from} TO {to}]")
+                    }
+                    _ => return None,
+                };
+                QueryTarget::Search {
+                    query,
+                    newest_first: true,
+                }
+            }
+            ["a", author] => {
+                let author = author
+                    .trim_end_matches(".html")
+                    .trim_end_matches(".atom")
+                    .to_string();
+                if author.is_empty() {
+                    return None;
+                }
+                QueryTarget::Author(author)
+            }
+            ["search", ..] => {
+                let pairs: HashMap<String, String> = url.query_pairs().into_owned().collect();
+                let terms = pairs
+                    .get("query")
+                    .map(|q| q.trim())
+                    .filter(|q| !q.is_empty())?;
+                let field = match pairs.get("searchtype").map(String::as_str) {
+                    Some("title") => "ti",
+                    Some("author") => "au",
+                    Some("abstract") => "abs",
+                    Some("comments") => "co",
+                    Some("journal_ref") => "jr",
+                    _ => "all",
+                };
+                QueryTarget::Search {
+                    query: field_query(field, terms),
+                    newest_first: false,
+                }
+            }
+            ["find", rest @ ..] => {
+                // Legacy search paths carry terms as `au:+Devlin_J` or `ti:+some+words`.
+                let terms: Vec<String> = rest
+                    .iter()
+                    .filter_map(|seg| seg.split_once(':'))
+                    .filter(|(field, _)| matches!(*field, "au" | "ti" | "abs"))
+                    .map(|(field, words)| {
+                        let words = words.replace('+', " ");
+                        field_query(field, words.trim())
+                    })
+                    .collect();
+                if terms.is_empty() {
+                    return None;
+                }
+                QueryTarget::Search {
+                    query: terms.join(" AND "),
+                    newest_first: false,
+                }
+            }
+            _ => return None,
+        };
+        Some(Box::new(ArxivQuery { target }))
+    }
+
+    fn resolve(&self) -> anyhow::Result<Entry> {
+        Err(anyhow::anyhow!(
+            "arXiv listing and search pages contain several items; resolve them as a multi-item source"
+        ))
+    }
+
+    fn resolve_many(&self, limit: Option<usize>) -> anyhow::Result<Vec<Entry>> {
+        self.fetch(limit)
+    }
+}
+
+impl IdFamily for ArxivQuery {
+    type For<'a> = ArxivQuery;
+}
+
+impl ArxivQuery {
+    /// A search as run by `bib arxiv search`.
+    pub fn search(terms: &str, filters: &SearchFilters) -> anyhow::Result<Self> {
+        let mut parts = Vec::new();
+        if !terms.trim().is_empty() {
+            parts.push(field_query("all", terms.trim()));
+        }
+        if !filters.categories.is_empty() {
+            let cats: Vec<String> = filters
+                .categories
+                .iter()
+                .map(|c| format!("cat:{c}"))
+                .collect();
+            parts.push(format!("({})", cats.join(" OR ")));
+        }
+        if filters.since.is_some() || filters.until.is_some() {
+            let from = match &filters.since {
+                Some(d) => date_bound(d, false).ok_or_else(|| {
+                    anyhow::anyhow!("invalid date: {d} (expected YYYY[-MM[-DD]])")
+                })?,
+                None => "000001010000".to_string(),
+            };
+            let to = match &filters.until {
+                Some(d) => date_bound(d, true).ok_or_else(|| {
+                    anyhow::anyhow!("invalid date: {d} (expected YYYY[-MM[-DD]])")
+                })?,
+                None => "999912312359".to_string(),
+            };
+            parts.push(format!("submittedDate:[{from} TO {to}]"));
+        }
+        if parts.is_empty() {
+            return Err(anyhow::anyhow!("empty arXiv search"));
+        }
+        Ok(ArxivQuery {
+            target: QueryTarget::Search {
+                query: parts.join(" AND "),
+                // Without search terms there is no relevance to rank by.
+                newest_first: terms.trim().is_empty(),
+            },
+        })
+    }
+
+    /// Fetch up to `max_results` papers (every one with `None`), in the order arXiv lists them.
+    /// Searches are paged through [`PAGE_SIZE`] results at a time; author feeds come whole.
+    pub fn fetch(&self, max_results: Option<usize>) -> anyhow::Result<Vec<Entry>> {
+        let mut out = Vec::new();
+        loop {
+            let want = max_results.map_or(PAGE_SIZE, |max| (max - out.len()).min(PAGE_SIZE));
+            if !out.is_empty() {
+                // arXiv asks API clients to wait three seconds between calls.
+                std::thread::sleep(std::time::Duration::from_secs(3));
+            }
+            let (feed, paged) = self.fetch_page(out.len(), want)?;
+            let page = entries_from_feed(&feed, want)?;
+            let last = !paged || page.len() < want;
+            out.extend(page);
+            if last || max_results.is_some_and(|max| out.len() >= max) {
+                break;
+            }
+        }
+        if out.is_empty() {
+            return Err(anyhow::anyhow!("no arXiv papers found for {}", self.what()));
+        }
+        Ok(out)
+    }
+
+    /// One page of results from `start`, and whether the target can be paged at all.
+    fn fetch_page(
+        &self,
+        start: usize,
+        max_results: usize,
+    ) -> anyhow::Result<(Vec<ArxivMeta>, bool)> {
+        let (url, paged) = match &self.target {
+            QueryTarget::Search {
+                query,
+                newest_first,
+            } => {
+                let mut url = url::Url::parse("https://export.arxiv.org/api/query")?;
+                url.query_pairs_mut()
+                    .append_pair("search_query", query)
+                    .append_pair("start", &start.to_string())
+                    .append_pair("max_results", &max_results.to_string());
+                if *newest_first {
+                    url.query_pairs_mut()
+                        .append_pair("sortBy", "submittedDate")
+                        .append_pair("sortOrder", "descending");
+                }
+                (url.to_string(), true)
+            }
+            QueryTarget::Author(author) => (format!("https://arxiv.org/a/{author}.atom"), false),
+        };
+        let cfg = ureq::Agent::config_builder()
+            .timeout_connect(Some(std::time::Duration::from_secs(5)))
+            .timeout_global(Some(std::time::Duration::from_secs(30)))
+            .build();
+        let agent = ureq::Agent::new_with_config(cfg);
+        let body: String = agent
+            .get(&url)
+            .header(
+                "User-Agent",
+                "Mozilla/5.0 (compatible; bib/0.1; +https://arxiv.org)",
+            )
+            .call()
+            .with_context(|| format!("failed arXiv query for {}", self.what()))?
+            .into_body()
+            .read_to_string()
+            .context("failed to read Atom response body")?;
+        Ok((parse_atom_feed(&body)?, paged))
+    }
+
+    /// How the query is named in messages.
+    fn what(&self) -> String {
+        match &self.target {
+            QueryTarget::Search { query, .. } => query.clone(),
+            QueryTarget::Author(author) => format!("author {author}"),
+        }
+    }
+}
+
+/// Most results asked of the API at once, which keeps a page well within the request timeout.
+const PAGE_SIZE: usize = 500;
+
+/// Build an entry for each paper of a feed, latest version, up to `max` of them.
+fn entries_from_feed(feed: &[ArxivMeta], max: usize) -> anyhow::Result<Vec<Entry>> {
+    let mut out = Vec::new();
+    for meta in feed.iter().filter(|m| !m.id.is_empty()).take(max) {
+        let bib = build_biblatex(meta, &meta.id, None, meta.id.contains('/'));
+        let bib = Bibliography::parse(&bib)
+            .map_err(|e| anyhow::anyhow!("failed to parse constructed BibLaTeX: {e}"))?;
+        out.extend(bib.iter().next().cloned());
+    }
+    Ok(out)
+}
+
+/// `field:word` for each word, ANDed; words already carrying a field (`au:smith`) are kept as is.
+fn field_query(field: &str, terms: &str) -> String {
+    terms
+        .split_whitespace()
+        .map(|w| {
+            if w.contains(':') {
+                w.to_string()
+            } else {
+                format!("{field}:{w}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+/// The `submittedDate` range of a monthly or yearly listing: `YYMM` and `YY` as in the classic
+/// listing URLs, or `YYYY-MM` and `YYYY`. Four digits that do not end in a month are a year.
+fn listing_period(period: &str) -> Option<(String, String)> {
+    let century = |yy: &str| if yy >= "91" { "19" } else { "20" };
+    let digits = period.bytes().all(|b| b.is_ascii_digit());
+    let date = match period.len() {
+        2 if digits => format!("{}{period}", century(period)),
+        4 if digits && (1..=12).contains(&period[2..].parse::<u32>().unwrap_or(0)) => {
+            let (yy, mm) = period.split_at(2);
+            format!("{}{yy}-{mm}", century(yy))
+        }
+        _ => period.to_string(),
+    };
+    Some((date_bound(&date, false)?, date_bound(&date, true)?))
+}
+
+/// An API date stamp (`YYYYMMDDHHMM`) for the start or end of `YYYY`, `YYYY-MM` or `YYYY-MM-DD`.
+fn date_bound(date: &str, end: bool) -> Option<String> {
+    static DATE_RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^(?P<y>\d{4})(?:-(?P<m>0[1-9]|1[0-2])(?:-(?P<d>0[1-9]|[12]\d|3[01]))?)?$")
+            .unwrap()
+    });
+    let c = DATE_RE.captures(date.trim())?;
+    let year = &c["y"];
+    let (month, day) = match (c.name("m"), c.name("d")) {
+        (Some(m), Some(d)) => (m.as_str().to_string(), d.as_str().to_string()),
+        (Some(m), None) if end => {
+            let last = match m.as_str() {
+                "02" => {
+                    let y: u32 = year.parse().ok()?;
+                    let leap =
+                        y.is_multiple_of(4) && !y.is_multiple_of(100) || y.is_multiple_of(400);
+                    if leap { "29" } else { "28" }
+                }
+                "04" | "06" | "09" | "11" => "30",
+                _ => "31",
+            };
+            (m.as_str().to_string(), last.to_string())
+        }
+        (Some(m), None) => (m.as_str().to_string(), "01".to_string()),
+        (None, _) if end => ("12".to_string(), "31".to_string()),
+        (None, _) => ("01".to_string(), "01".to_string()),
+    };
+    let time = if end { "2359" } else { "0000" };
+    Some(format!("{year}{month}{day}{time}"))
+}
+
 /// Normalised arXiv Atom metadata we care about.
+#[derive(Clone, Default)]
 struct ArxivMeta {
+    /// Canonical ID from the entry's `<id>`, empty if the feed did not carry one.
+    id: String,
+    /// Version the entry describes, from the entry's `<id>`.
+    latest_version: Option<String>,
     title: String,
     summary: String,
+    /// Submission date of v1.
+    published: Option<String>,
+    /// Submission date of the version the entry describes.
     updated: Option<String>,
     authors: Vec<String>,
+    /// Affiliations of each author, by position in `authors`; may be shorter than it.
+    affiliations: Vec<Vec<String>>,
     published_doi: Option<String>,
     primary_class: Option<String>,
     categories: Vec<String>,
     comments: Vec<String>,
+    /// Free-text `<arxiv:journal_ref>`, e.g. "Phys. Rev. D 76, 044016 (2007)".
+    journal_ref: Option<String>,
+}
+
+/// Atom metadata fetched ahead of time by [`prefetch`], keyed by the queried ID (with `vN` if pinned).
+static PREFETCHED: Lazy<Mutex<HashMap<String, ArxivMeta>>> = Lazy::new(Default::default);
+
+/// Number of IDs per Atom query; the API documents `id_list` for up to a few hundred IDs.
+const BATCH_SIZE: usize = 100;
+
+/// Fetch the Atom metadata of every arXiv identifier in `identifiers` with batched queries, so
+/// resolving them afterwards costs no further requests. Failures are ignored here: the affected
+/// identifiers fall back to their own request in `resolve`.
+pub fn prefetch(identifiers: &[&str]) {
+    let mut queries: Vec<String> = identifiers
+        .iter()
+        .filter_map(|s| <Arxiv<'_> as Identifier<'_>>::parse(s))
+        .map(|a| a.query_id())
+        .collect();
+    queries.sort();
+    queries.dedup();
+    // A single ID gains nothing from batching.
+    if queries.len() < 2 {
+        return;
+    }
+    for (i, chunk) in queries.chunks(BATCH_SIZE).enumerate() {
+        if i > 0 {
+            // arXiv asks API clients to wait three seconds between calls.
+            std::thread::sleep(std::time::Duration::from_secs(3));
+        }
+        let Ok(entries) =
+            fetch_atom(&chunk.join(","), chunk.len()).and_then(|x| parse_atom_feed(&x))
+        else {
+            continue;
+        };
+        let mut cache = PREFETCHED.lock().unwrap();
+        for query in chunk {
+            if let Some(meta) = match_entry(&entries, query) {
+                cache.insert(query.clone(), meta.clone());
+            }
+        }
+    }
 }
 
-fn fetch_atom(id: &str) -> anyhow::Result<String> {
+/// Find the entry answering `query`: a pinned version must match exactly; an unpinned ID takes
+/// the latest version present.
+fn match_entry<'m>(entries: &'m [ArxivMeta], query: &str) -> Option<&'m ArxivMeta> {
+    let (id, version) = split_version(query);
+    entries
+        .iter()
+        .filter(|m| m.id == id)
+        .filter(|m| version.is_none() || m.latest_version.as_deref() == version)
+        .max_by_key(|m| {
+            m.latest_version
+                .as_deref()
+                .and_then(|v| v.parse::<u32>().ok())
+        })
+}
+
+fn fetch_atom(id_list: &str, max_results: usize) -> anyhow::Result<String> {
     let mut url = url::Url::parse("https://export.arxiv.org/api/query")?;
     url.query_pairs_mut()
-        .append_pair("id_list", id)
-        .append_pair("max_results", "1");
+        .append_pair("id_list", id_list)
+        .append_pair("max_results", &max_results.to_string());
     let cfg = ureq::Agent::config_builder()
         .timeout_connect(Some(std::time::Duration::from_secs(5)))
-        .timeout_global(Some(std::time::Duration::from_secs(10)))
+        .timeout_global(Some(std::time::Duration::from_secs(30)))
         .build();
     let agent = ureq::Agent::new_with_config(cfg);
     let body: String = agent
@@ -149,29 +572,35 @@ fn fetch_atom(id: &str) -> anyhow::Result<String> {
             "Mozilla/5.0 (compatible; bib/0.1; +https://arxiv.org)",
         )
         .call()
-        .with_context(|| format!("failed Atom request for arXiv id {id}"))?
+        .with_context(|| format!("failed Atom request for arXiv id {id_list}"))?
         .into_body()
         .read_to_string()
         .context("failed to read Atom response body")?;
     Ok(body)
 }
 
+/// Parse the Atom entry for `id`, or the first entry of a single-ID response.
 fn parse_atom_entry(xml: &str, id: &str) -> anyhow::Result<ArxivMeta> {
+    let mut entries = parse_atom_feed(xml)?;
+    if entries.is_empty() {
+        return Err(anyhow::anyhow!("no Atom entry found for arXiv id {id}"));
+    }
+    let pos = entries.iter().position(|m| m.id == id).unwrap_or(0);
+    Ok(entries.swap_remove(pos))
+}
+
+/// Parse every `<entry>` of an Atom feed, in feed order.
+fn parse_atom_feed(xml: &str) -> anyhow::Result<Vec<ArxivMeta>> {
     let mut reader = Reader::from_str(xml);
     reader.config_mut().trim_text(true);
 
     let mut in_entry = false;
     let mut in_author = false;
     let mut cur_text = String::new();
+    let mut author_affiliations: Vec<String> = Vec::new();
 
-    let mut title = String::new();
-    let mut summary = String::new();
-    let mut updated = None;
-    let mut authors = Vec::new();
-    let mut published_doi: Option<String> = None;
-    let mut primary_class: Option<String> = None;
-    let mut categories: Vec<String> = Vec::new();
-    let mut comments: Vec<String> = Vec::new();
+    let mut entries = Vec::new();
+    let mut cur = ArxivMeta::default();
 
     fn is_local(name: &[u8], target: &str) -> bool {
         // Compare local name ignoring namespace prefixes.
@@ -189,56 +618,76 @@ fn parse_atom_entry(xml: &str, id: &str) -> anyhow::Result<ArxivMeta> {
             Ok(Event::Start(e)) => {
                 if is_local(e.name().as_ref(), "entry") {
                     in_entry = true;
+                    cur = ArxivMeta::default();
                 } else if in_entry && is_local(e.name().as_ref(), "author") {
                     in_author = true;
-                } else if in_entry && is_local(e.name().as_ref(), "primary_category") {
-                    if let Some(val) = get_attr_value(&e, b"term") {
-                        primary_class = Some(val);
-                    }
-                } else if in_entry && is_local(e.name().as_ref(), "category") {
-                    if let Some(val) = get_attr_value(&e, b"term") {
-                        categories.push(val);
-                    }
-                } else if in_entry && is_local(e.name().as_ref(), "link") {
-                    let rel = get_attr_value(&e, b"rel");
-                    if matches!(rel.as_deref(), Some("related"))
-                        && let Some(href) = get_attr_value(&e, b"href")
-                        && let Some(doi) = extract_doi_from_url(&href)
-                    {
-                        published_doi.get_or_insert(doi);
-                    }
+                    author_affiliations.clear();
+                } else if in_entry {
+                    read_entry_attrs(&e, &mut cur);
                 }
                 cur_text.clear();
             }
             Ok(Event::End(e)) => {
                 if is_local(e.name().as_ref(), "entry") {
-                    break; // finished first entry
+                    in_entry = false;
+                    let m = std::mem::take(&mut cur);
+                    if !(m.title.is_empty() && m.summary.is_empty() && m.authors.is_empty()) {
+                        entries.push(m);
+                    }
                 } else if is_local(e.name().as_ref(), "author") {
                     in_author = false;
+                    // Line affiliations up with the author just read.
+                    if in_entry && !author_affiliations.is_empty() && !cur.authors.is_empty() {
+                        cur.affiliations.resize(cur.authors.len(), Vec::new());
+                        cur.affiliations[cur.authors.len() - 1] =
+                            std::mem::take(&mut author_affiliations);
+                    }
+                } else if in_entry && in_author && is_local(e.name().as_ref(), "affiliation") {
+                    // <arxiv:affiliation>
+                    let a = normalize_ws(&cur_text);
+                    if !a.is_empty() {
+                        author_affiliations.push(a);
+                    }
+                } else if in_entry && is_local(e.name().as_ref(), "id") {
+                    // <id>http://arxiv.org/abs/1810.04805v2</id>
+                    if let Some((id, version)) = split_abs_url(cur_text.trim()) {
+                        cur.id = id;
+                        cur.latest_version = version;
+                    }
                 } else if in_entry && is_local(e.name().as_ref(), "title") {
-                    title = normalize_ws(&cur_text);
+                    cur.title = normalize_ws(&cur_text);
                 } else if in_entry && is_local(e.name().as_ref(), "summary") {
-                    summary = cur_text.trim().to_string();
+                    cur.summary = cur_text.trim().to_string();
                 } else if in_entry && is_local(e.name().as_ref(), "updated") {
                     let t = cur_text.trim();
                     if !t.is_empty() {
-                        updated = Some(t.to_string());
+                        cur.updated = Some(t.to_string());
+                    }
+                } else if in_entry && is_local(e.name().as_ref(), "published") {
+                    let t = cur_text.trim();
+                    if !t.is_empty() {
+                        cur.published = Some(t.to_string());
                     }
                 } else if in_entry && in_author && is_local(e.name().as_ref(), "name") {
                     let n = cur_text.trim();
                     if !n.is_empty() {
-                        authors.push(n.to_string());
+                        cur.authors.push(n.to_string());
                     }
                 } else if in_entry && is_local(e.name().as_ref(), "doi") {
                     // <arxiv:doi>
                     let d = cur_text.trim();
                     if !d.is_empty() {
-                        published_doi.get_or_insert(d.to_string());
+                        cur.published_doi.get_or_insert(d.to_string());
                     }
                 } else if in_entry && is_local(e.name().as_ref(), "comment") {
                     let c = cur_text.trim();
                     if !c.is_empty() {
-                        comments.push(c.to_string());
+                        cur.comments.push(c.to_string());
+                    }
+                } else if in_entry && is_local(e.name().as_ref(), "journal_ref") {
+                    let j = normalize_ws(&cur_text);
+                    if !j.is_empty() {
+                        cur.journal_ref = Some(j);
                     }
                 }
                 cur_text.clear();
@@ -249,24 +698,8 @@ fn parse_atom_entry(xml: &str, id: &str) -> anyhow::Result<ArxivMeta> {
             Ok(Event::CData(t)) => {
                 cur_text.push_str(&String::from_utf8_lossy(t.as_ref()));
             }
-            Ok(Event::Empty(e)) => {
-                if in_entry && is_local(e.name().as_ref(), "primary_category") {
-                    if let Some(val) = get_attr_value(&e, b"term") {
-                        primary_class = Some(val);
-                    }
-                } else if in_entry && is_local(e.name().as_ref(), "category") {
-                    if let Some(val) = get_attr_value(&e, b"term") {
-                        categories.push(val);
-                    }
-                } else if in_entry && is_local(e.name().as_ref(), "link") {
-                    let rel = get_attr_value(&e, b"rel");
-                    if matches!(rel.as_deref(), Some("related"))
-                        && let Some(href) = get_attr_value(&e, b"href")
-                        && let Some(doi) = extract_doi_from_url(&href)
-                    {
-                        published_doi.get_or_insert(doi);
-                    }
-                }
+            Ok(Event::Empty(e)) if in_entry => {
+                read_entry_attrs(&e, &mut cur);
             }
             Err(e) => return Err(anyhow::anyhow!("XML parse error: {e}")),
             _ => {}
@@ -274,20 +707,182 @@ fn parse_atom_entry(xml: &str, id: &str) -> anyhow::Result<ArxivMeta> {
         buf.clear();
     }
 
-    if title.is_empty() && summary.is_empty() && authors.is_empty() {
-        return Err(anyhow::anyhow!("no Atom entry found for arXiv id {id}"));
+    Ok(entries)
+}
+
+/// Pick up the attribute-only children of an entry: categories and the related DOI link.
+fn read_entry_attrs(e: &BytesStart<'_>, cur: &mut ArxivMeta) {
+    let name = e.local_name();
+    match name.as_ref() {
+        b"primary_category" => {
+            if let Some(val) = get_attr_value(e, b"term") {
+                cur.primary_class = Some(val);
+            }
+        }
+        b"category" => {
+            if let Some(val) = get_attr_value(e, b"term") {
+                cur.categories.push(val);
+            }
+        }
+        b"link" => {
+            let rel = get_attr_value(e, b"rel");
+            if matches!(rel.as_deref(), Some("related"))
+                && let Some(href) = get_attr_value(e, b"href")
+                && let Some(doi) = extract_doi_from_url(&href)
+            {
+                cur.published_doi.get_or_insert(doi);
+            }
+        }
+        _ => {}
     }
+}
 
-    Ok(ArxivMeta {
-        title,
-        summary,
-        updated,
-        authors,
-        published_doi,
-        primary_class,
-        categories,
-        comments,
-    })
+/// Split an abstract URL (`http://arxiv.org/abs/1810.04805v2`) into the canonical ID and version.
+fn split_abs_url(url: &str) -> Option<(String, Option<String>)> {
+    let rest = url.split_once("/abs/")?.1;
+    let (id, version) = split_version(rest);
+    (!id.is_empty()).then(|| (id.to_string(), version.map(str::to_string)))
+}
+
+/// Split a trailing `vN` off an arXiv ID.
+fn split_version(id: &str) -> (&str, Option<&str>) {
+    static VERSION_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(.+?)v(\d+)$").unwrap());
+    match VERSION_RE.captures(id) {
+        Some(c) => (c.get(1).unwrap().as_str(), Some(c.get(2).unwrap().as_str())),
+        None => (id, None),
+    }
+}
+
+/// Download the e-print source of the paper behind `identifier` (`https://arxiv.org/e-print/ID`).
+///
+/// Returns the ID as queried (with `vN` if the input pinned a version) and the raw bytes, which
+/// are usually a gzipped tarball, sometimes a single gzipped TeX file, or a PDF when the authors
+/// submitted no source.
+pub fn fetch_eprint(identifier: &str) -> anyhow::Result<(String, Vec<u8>)> {
+    let arxiv = <Arxiv<'_> as Identifier<'_>>::parse(identifier)
+        .ok_or_else(|| anyhow::anyhow!("not an arXiv identifier: {identifier}"))?;
+    let id = arxiv.query_id();
+    let cfg = ureq::Agent::config_builder()
+        .timeout_connect(Some(std::time::Duration::from_secs(5)))
+        .timeout_global(Some(std::time::Duration::from_secs(120)))
+        .build();
+    let agent = ureq::Agent::new_with_config(cfg);
+    let bytes = agent
+        .get(&format!("https://arxiv.org/e-print/{id}"))
+        .header(
+            "User-Agent",
+            "Mozilla/5.0 (compatible; bib/0.1; +https://arxiv.org)",
+        )
+        .call()
+        .with_context(|| format!("failed to download e-print source for arXiv id {id}"))?
+        .into_body()
+        .with_config()
+        .limit(200 * 1024 * 1024)
+        .read_to_vec()
+        .context("failed to read e-print source")?;
+    Ok((id, bytes))
+}
+
+/// One version in a paper's submission history.
+pub struct VersionInfo {
+    pub version: u32,
+    /// Submission date, as `YYYY-MM-DD`.
+    pub date: String,
+    /// Size as shown by arXiv, e.g. "227 KB".
+    pub size: Option<String>,
+    /// The author comment attached to this version.
+    pub comment: Option<String>,
+}
+
+/// The submission history of the paper behind `identifier`, oldest version first.
+///
+/// Dates and sizes come from the abstract page; comments from one Atom query for all versions.
+pub fn versions(identifier: &str) -> anyhow::Result<Vec<VersionInfo>> {
+    let arxiv = <Arxiv<'_> as Identifier<'_>>::parse(identifier)
+        .ok_or_else(|| anyhow::anyhow!("not an arXiv identifier: {identifier}"))?;
+    let id = arxiv.canonical_id;
+    let url = format!("https://arxiv.org/abs/{id}");
+    let cfg = ureq::Agent::config_builder()
+        .timeout_connect(Some(std::time::Duration::from_secs(5)))
+        .timeout_global(Some(std::time::Duration::from_secs(15)))
+        .build();
+    let agent = ureq::Agent::new_with_config(cfg);
+    let html: String = agent
+        .get(&url)
+        .header(
+            "User-Agent",
+            "Mozilla/5.0 (compatible; bib/0.1; +https://arxiv.org)",
+        )
+        .call()
+        .with_context(|| format!("failed to fetch abstract page for arXiv id {id}"))?
+        .into_body()
+        .read_to_string()
+        .context("failed to read arXiv abstract page")?;
+    let mut history = parse_submission_history(&html);
+    if history.is_empty() {
+        return Err(anyhow::anyhow!(
+            "no submission history found for arXiv id {id}"
+        ));
+    }
+
+    let queries: Vec<String> = history
+        .iter()
+        .map(|v| format!("{id}v{}", v.version))
+        .collect();
+    let entries = parse_atom_feed(&fetch_atom(&queries.join(","), queries.len())?)?;
+    for (v, query) in history.iter_mut().zip(&queries) {
+        v.comment = match_entry(&entries, query)
+            .map(|m| m.comments.join("; "))
+            .filter(|c| !c.is_empty());
+    }
+    Ok(history)
+}
+
+/// Parse the "Submission history" block of an abstract page, e.g.
+/// `<strong>[v2]</strong> Fri, 24 May 2019 20:37:26 UTC (309 KB)`.
+fn parse_submission_history(html: &str) -> Vec<VersionInfo> {
+    static VERSION_RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"\[v(?P<v>\d+)\]\s*(?:</a>)?\s*</strong>\s*[A-Z][a-z]{2},\s+(?P<day>\d{1,2})\s+(?P<mon>[A-Z][a-z]{2})\s+(?P<year>\d{4})[^(<]*(?:\((?P<size>[^)]*)\))?",
+        )
+        .unwrap()
+    });
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let html = html
+        .find("Submission history")
+        .map_or(html, |pos| &html[pos..]);
+    VERSION_RE
+        .captures_iter(html)
+        .filter_map(|c| {
+            let month = MONTHS.iter().position(|m| *m == &c["mon"])? + 1;
+            Some(VersionInfo {
+                version: c["v"].parse().ok()?,
+                date: format!("{}-{:02}-{:0>2}", &c["year"], month, &c["day"]),
+                size: c.name("size").map(|m| m.as_str().trim().to_string()),
+                comment: None,
+            })
+        })
+        .collect()
+}
+
+/// Combine the published version of a paper with its arXiv record: the venue metadata comes from
+/// the publisher, the `eprint` fields from arXiv, and the abstract from arXiv if the publisher has none.
+fn merge_published(mut published: Entry, preprint: &Entry) -> Entry {
+    for key in ["eprint", "eprinttype", "eprintclass", "eprintversion"] {
+        if let Some(value) = preprint.fields.get(key) {
+            published.fields.insert(key.to_string(), value.clone());
+        }
+    }
+    for key in ["abstract", "author", "title"] {
+        if !published.fields.contains_key(key)
+            && let Some(value) = preprint.fields.get(key)
+        {
+            published.fields.insert(key.to_string(), value.clone());
+        }
+    }
+    published
 }
 
 fn get_attr_value(e: &BytesStart<'_>, key: &[u8]) -> Option<String> {
@@ -329,10 +924,6 @@ fn build_biblatex(meta: &ArxivMeta, id: &str, version: Option<&str>, legacy: boo
     let key = format!("arXiv:{}", id);
     let url = format!("https://arxiv.org/abs/{}", id);
     // PDF URL derivable from ID; omitted in BibLaTeX fields.
-    let doi = meta
-        .published_doi
-        .clone()
-        .unwrap_or_else(|| format!("10.48550/arXiv.{}", id));
 
     // Map categories to human-readable keywords.
     let mut tags: Vec<String> = Vec::new();
@@ -351,19 +942,80 @@ fn build_biblatex(meta: &ArxivMeta, id: &str, version: Option<&str>, legacy: boo
         tags.push(label);
     }
 
+    // A parseable journal reference turns the preprint into the published work it describes.
+    let venue = meta.journal_ref.as_deref().and_then(parse_journal_ref);
+    let entry_type = match &venue {
+        Some(v) if v.proceedings => "inproceedings",
+        Some(_) => "article",
+        None => "online",
+    };
+    // arXiv's own DOI names the preprint, so a published work only gets its publisher's DOI.
+    let doi = meta
+        .published_doi
+        .clone()
+        .or_else(|| venue.is_none().then(|| format!("10.48550/arXiv.{}", id)));
+
     let mut fields = Vec::new();
     fields.push(format!("title = {{{}}}", meta.title));
     if !meta.summary.trim().is_empty() {
         fields.push(format!("abstract = {{{}}}", meta.summary));
     }
-    if let Some(d) = &meta.updated {
-        fields.push(format!("date = {{{}}}", d));
+    match &venue {
+        // `date` would shadow the venue's `year`, so only the arXiv record carries it.
+        Some(v) => fields.push(format!("year = {{{}}}", v.year)),
+        // The submission date of the requested version: `published` is v1's, and `updated` that
+        // of the version the entry describes (the pinned one, or else the latest).
+        None => {
+            let date = match version {
+                Some("1") => meta.published.as_ref().or(meta.updated.as_ref()),
+                _ => meta.updated.as_ref(),
+            };
+            if let Some(d) = date {
+                fields.push(format!("date = {{{}}}", d.get(..10).unwrap_or(d)));
+            }
+        }
     }
     if !meta.authors.is_empty() {
         let authors = meta.authors.join(" and ");
         fields.push(format!("author = {{{}}}", authors));
     }
-    fields.push(format!("doi = {{{}}}", doi));
+    // Affiliations as a named data annotation on the author list (biblatex/biber `+an:` syntax).
+    let affiliations: Vec<String> = meta
+        .affiliations
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| !a.is_empty())
+        .map(|(i, a)| {
+            let a = a.join(", ").replace('"', "'").replace(';', ",");
+            format!("{}=\"{}\"", i + 1, a.replace(['{', '}'], ""))
+        })
+        .collect();
+    if !affiliations.is_empty() {
+        fields.push(format!(
+            "author+an:affiliation = {{{}}}",
+            affiliations.join("; ")
+        ));
+    }
+    if let Some(v) = &venue {
+        let venue_field = if v.proceedings {
+            "booktitle"
+        } else {
+            "journaltitle"
+        };
+        fields.push(format!("{} = {{{}}}", venue_field, v.venue));
+        if let Some(vol) = &v.volume {
+            fields.push(format!("volume = {{{}}}", vol));
+        }
+        if let Some(num) = &v.number {
+            fields.push(format!("number = {{{}}}", num));
+        }
+        if let Some(pages) = &v.pages {
+            fields.push(format!("pages = {{{}}}", pages));
+        }
+    }
+    if let Some(doi) = doi {
+        fields.push(format!("doi = {{{}}}", doi));
+    }
     fields.push(format!("url = {{{}}}", url));
     fields.push("eprinttype = {arXiv}".to_string());
     fields.push(format!("eprint = {{{}}}", id));
@@ -375,28 +1027,33 @@ fn build_biblatex(meta: &ArxivMeta, id: &str, version: Option<&str>, legacy: boo
     if let Some(v) = version {
         fields.push(format!("eprintversion = {{{}}}", v));
     }
-    // Venue-like fields for arXiv-only (these do not overwrite DOI-derived entries since we don't merge):
-    fields.push("publisher = {arXiv}".to_string());
-    fields.push(format!("number = {{{}}}", key));
+    // Venue-like fields for arXiv-only records (`--prefer-published` swaps in the DOI record instead):
+    if venue.is_none() {
+        fields.push("publisher = {arXiv}".to_string());
+        fields.push(format!("number = {{{}}}", key));
+    }
     if !tags.is_empty() {
         fields.push(format!("keywords = {{{}}}", tags.join(", ")));
     }
-    if !meta.comments.is_empty() {
-        // Concatenate comments as a single note with Comment: prefix for each.
-        let mut note = String::new();
-        for (i, c) in meta.comments.iter().enumerate() {
-            if i > 0 {
-                note.push_str("; ");
-            }
-            note.push_str("Comment: ");
-            note.push_str(c);
-        }
-        fields.push(format!("note = {{{}}}", note));
+    // Concatenate comments as a single note with Comment: prefix for each, and keep a
+    // journal reference we could not take apart so the venue is not lost.
+    let mut notes: Vec<String> = meta
+        .comments
+        .iter()
+        .map(|c| format!("Comment: {}", c))
+        .collect();
+    if venue.is_none()
+        && let Some(j) = &meta.journal_ref
+    {
+        notes.push(format!("Journal reference: {}", j));
+    }
+    if !notes.is_empty() {
+        fields.push(format!("note = {{{}}}", notes.join("; ")));
     }
     // Include a PDF hint via howpublished or addenda? BibLaTeX has no attachment; omit but keep URL.
     // Build the entry.
     let mut out = String::new();
-    out.push_str(&format!("@online{{{},\n", key));
+    out.push_str(&format!("@{}{{{},\n", entry_type, key));
     for f in fields {
         out.push_str("    ");
         out.push_str(&f);
@@ -406,6 +1063,120 @@ fn build_biblatex(meta: &ArxivMeta, id: &str, version: Option<&str>, legacy: boo
     out
 }
 
+/// Venue details recovered from a free-text `<arxiv:journal_ref>`.
+#[derive(Debug, PartialEq)]
+struct JournalRef {
+    venue: String,
+    proceedings: bool,
+    volume: Option<String>,
+    number: Option<String>,
+    pages: Option<String>,
+    year: String,
+}
+
+/// A page or article number, optionally a range (e.g., "044016", "L1-L4", "436–444").
+const PAGES: &str = r"[A-Za-z]?\d+[A-Za-z]?(?:\s*[-–]+\s*[A-Za-z]?\d+[A-Za-z]?)?";
+
+/// Parse the common shapes of arXiv journal references:
+///  - "Phys. Rev. D 76, 044016 (2007)", "J. Chem. Phys. 150(3):034101 (2019)"
+///  - "Nature 521 (2015) 436-444"
+///  - "Proceedings of NeurIPS 2017, pp. 5998-6008", "ICML 2020"
+///
+/// Returns None when the shape is not recognised or no year can be found.
+fn parse_journal_ref(raw: &str) -> Option<JournalRef> {
+    // venue volume(number)?, pages (year)
+    static VOL_PAGES_YEAR: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(&format!(
+            r"^(?P<venue>.*?[A-Za-z.)])\s*,?\s+(?:[Vv]ol\.?\s*)?(?P<vol>\d+)\s*(?:\((?P<num>[^)]+)\))?\s*[,:]\s*(?:pp?\.\s*)?(?P<pages>{PAGES})\s*,?\s*\((?P<year>(?:19|20)\d{{2}})\)$"
+        ))
+        .unwrap()
+    });
+    // venue volume (year) pages
+    static VOL_YEAR_PAGES: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(&format!(
+            r"^(?P<venue>.*?[A-Za-z.)])\s*,?\s+(?:[Vv]ol\.?\s*)?(?P<vol>\d+)\s*\((?P<year>(?:19|20)\d{{2}})\)\s*,?\s*(?:pp?\.\s*)?(?P<pages>{PAGES})$"
+        ))
+        .unwrap()
+    });
+    static LOOSE_PAGES: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(&format!(r",?\s*\b(?:pp?\.|pages)\s*(?P<pages>{PAGES})")).unwrap()
+    });
+    static LOOSE_VOLUME: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r",?\s*\b[Vv]ol(?:ume)?\.?\s*(?P<vol>\d+)").unwrap());
+    static YEAR: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(?:19|20)\d{2}\b").unwrap());
+
+    let s = raw.trim().trim_end_matches(['.', ';', ' ']);
+    let s = s
+        .strip_prefix("In ")
+        .or_else(|| s.strip_prefix("in "))
+        .unwrap_or(s)
+        .trim();
+
+    if let Some(c) = VOL_PAGES_YEAR
+        .captures(s)
+        .or_else(|| VOL_YEAR_PAGES.captures(s))
+    {
+        let venue = c["venue"].trim_end_matches(',').trim().to_string();
+        return Some(JournalRef {
+            proceedings: is_proceedings(&venue),
+            venue,
+            volume: Some(c["vol"].to_string()),
+            number: c.name("num").map(|m| m.as_str().trim().to_string()),
+            pages: Some(normalize_pages(&c["pages"])),
+            year: c["year"].to_string(),
+        });
+    }
+
+    // Conference references rarely follow a fixed layout: take the year and any explicit
+    // pages/volume, and keep the rest as the proceedings title.
+    if !is_proceedings(s) {
+        return None;
+    }
+    let year = YEAR.find_iter(s).last()?.as_str().to_string();
+    let pages = LOOSE_PAGES
+        .captures(s)
+        .map(|c| normalize_pages(&c["pages"]));
+    let volume = LOOSE_VOLUME.captures(s).map(|c| c["vol"].to_string());
+    let rest = LOOSE_PAGES.replace_all(s, "");
+    let rest = LOOSE_VOLUME.replace_all(&rest, "");
+    let venue = rest
+        .trim()
+        .trim_end_matches([',', ';', ':', '.', ' '])
+        .to_string();
+    if venue.is_empty() {
+        return None;
+    }
+    Some(JournalRef {
+        venue,
+        proceedings: true,
+        volume,
+        number: None,
+        pages,
+        year,
+    })
+}
+
+/// Whether a venue looks like a conference or workshop rather than a journal.
+fn is_proceedings(venue: &str) -> bool {
+    static KEYWORDS: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"(?i)\b(?:proceedings\s+of|conference|conf\.|workshop|symposium|colloquium)")
+            .unwrap()
+    });
+    static ACRONYMS: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(
+            r"\b(?:NeurIPS|NIPS|ICML|ICLR|CVPR|ICCV|ECCV|ACL|EMNLP|NAACL|EACL|COLING|AAAI|IJCAI|KDD|SIGIR|SIGMOD|VLDB|STOC|FOCS|SODA|ICALP|CHI|SIGGRAPH|INTERSPEECH|ICASSP|AISTATS|UAI|COLT|CoRL|ICRA|IROS)\b",
+        )
+        .unwrap()
+    });
+    KEYWORDS.is_match(venue) || ACRONYMS.is_match(venue)
+}
+
+/// Use BibLaTeX's double dash for page ranges.
+fn normalize_pages(pages: &str) -> String {
+    static DASHES: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s*[-–]+\s*").unwrap());
+    DASHES.replace_all(pages.trim(), "--").into_owned()
+}
+
 fn primary_class_of(term: &str) -> Option<&'static str> {
     // For dot-coded categories, take the archive before the dot.
     let head = term.split('.').next().unwrap_or(term);
@@ -717,4 +1488,340 @@ mod tests {
         let lbl2 = map_category("math-ph", None).unwrap();
         assert_eq!(lbl2, "Mathematical Physics");
     }
+
+    fn meta_with_ref(journal_ref: Option<&str>) -> ArxivMeta {
+        ArxivMeta {
+            title: "A Paper".into(),
+            summary: String::new(),
+            updated: Some("2007-08-01T00:00:00Z".into()),
+            authors: vec!["Jane Doe".into()],
+            published_doi: None,
+            primary_class: Some("gr-qc".into()),
+            categories: vec!["gr-qc".into()],
+            comments: vec![],
+            journal_ref: journal_ref.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parse_journal_ref_shapes() {
+        let j = parse_journal_ref("Phys. Rev. D 76, 044016 (2007)").unwrap();
+        assert_eq!(j.venue, "Phys. Rev. D");
+        assert!(!j.proceedings);
+        assert_eq!(j.volume.as_deref(), Some("76"));
+        assert_eq!(j.pages.as_deref(), Some("044016"));
+        assert_eq!(j.year, "2007");
+
+        let j = parse_journal_ref("Nature 521 (2015) 436-444").unwrap();
+        assert_eq!(j.venue, "Nature");
+        assert_eq!(j.volume.as_deref(), Some("521"));
+        assert_eq!(j.pages.as_deref(), Some("436--444"));
+        assert_eq!(j.year, "2015");
+
+        let j = parse_journal_ref("J. Chem. Phys. 150(3):034101 (2019).").unwrap();
+        assert_eq!(j.venue, "J. Chem. Phys.");
+        assert_eq!(j.number.as_deref(), Some("3"));
+
+        let j = parse_journal_ref("Proceedings of NeurIPS 2017, pp. 5998-6008").unwrap();
+        assert!(j.proceedings);
+        assert_eq!(j.venue, "Proceedings of NeurIPS 2017");
+        assert_eq!(j.pages.as_deref(), Some("5998--6008"));
+        assert_eq!(j.year, "2017");
+
+        assert!(parse_journal_ref("To appear somewhere").is_none());
+        assert!(parse_journal_ref("Workshop on Things").is_none());
+    }
+
+    #[test]
+    fn journal_ref_switches_entry_type() {
+        let meta = meta_with_ref(Some("Phys. Rev. D 76, 044016 (2007)"));
+        let bib = build_biblatex(&meta, "0704.0001", None, false);
+        assert!(bib.starts_with("@article{arXiv:0704.0001,"), "{bib}");
+        assert!(bib.contains("journaltitle = {Phys. Rev. D}"), "{bib}");
+        assert!(bib.contains("volume = {76}"), "{bib}");
+        assert!(bib.contains("year = {2007}"), "{bib}");
+        assert!(!bib.contains("publisher = {arXiv}"), "{bib}");
+        assert!(!bib.contains("date ="), "{bib}");
+
+        let meta = meta_with_ref(Some("ICML 2020"));
+        let bib = build_biblatex(&meta, "2001.00001", None, false);
+        assert!(bib.starts_with("@inproceedings{"), "{bib}");
+        assert!(bib.contains("booktitle = {ICML 2020}"), "{bib}");
+
+        let meta = meta_with_ref(Some("Accepted in some journal"));
+        let bib = build_biblatex(&meta, "2001.00001", None, false);
+        assert!(bib.starts_with("@online{"), "{bib}");
+        assert!(
+            bib.contains("note = {Journal reference: Accepted in some journal}"),
+            "{bib}"
+        );
+    }
+
+    #[test]
+    fn arxiv_doi_only_on_preprints() {
+        let meta = meta_with_ref(Some("Phys. Rev. D 76, 044016 (2007)"));
+        let bib = build_biblatex(&meta, "0704.0001", None, false);
+        assert!(!bib.contains("doi ="), "{bib}");
+        assert!(bib.contains("eprint = {0704.0001}"), "{bib}");
+
+        let mut meta = meta_with_ref(Some("Phys. Rev. D 76, 044016 (2007)"));
+        meta.published_doi = Some("10.1103/PhysRevD.76.044016".into());
+        let bib = build_biblatex(&meta, "0704.0001", None, false);
+        assert!(bib.contains("doi = {10.1103/PhysRevD.76.044016}"), "{bib}");
+
+        let meta = meta_with_ref(None);
+        let bib = build_biblatex(&meta, "0704.0001", None, false);
+        assert!(bib.contains("doi = {10.48550/arXiv.0704.0001}"), "{bib}");
+    }
+
+    #[test]
+    fn parse_atom_entry_reads_journal_ref() {
+        let xml = r#"<feed xmlns="http://www.w3.org/2005/Atom" xmlns:arxiv="http://arxiv.org/schemas/atom">
+  <entry>
+    <title>A Paper</title>
+    <author><name>Jane Doe</name></author>
+    <arxiv:journal_ref>Phys. Rev. D 76,
+      044016 (2007)</arxiv:journal_ref>
+  </entry>
+</feed>"#;
+        let meta = parse_atom_entry(xml, "0704.0001").unwrap();
+        assert_eq!(
+            meta.journal_ref.as_deref(),
+            Some("Phys. Rev. D 76, 044016 (2007)")
+        );
+    }
+
+    #[test]
+    fn merge_published_keeps_venue_and_eprint() {
+        let published = Bibliography::parse(
+            "@article{Doe_2007, title = {A Paper}, author = {Doe, Jane}, \
+             journal = {Physical Review D}, volume = {76}, year = {2007}, \
+             doi = {10.1103/PhysRevD.76.044016}}",
+        )
+        .unwrap()
+        .iter()
+        .next()
+        .cloned()
+        .unwrap();
+        let bib = build_biblatex(&meta_with_ref(None), "0704.0001", Some("2"), false);
+        let preprint = Bibliography::parse(&bib)
+            .unwrap()
+            .iter()
+            .next()
+            .cloned()
+            .unwrap();
+
+        let merged = merge_published(published, &preprint).to_biblatex_string();
+        assert!(merged.starts_with("@article{Doe_2007,"), "{merged}");
+        assert!(
+            merged.contains("doi = {10.1103/PhysRevD.76.044016}"),
+            "{merged}"
+        );
+        assert!(
+            merged.contains("journaltitle = {Physical Review D}"),
+            "{merged}"
+        );
+        assert!(merged.contains("eprint = {0704.0001}"), "{merged}");
+        assert!(merged.contains("eprinttype = {arXiv}"), "{merged}");
+        assert!(merged.contains("eprintclass = {gr-qc}"), "{merged}");
+        assert!(merged.contains("eprintversion = {2}"), "{merged}");
+        assert!(!merged.contains("publisher = {arXiv}"), "{merged}");
+    }
+
+    #[test]
+    fn parse_atom_feed_maps_entries_back_by_id_and_version() {
+        let xml = r#"<feed xmlns="http://www.w3.org/2005/Atom" xmlns:arxiv="http://arxiv.org/schemas/atom">
+  <id>http://arxiv.org/api/abc</id>
+  <entry>
+    <id>http://arxiv.org/abs/1810.04805v2</id>
+    <title>BERT</title>
+    <author><name>Jacob Devlin</name></author>
+    <arxiv:primary_category term="cs.CL"/>
+  </entry>
+  <entry>
+    <id>http://arxiv.org/abs/hep-th/9901001v1</id>
+    <title>Old paper</title>
+    <author><name>A. Physicist</name></author>
+    <link title="doi" href="http://dx.doi.org/10.1000/xyz" rel="related"/>
+  </entry>
+  <entry>
+    <id>http://arxiv.org/abs/1810.04805v1</id>
+    <title>BERT (first version)</title>
+  </entry>
+</feed>"#;
+        let entries = parse_atom_feed(xml).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].primary_class.as_deref(), Some("cs.CL"));
+        assert_eq!(entries[1].published_doi.as_deref(), Some("10.1000/xyz"));
+
+        assert_eq!(match_entry(&entries, "1810.04805").unwrap().title, "BERT");
+        assert_eq!(
+            match_entry(&entries, "1810.04805v1").unwrap().title,
+            "BERT (first version)"
+        );
+        assert_eq!(
+            match_entry(&entries, "hep-th/9901001").unwrap().id,
+            "hep-th/9901001"
+        );
+        assert!(match_entry(&entries, "1810.04805v3").is_none());
+        assert!(match_entry(&entries, "2101.00001").is_none());
+        assert_eq!(
+            parse_atom_entry(xml, "hep-th/9901001").unwrap().title,
+            "Old paper"
+        );
+    }
+
+    #[test]
+    fn pinned_version_gets_its_own_date() {
+        let mut meta = meta_with_ref(None);
+        meta.published = Some("2018-10-11T00:50:01Z".into());
+        meta.updated = Some("2019-05-24T20:37:26Z".into());
+
+        let bib = build_biblatex(&meta, "1810.04805", Some("1"), false);
+        assert!(bib.contains("date = {2018-10-11}"), "{bib}");
+        assert!(bib.contains("eprintversion = {1}"), "{bib}");
+
+        let bib = build_biblatex(&meta, "1810.04805", None, false);
+        assert!(bib.contains("date = {2019-05-24}"), "{bib}");
+        assert!(!bib.contains("eprintversion"), "{bib}");
+    }
+
+    #[test]
+    fn parse_submission_history_versions() {
+        let html = r#"<div class="submission-history">
+  <h2>Submission history</h2> From: Jacob Devlin [<a href="/show-email/x/1810.04805">view email</a>]
+  <br/>
+  <strong><a href="/abs/1810.04805v1" rel="nofollow">[v1]</a></strong>
+  Thu, 11 Oct 2018 00:50:01 UTC (227 KB)<br/>
+  <strong>[v2]</strong> Fri, 24 May 2019 20:37:26 UTC (309 KB)<br/>
+</div>"#;
+        let history = parse_submission_history(html);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].version, 1);
+        assert_eq!(history[0].date, "2018-10-11");
+        assert_eq!(history[0].size.as_deref(), Some("227 KB"));
+        assert_eq!(history[1].version, 2);
+        assert_eq!(history[1].date, "2019-05-24");
+    }
+
+    fn search_query(s: &str) -> Option<(String, bool)> {
+        match <ArxivQuery as Identifier<'_>>::parse(s)?.target {
+            QueryTarget::Search {
+                query,
+                newest_first,
+            } => Some((query, newest_first)),
+            QueryTarget::Author(a) => Some((format!("author {a}"), false)),
+        }
+    }
+
+    #[test]
+    fn parse_listing_and_search_pages() {
+        assert_eq!(
+            search_query("https://arxiv.org/list/cs.CL/recent"),
+            Some(("cat:cs.CL".into(), true))
+        );
+        assert_eq!(
+            search_query("https://arxiv.org/list/hep-th/0701")
+                .unwrap()
+                .0,
+            "cat:hep-th AND submittedDate:[200701010000 TO 200701312359]"
+        );
+        assert_eq!(
+            search_query("https://arxiv.org/list/cs.LG/2024").unwrap().0,
+            "cat:cs.LG AND submittedDate:[202401010000 TO 202412312359]"
+        );
+        assert_eq!(
+            search_query("https://arxiv.org/search/?query=attention+is&searchtype=title"),
+            Some(("ti:attention AND ti:is".into(), false))
+        );
+        assert_eq!(
+            search_query("https://arxiv.org/a/devlin_j_1.html")
+                .unwrap()
+                .0,
+            "author devlin_j_1"
+        );
+        assert_eq!(
+            search_query("https://arxiv.org/find/cs/1/au:+Devlin_J/0/1/0/all/0/1")
+                .unwrap()
+                .0,
+            "au:Devlin_J"
+        );
+        for bad in [
+            "https://arxiv.org/abs/1810.04805",
+            "https://arxiv.org/list/cs.CL/someday",
+            "https://example.org/list/cs.CL/recent",
+            "cs.CL",
+        ] {
+            assert!(search_query(bad).is_none(), "should reject {bad}");
+        }
+        // Single papers still go to `Arxiv`, and pages are no longer taken for one.
+        assert!(<Arxiv<'_> as Identifier<'_>>::parse("https://arxiv.org/a/devlin_j_1").is_none());
+    }
+
+    #[test]
+    fn search_filters_build_query() {
+        let filters = SearchFilters {
+            categories: vec!["cs.CL".into(), "cs.LG".into()],
+            since: Some("2023-02".into()),
+            until: Some("2024".into()),
+        };
+        let q = ArxivQuery::search("bert au:devlin", &filters).unwrap();
+        match q.target {
+            QueryTarget::Search {
+                query,
+                newest_first,
+            } => {
+                assert_eq!(
+                    query,
+                    "all:bert AND au:devlin AND (cat:cs.CL OR cat:cs.LG) \
+                     AND submittedDate:[202302010000 TO 202412312359]"
+                );
+                assert!(!newest_first);
+            }
+            QueryTarget::Author(_) => panic!("expected a search"),
+        }
+        assert_eq!(date_bound("2024-02", true).as_deref(), Some("202402292359"));
+        assert!(date_bound("2024-13", false).is_none());
+        assert!(ArxivQuery::search("", &SearchFilters::default()).is_err());
+    }
+
+    #[test]
+    fn affiliations_become_author_annotations() {
+        let xml = r#"<feed xmlns="http://www.w3.org/2005/Atom" xmlns:arxiv="http://arxiv.org/schemas/atom">
+  <entry>
+    <id>http://arxiv.org/abs/2101.00001v1</id>
+    <title>A Paper</title>
+    <author><name>Jane Doe</name></author>
+    <author>
+      <name>Rick Roe</name>
+      <arxiv:affiliation>MIT</arxiv:affiliation>
+      <arxiv:affiliation>CERN; Geneva</arxiv:affiliation>
+    </author>
+    <author><name>Ann Poe</name><arxiv:affiliation>ETH "Zurich"</arxiv:affiliation></author>
+  </entry>
+</feed>"#;
+        let meta = parse_atom_entry(xml, "2101.00001").unwrap();
+        assert_eq!(meta.authors.len(), 3);
+        assert_eq!(
+            meta.affiliations,
+            vec![
+                vec![],
+                vec!["MIT".to_string(), "CERN; Geneva".to_string()],
+                vec!["ETH \"Zurich\"".to_string()],
+            ]
+        );
+        let bib = build_biblatex(&meta, "2101.00001", None, false);
+        assert!(
+            bib.contains(r#"author+an:affiliation = {2="MIT, CERN, Geneva"; 3="ETH 'Zurich'"}"#),
+            "{bib}"
+        );
+        let entry = Bibliography::parse(&bib)
+            .unwrap()
+            .iter()
+            .next()
+            .cloned()
+            .unwrap();
+        assert!(entry.fields.contains_key("author+an:affiliation"));
+    }
 }
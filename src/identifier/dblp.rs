@@ -0,0 +1,254 @@
+use anyhow::Context;
+use biblatex::{Bibliography, Entry};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{identifier::Identifier, resolver::IdFamily};
+
+/// A DBLP record key or URL. Person pages and venue volumes are multi-item sources.
+pub struct Dblp<'a> {
+    target: DblpTarget<'a>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum DblpTarget<'a> {
+    /// Record key without the "DBLP:" prefix (e.g., "conf/osdi/SmithJ20").
+    Record(&'a str),
+    /// Person ID (e.g., "123/4567" or "s/JaneSmith-1").
+    Person(&'a str),
+    /// Table of contents of one venue volume (e.g., "conf/osdi/osdi2020").
+    Toc(&'a str),
+}
+
+static RECORD_KEY_RE: Lazy<Regex> = Lazy::new(|| {
+    // <kind>/<venue>/<name>, e.g. conf/osdi/SmithJ20 or journals/corr/abs-1810-04805
+    Regex::new(r"^[a-z]+/[A-Za-z0-9_-]+/[A-Za-z0-9_.:-]+$").unwrap()
+});
+
+impl<'a> Identifier<'a> for Dblp<'a> {
+    fn parse(identifier: &'a str) -> Option<Box<Self>> {
+        let s = identifier.trim();
+
+        if let Some(key) = s.strip_prefix("DBLP:").or_else(|| s.strip_prefix("dblp:")) {
+            let key = key.trim();
+            return RECORD_KEY_RE.is_match(key).then(|| {
+                Box::new(Dblp {
+                    target: DblpTarget::Record(key),
+                })
+            });
+        }
+
+        // URLs:
+        //  - https://dblp.org/rec/conf/osdi/SmithJ20(.html|.bib|.xml)
+        //  - https://dblp.org/pid/123/4567(.html|.bib)
+        //  - https://dblp.org/db/conf/osdi/osdi2020(.html)
+        let (host, path) = s
+            .strip_prefix("http://")
+            .or_else(|| s.strip_prefix("https://"))
+            .and_then(|rest| rest.split_once('/'))?;
+        let host = host.to_ascii_lowercase();
+        if !matches!(
+            host.as_str(),
+            "dblp.org" | "www.dblp.org" | "dblp.uni-trier.de" | "dblp.dagstuhl.de"
+        ) {
+            return None;
+        }
+        let path = path.split(['?', '#']).next().unwrap_or(path);
+        let path = path.trim_matches('/');
+        let path = [".html", ".bib", ".xml", ".rdf"]
+            .iter()
+            .find_map(|ext| path.strip_suffix(ext))
+            .unwrap_or(path);
+
+        let target = if let Some(key) = path.strip_prefix("rec/") {
+            // Older links carry a format segment, e.g. rec/bibtex/conf/osdi/SmithJ20.
+            let key = ["bibtex/", "bib/", "xml/", "html/"]
+                .iter()
+                .find_map(|p| key.strip_prefix(p))
+                .unwrap_or(key);
+            RECORD_KEY_RE
+                .is_match(key)
+                .then_some(DblpTarget::Record(key))?
+        } else if let Some(pid) = path.strip_prefix("pid/") {
+            (!pid.is_empty()).then_some(DblpTarget::Person(pid))?
+        } else if let Some(toc) = path.strip_prefix("db/") {
+            // Venue index pages list every volume; only single volumes are accepted.
+            if toc.ends_with("/index") || toc.split('/').count() != 3 {
+                return None;
+            }
+            DblpTarget::Toc(toc)
+        } else {
+            return None;
+        };
+        Some(Box::new(Dblp { target }))
+    }
+
+    fn resolve(&self) -> anyhow::Result<Entry> {
+        match self.target {
+            DblpTarget::Record(key) => {
+                let body = fetch_bib(&format!("https://dblp.org/rec/{key}.bib?param=1"))?;
+                let bib = parse_dblp_bibtex(&body)?;
+                bib.into_iter()
+                    .find(|e| e.key == format!("DBLP:{key}"))
+                    .ok_or_else(|| anyhow::anyhow!("no DBLP record found for {key}"))
+            }
+            DblpTarget::Person(_) | DblpTarget::Toc(_) => Err(anyhow::anyhow!(
+                "DBLP listing pages contain several items; resolve them as a multi-item source"
+            )),
+        }
+    }
+
+    fn resolve_many(&self, _limit: Option<usize>) -> anyhow::Result<Vec<Entry>> {
+        let url = match self.target {
+            DblpTarget::Record(_) => return self.resolve().map(|e| vec![e]),
+            DblpTarget::Person(pid) => format!("https://dblp.org/pid/{pid}.bib?param=1"),
+            DblpTarget::Toc(toc) => {
+                let mut url = url::Url::parse("https://dblp.org/search/publ/api")?;
+                url.query_pairs_mut()
+                    .append_pair("q", &format!("toc:db/{toc}.bht:"))
+                    .append_pair("h", "1000")
+                    .append_pair("format", "bib1")
+                    .append_pair("rd", "1a");
+                url.to_string()
+            }
+        };
+        let body = fetch_bib(&url)?;
+        let entries = parse_dblp_bibtex(&body)?;
+        if entries.is_empty() {
+            return Err(anyhow::anyhow!("no DBLP records found at {url}"));
+        }
+        Ok(entries)
+    }
+}
+
+impl IdFamily for Dblp<'_> {
+    type For<'a> = Dblp<'a>;
+}
+
+fn fetch_bib(url: &str) -> anyhow::Result<String> {
+    let cfg = ureq::Agent::config_builder()
+        .timeout_connect(Some(std::time::Duration::from_secs(5)))
+        .timeout_global(Some(std::time::Duration::from_secs(30)))
+        .build();
+    let agent = ureq::Agent::new_with_config(cfg);
+    let body: String = agent
+        .get(url)
+        .header(
+            "User-Agent",
+            "Mozilla/5.0 (compatible; bib/0.1; +https://dblp.org)",
+        )
+        .header("Accept", "application/x-bibtex")
+        .call()
+        .with_context(|| format!("failed DBLP request for {url}"))?
+        .into_body()
+        .read_to_string()
+        .context("failed to read DBLP response body")?;
+    Ok(body)
+}
+
+/// Parse DBLP's standard BibTeX export and drop its bookkeeping fields.
+///
+/// DBLP's "standard" form (param=1) inlines the full, normalised venue name in `booktitle` or
+/// `journal`, which BibLaTeX output maps to `booktitle`/`journaltitle` as usual.
+fn parse_dblp_bibtex(body: &str) -> anyhow::Result<Vec<Entry>> {
+    let bib = Bibliography::parse(body)
+        .map_err(|e| anyhow::anyhow!("failed to parse DBLP BibTeX: {e}"))?;
+    Ok(bib
+        .into_iter()
+        .map(|mut e| {
+            for field in ["timestamp", "biburl", "bibsource"] {
+                e.remove(field);
+            }
+            e
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Option<DblpTarget<'_>> {
+        <Dblp<'_> as Identifier<'_>>::parse(s).map(|d| d.target)
+    }
+
+    #[test]
+    fn parse_record_keys_and_urls() {
+        for input in [
+            "DBLP:conf/osdi/SmithJ20",
+            "dblp:conf/osdi/SmithJ20",
+            "https://dblp.org/rec/conf/osdi/SmithJ20",
+            "https://dblp.org/rec/conf/osdi/SmithJ20.html",
+            "https://dblp.org/rec/conf/osdi/SmithJ20.bib?param=1",
+            "https://dblp.uni-trier.de/rec/bibtex/conf/osdi/SmithJ20",
+        ] {
+            assert_eq!(
+                parse(input),
+                Some(DblpTarget::Record("conf/osdi/SmithJ20")),
+                "input {input}"
+            );
+        }
+        assert_eq!(
+            parse("DBLP:journals/corr/abs-1810-04805"),
+            Some(DblpTarget::Record("journals/corr/abs-1810-04805"))
+        );
+    }
+
+    #[test]
+    fn parse_multi_item_urls() {
+        assert_eq!(
+            parse("https://dblp.org/pid/123/4567.html"),
+            Some(DblpTarget::Person("123/4567"))
+        );
+        assert_eq!(
+            parse("https://dblp.org/db/conf/osdi/osdi2020.html"),
+            Some(DblpTarget::Toc("conf/osdi/osdi2020"))
+        );
+        assert_eq!(
+            parse("https://dblp.org/db/journals/pacmpl/pacmpl8.html#Reitz24"),
+            Some(DblpTarget::Toc("journals/pacmpl/pacmpl8"))
+        );
+    }
+
+    #[test]
+    fn parse_rejects_other_inputs() {
+        for bad in [
+            "DBLP:",
+            "DBLP:not a key",
+            "https://dblp.org/db/conf/osdi/index.html",
+            "https://dblp.org/search?q=osdi",
+            "https://example.com/rec/conf/osdi/SmithJ20",
+        ] {
+            assert!(parse(bad).is_none(), "should reject {bad}");
+        }
+    }
+
+    #[test]
+    fn dblp_bibtex_drops_bookkeeping_fields() {
+        let body = r#"@inproceedings{DBLP:conf/osdi/SmithJ20,
+  author       = {Jane Smith and
+                  John Jones},
+  editor       = {Jon Howell and
+                  Shan Lu},
+  title        = {A Paper},
+  booktitle    = {14th {USENIX} Symposium on Operating Systems Design and Implementation,
+                  {OSDI} 2020, Virtual Event, November 4-6, 2020},
+  pages        = {1--16},
+  publisher    = {{USENIX} Association},
+  year         = {2020},
+  url          = {https://www.usenix.org/conference/osdi20/presentation/smith},
+  timestamp    = {Tue, 02 Feb 2021 08:06:02 +0100},
+  biburl       = {https://dblp.org/rec/conf/osdi/SmithJ20.bib},
+  bibsource    = {dblp computer science bibliography, https://dblp.org}
+}
+"#;
+        let entries = parse_dblp_bibtex(body).unwrap();
+        assert_eq!(entries.len(), 1);
+        let e = &entries[0];
+        assert_eq!(e.key, "DBLP:conf/osdi/SmithJ20");
+        assert!(e.get("booktitle").is_some());
+        for field in ["timestamp", "biburl", "bibsource"] {
+            assert!(e.get(field).is_none(), "{field} should be dropped");
+        }
+    }
+}
@@ -4,7 +4,11 @@ use once_cell::sync::Lazy;
 use regex::Regex;
 use url::Url;
 
-use crate::{identifier::Identifier, resolver::IdFamily};
+use crate::{
+    identifier::Identifier,
+    pdf,
+    resolver::{self, IdFamily},
+};
 
 /// A generic, last-resort translator for HTTP(S) webpages using embedded metadata and
 /// conservative heuristics.
@@ -28,7 +32,18 @@ impl<'a> Identifier<'a> for Embedded {
     }
 
     fn resolve(&self) -> anyhow::Result<Entry> {
-        let (final_url, html) = fetch(self.url.clone())?;
+        match fetch(self.url.clone())? {
+            (final_url, Body::Html(html)) => Self::entry_from_html(final_url, html),
+            (final_url, Body::Pdf { bytes, canonical }) => {
+                resolve_pdf(&final_url, &bytes, canonical)
+            }
+        }
+    }
+}
+
+impl Embedded {
+    /// Build an entry from a webpage's embedded metadata.
+    fn entry_from_html(final_url: Url, html: String) -> anyhow::Result<Entry> {
         // Determine base URL for resolving relative links using <base href> when present
         let base_url = if let Some(href) = collect_base_href(&html) {
             absolutise(&final_url, &href).unwrap_or_else(|_| final_url.clone())
@@ -306,11 +321,84 @@ impl IdFamily for Embedded {
     type For<'a> = Embedded;
 }
 
+/// Cite a PDF served at `url`: through the identifiers it carries, else through its landing
+/// page's metadata, else as a `@misc` from the PDF's own title and authors.
+fn resolve_pdf(url: &Url, bytes: &[u8], canonical: Option<Url>) -> anyhow::Result<Entry> {
+    let meta = pdf::inspect(bytes).ok();
+    if let Some(meta) = &meta
+        && let Some(id) = meta.identifier()
+        && let Ok(entry) = resolver::resolve(&id)
+        && meta.describes(&entry)
+    {
+        return Ok(entry);
+    }
+    for page in landing_pages(url, canonical) {
+        // A page we guessed at must look like it describes a work, not be, say, a site's home.
+        let trusted = page.trusted;
+        if let Ok((final_url, Body::Html(html))) = fetch(page.url)
+            && (trusted || meta_value(&collect_meta(&html), "citation_title").is_some())
+        {
+            return Embedded::entry_from_html(final_url, html);
+        }
+    }
+    let meta = meta.ok_or_else(|| anyhow::anyhow!("not a readable PDF: {url}"))?;
+    let file_name = url
+        .path_segments()
+        .and_then(|mut s| s.next_back())
+        .unwrap_or_default();
+    let untitled = file_name.trim_end_matches(".pdf").replace(['_', '-'], " ");
+    meta.to_entry(&untitled, ("url", url.as_str()))
+}
+
+struct LandingPage {
+    url: Url,
+    /// Whether the server named it (in a `Link: rel=canonical` header) rather than us guessing.
+    trusted: bool,
+}
+
+/// Where the landing page of a PDF might be: the canonical URL the server gave, then the PDF's
+/// URL without its `.pdf` extension (nor any download parameters).
+fn landing_pages(url: &Url, canonical: Option<Url>) -> Vec<LandingPage> {
+    let mut pages = Vec::new();
+    if let Some(c) = canonical.filter(|c| c != url) {
+        pages.push(LandingPage {
+            url: c,
+            trusted: true,
+        });
+    }
+    let path = url.path();
+    if path.len() > 4 && path[path.len() - 4..].eq_ignore_ascii_case(".pdf") {
+        let mut stripped = url.clone();
+        stripped.set_path(&path[..path.len() - 4]);
+        stripped.set_query(None);
+        stripped.set_fragment(None);
+        if !pages.iter().any(|p| p.url == stripped) {
+            pages.push(LandingPage {
+                url: stripped,
+                trusted: false,
+            });
+        }
+    }
+    pages
+}
+
 // ----------------------------
 // Helpers and collectors
 // ----------------------------
 
-fn fetch(url: Url) -> anyhow::Result<(Url, String)> {
+/// Largest PDF we download to look for metadata in.
+const MAX_PDF_BYTES: u64 = 64 * 1024 * 1024;
+
+/// What a URL serves: a webpage, or a PDF along with the canonical URL the server gave for it.
+enum Body {
+    Html(String),
+    Pdf {
+        bytes: Vec<u8>,
+        canonical: Option<Url>,
+    },
+}
+
+fn fetch(url: Url) -> anyhow::Result<(Url, Body)> {
     let cfg = ureq::Agent::config_builder()
         .timeout_connect(Some(std::time::Duration::from_secs(5)))
         .timeout_global(Some(std::time::Duration::from_secs(15)))
@@ -323,6 +411,25 @@ fn fetch(url: Url) -> anyhow::Result<(Url, String)> {
     let res = req
         .call()
         .with_context(|| format!("failed request for URL {}", url))?;
+    // Check the content type before reading: PDFs are binary, and large.
+    let header = |name: &str| {
+        res.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    if is_pdf_response(header("content-type").as_deref(), &url) {
+        let canonical = header("link")
+            .and_then(|l| canonical_link(&l))
+            .and_then(|href| absolutise(&url, &href).ok());
+        let bytes = res
+            .into_body()
+            .with_config()
+            .limit(MAX_PDF_BYTES)
+            .read_to_vec()
+            .context("read body")?;
+        return Ok((url, Body::Pdf { bytes, canonical }));
+    }
     let body = res.into_body().read_to_string().context("read body")?;
     // Honour <base href> when present for absolutising relative URLs.
     let base = if let Some(href) = collect_base_href(&body) {
@@ -330,7 +437,35 @@ fn fetch(url: Url) -> anyhow::Result<(Url, String)> {
     } else {
         url
     };
-    Ok((base, body))
+    Ok((base, Body::Html(body)))
+}
+
+/// Whether a response is a PDF: by its content type, or by the URL when the server only says
+/// it is some binary file.
+fn is_pdf_response(content_type: Option<&str>, url: &Url) -> bool {
+    let ct = content_type.unwrap_or_default().to_ascii_lowercase();
+    if ct.contains("application/pdf") || ct.contains("application/x-pdf") {
+        return true;
+    }
+    (ct.is_empty() || ct.contains("application/octet-stream") || ct.contains("binary/octet-stream"))
+        && url.path().to_ascii_lowercase().ends_with(".pdf")
+}
+
+/// The target of a `rel=canonical` link in a `Link` header, e.g.
+/// `<https://example.org/paper/1>; rel="canonical"`.
+fn canonical_link(header: &str) -> Option<String> {
+    header.split(',').find_map(|link| {
+        let (target, params) = link.split_once(';')?;
+        let target = target.trim().strip_prefix('<')?.strip_suffix('>')?;
+        let is_canonical = params.split(';').any(|p| {
+            p.trim().strip_prefix("rel=").is_some_and(|rel| {
+                rel.trim_matches('"')
+                    .split_whitespace()
+                    .any(|r| r.eq_ignore_ascii_case("canonical"))
+            })
+        });
+        is_canonical.then(|| target.to_string())
+    })
 }
 
 #[derive(Debug, Clone)]
@@ -1418,6 +1553,44 @@ mod tests {
         assert_eq!(strip_site_suffix(t, "My Blog"), "An Interesting Post");
     }
 
+    #[test]
+    fn pdf_responses_by_content_type_or_extension() {
+        let pdf = Url::parse("https://example.org/files/Paper.PDF").unwrap();
+        let page = Url::parse("https://example.org/papers/1").unwrap();
+        assert!(is_pdf_response(Some("application/pdf"), &page));
+        assert!(is_pdf_response(Some("application/octet-stream"), &pdf));
+        assert!(is_pdf_response(None, &pdf));
+        assert!(!is_pdf_response(Some("text/html; charset=utf-8"), &pdf));
+        assert!(!is_pdf_response(Some("application/octet-stream"), &page));
+    }
+
+    #[test]
+    fn canonical_link_header() {
+        let header = concat!(
+            r#"<https://example.org/1.pdf>; rel="alternate", "#,
+            r#"<https://example.org/papers/1>; rel="canonical""#
+        );
+        assert_eq!(
+            canonical_link(header).as_deref(),
+            Some("https://example.org/papers/1")
+        );
+        assert_eq!(canonical_link("<https://example.org/>; rel=preload"), None);
+    }
+
+    #[test]
+    fn landing_pages_for_a_pdf() {
+        let url = Url::parse("https://example.org/papers/1.pdf?download=1").unwrap();
+        let canonical = Url::parse("https://example.org/papers/1").unwrap();
+        let pages = landing_pages(&url, Some(canonical.clone()));
+        // The guess is the same page the server named, so it is only tried once.
+        assert_eq!(pages.len(), 1);
+        assert!(pages[0].trusted);
+        let pages = landing_pages(&url, None);
+        assert_eq!(pages[0].url, canonical);
+        assert!(!pages[0].trusted);
+        assert!(landing_pages(&canonical, Some(canonical.clone())).is_empty());
+    }
+
     #[test]
     fn normalise_date_variants() {
         assert_eq!(normalise_date("2020-01-02"), Some("2020-01-02".to_string()));
@@ -0,0 +1,236 @@
+use anyhow::Context;
+use biblatex::{Bibliography, Entry};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::{
+    bibtex::{escape_braces, html_to_text, normalize_ws, scalar, write_entry},
+    identifier::Identifier,
+    resolver::IdFamily,
+};
+
+/// A Figshare item URL, on figshare.com or an institutional portal (`*.figshare.com`).
+pub struct Figshare<'a> {
+    article: &'a str,
+    version: Option<&'a str>,
+}
+
+static VERSION_SUFFIX_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\.v\d+$").unwrap());
+
+impl<'a> Identifier<'a> for Figshare<'a> {
+    fn parse(identifier: &'a str) -> Option<Box<Self>> {
+        let s = identifier.trim();
+
+        // URLs:
+        //  - https://figshare.com/articles/dataset/Some_title/1234567(/2)
+        //  - https://figshare.com/articles/1234567
+        //  - https://springernature.figshare.com/articles/dataset/Some_title/1234567
+        let (host, path) = s
+            .strip_prefix("http://")
+            .or_else(|| s.strip_prefix("https://"))
+            .and_then(|rest| rest.split_once('/'))?;
+        let host = host.to_ascii_lowercase();
+        if host != "figshare.com" && !host.ends_with(".figshare.com") {
+            return None;
+        }
+        let path = path.split(['?', '#']).next().unwrap_or(path);
+        let rest = path.trim_matches('/').strip_prefix("articles/")?;
+        // The article ID follows the type and slug, when there are any, and may be followed by
+        // the version. Slugs can be all digits too, so the ID is found by position.
+        let segs: Vec<&str> = rest.split('/').collect();
+        let (article, version) = match segs.as_slice() {
+            [id] | [_, _, id] => (*id, None),
+            [id, v] if is_number(id) => (*id, Some(*v)),
+            [_, _, id, v] => (*id, Some(*v)),
+            _ => return None,
+        };
+        if !is_number(article) || !version.is_none_or(is_number) {
+            return None;
+        }
+        Some(Box::new(Figshare { article, version }))
+    }
+
+    fn resolve(&self) -> anyhow::Result<Entry> {
+        let url = match self.version {
+            Some(v) => format!(
+                "https://api.figshare.com/v2/articles/{}/versions/{v}",
+                self.article
+            ),
+            None => format!("https://api.figshare.com/v2/articles/{}", self.article),
+        };
+        let cfg = ureq::Agent::config_builder()
+            .timeout_connect(Some(std::time::Duration::from_secs(5)))
+            .timeout_global(Some(std::time::Duration::from_secs(15)))
+            .build();
+        let agent = ureq::Agent::new_with_config(cfg);
+        let body: String = agent
+            .get(&url)
+            .header(
+                "User-Agent",
+                "Mozilla/5.0 (compatible; bib/0.1; +https://figshare.com)",
+            )
+            .call()
+            .with_context(|| format!("failed Figshare request for article {}", self.article))?
+            .into_body()
+            .read_to_string()
+            .context("failed to read Figshare response body")?;
+        let v: Value = serde_json::from_str(&body).context("invalid JSON from Figshare API")?;
+        let bib = build_biblatex(&v)?;
+        let bib = Bibliography::parse(&bib)
+            .map_err(|e| anyhow::anyhow!("failed to parse generated BibLaTeX: {e}"))?;
+        bib.into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("empty bibliography"))
+    }
+}
+
+impl IdFamily for Figshare<'_> {
+    type For<'a> = Figshare<'a>;
+}
+
+fn is_number(seg: &str) -> bool {
+    !seg.is_empty() && seg.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Map Figshare's `defined_type_name` to a BibLaTeX entry type.
+fn entry_type(defined_type: &str) -> &'static str {
+    match defined_type {
+        "software" | "code" => "software",
+        "dataset" | "data management plan" => "dataset",
+        "journal contribution" => "article",
+        "thesis" => "thesis",
+        "preprint" | "poster" | "presentation" => "unpublished",
+        "book" => "book",
+        "report" => "report",
+        _ => "misc",
+    }
+}
+
+fn build_biblatex(v: &Value) -> anyhow::Result<String> {
+    let id = scalar(v, "id").ok_or_else(|| anyhow::anyhow!("Figshare item has no id"))?;
+    let title =
+        scalar(v, "title").ok_or_else(|| anyhow::anyhow!("Figshare item {id} has no title"))?;
+    let ty = entry_type(&scalar(v, "defined_type_name").unwrap_or_default());
+
+    let mut fields: Vec<(&str, String)> = vec![("title", title)];
+    let authors: Vec<String> = v
+        .get("authors")
+        .and_then(Value::as_array)
+        .map(|a| a.iter().filter_map(|p| scalar(p, "full_name")).collect())
+        .unwrap_or_default();
+    if !authors.is_empty() {
+        fields.push(("author", authors.join(" and ")));
+    }
+    if let Some(d) = scalar(v, "published_date") {
+        fields.push(("date", d.chars().take(10).collect()));
+    }
+    if let Some(version) = scalar(v, "version") {
+        fields.push(("version", version));
+    }
+    // Version DOIs end in ".vN"; without that suffix the DOI resolves to the latest version.
+    if let Some(doi) = scalar(v, "doi") {
+        let concept = VERSION_SUFFIX_RE.replace(&doi, "").into_owned();
+        fields.push(("doi", doi.clone()));
+        if concept != doi {
+            fields.push(("conceptdoi", concept));
+        }
+    }
+    fields.push(("publisher", "Figshare".to_string()));
+    if let Some(url) = scalar(v, "url_public_html").or_else(|| scalar(v, "figshare_url")) {
+        fields.push(("url", url));
+    }
+    if let Some(license) = v.get("license").and_then(|l| scalar(l, "name")) {
+        fields.push(("license", license));
+    }
+    if let Some(desc) = scalar(v, "description") {
+        let text = html_to_text(&desc);
+        if !text.is_empty() {
+            fields.push(("abstract", text));
+        }
+    }
+    let tags: Vec<String> = v
+        .get("tags")
+        .and_then(Value::as_array)
+        .map(|t| {
+            t.iter()
+                .filter_map(|x| x.as_str().map(normalize_ws))
+                .collect()
+        })
+        .unwrap_or_default();
+    if !tags.is_empty() {
+        fields.push(("keywords", tags.join(", ")));
+    }
+    let fields = fields
+        .into_iter()
+        .map(|(k, v)| (k, escape_braces(&v)))
+        .collect();
+    Ok(write_entry(ty, &format!("figshare:{id}"), fields))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Option<(&str, Option<&str>)> {
+        <Figshare<'_> as Identifier<'_>>::parse(s).map(|f| (f.article, f.version))
+    }
+
+    #[test]
+    fn parse_article_urls() {
+        assert_eq!(
+            parse("https://figshare.com/articles/dataset/Some_title/1234567"),
+            Some(("1234567", None))
+        );
+        assert_eq!(
+            parse("https://figshare.com/articles/software/Tool/1234567/2"),
+            Some(("1234567", Some("2")))
+        );
+        assert_eq!(
+            parse("https://figshare.com/articles/1234567"),
+            Some(("1234567", None))
+        );
+        assert_eq!(
+            parse("https://springernature.figshare.com/articles/dataset/X/7654321"),
+            Some(("7654321", None))
+        );
+        assert_eq!(
+            parse("https://figshare.com/articles/dataset/2020/1234567"),
+            Some(("1234567", None))
+        );
+        for bad in [
+            "https://figshare.com/authors/Jane_Doe/123",
+            "https://figshare.com/articles/dataset/no_id",
+            "https://notfigshare.com/articles/1234567",
+        ] {
+            assert!(parse(bad).is_none(), "should reject {bad}");
+        }
+    }
+
+    #[test]
+    fn versioned_doi_yields_concept_doi() {
+        let v: Value = serde_json::from_str(
+            r#"{"id": 1234567, "title": "Data {set}", "version": 2,
+                "doi": "10.6084/m9.figshare.1234567.v2",
+                "defined_type_name": "dataset",
+                "published_date": "2023-05-01T10:00:00Z",
+                "authors": [{"full_name": "Jane Doe"}],
+                "url_public_html": "https://figshare.com/articles/dataset/Data/1234567",
+                "license": {"name": "CC BY 4.0"}, "tags": ["x", "y"]}"#,
+        )
+        .unwrap();
+        let bib = build_biblatex(&v).unwrap();
+        assert!(bib.starts_with("@dataset{figshare:1234567,"), "{bib}");
+        assert!(bib.contains("version = {2}"), "{bib}");
+        assert!(
+            bib.contains("doi = {10.6084/m9.figshare.1234567.v2}"),
+            "{bib}"
+        );
+        assert!(
+            bib.contains("conceptdoi = {10.6084/m9.figshare.1234567}"),
+            "{bib}"
+        );
+        assert!(bib.contains("date = {2023-05-01}"), "{bib}");
+        assert!(bib.contains("title = {Data \\{set\\}}"), "{bib}");
+    }
+}
@@ -0,0 +1,399 @@
+use anyhow::Context;
+use biblatex::{Bibliography, Chunk, Entry, Spanned};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::{
+    bibtex::{escape_braces, normalize_ws, write_entry},
+    identifier::Identifier,
+    resolver::IdFamily,
+};
+
+/// A Hugging Face Hub model or dataset URL, optionally pinned to a revision.
+pub struct HuggingFace<'a> {
+    kind: RepoKind,
+    /// `owner/name`, or just `name` for legacy top-level models.
+    id: &'a str,
+    revision: Option<&'a str>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum RepoKind {
+    Model,
+    Dataset,
+}
+
+/// First path segments on the Hub that are site pages rather than models.
+const RESERVED: &[&str] = &[
+    "api",
+    "blog",
+    "collections",
+    "docs",
+    "join",
+    "learn",
+    "login",
+    "models",
+    "new",
+    "organizations",
+    "papers",
+    "posts",
+    "pricing",
+    "settings",
+    "spaces",
+    "tasks",
+];
+
+/// Models from before the Hub had namespaces, which still live at `huggingface.co/<name>`.
+/// A single path segment names one of them only if it belongs to these families; anything
+/// else there (`huggingface.co/google`) is a user or organisation page.
+const LEGACY_FAMILIES: &[&str] = &[
+    "albert",
+    "bert",
+    "camembert",
+    "ctrl",
+    "distilbert",
+    "distilgpt2",
+    "distilroberta",
+    "flaubert",
+    "gpt2",
+    "openai-gpt",
+    "roberta",
+    "t5",
+    "transfo-xl",
+    "xlm",
+    "xlnet",
+];
+
+static BIB_BLOCK_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?s)```[A-Za-z]*[ \t]*\r?\n(.*?)```").unwrap());
+static ENTRY_START_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"@[A-Za-z]+\s*\{").unwrap());
+
+impl<'a> Identifier<'a> for HuggingFace<'a> {
+    fn parse(identifier: &'a str) -> Option<Box<Self>> {
+        let s = identifier.trim();
+
+        // URLs:
+        //  - https://huggingface.co/google-bert/bert-base-uncased(/tree/<rev>|/blob/<rev>/...)
+        //  - https://huggingface.co/datasets/rajpurkar/squad(/tree/<rev>)
+        //  - https://hf.co/... for either
+        let (host, path) = s
+            .strip_prefix("http://")
+            .or_else(|| s.strip_prefix("https://"))
+            .and_then(|rest| rest.split_once('/'))?;
+        if !matches!(
+            host.to_ascii_lowercase().as_str(),
+            "huggingface.co" | "www.huggingface.co" | "hf.co"
+        ) {
+            return None;
+        }
+        let path = path.split(['?', '#']).next().unwrap_or(path);
+        let path = path.trim_matches('/');
+        let (kind, rest) = match path.strip_prefix("datasets/") {
+            Some(rest) => (RepoKind::Dataset, rest),
+            None => (RepoKind::Model, path),
+        };
+
+        // The repository ID ends where a "tree"/"blob"/... segment begins.
+        let segs: Vec<&str> = rest.split('/').collect();
+        let id_len = match segs.as_slice() {
+            [] | [""] => return None,
+            [_, action, ..] if is_action(action) => 1,
+            [_] => 1,
+            _ => 2,
+        };
+        if kind == RepoKind::Model
+            && (RESERVED.contains(&segs[0]) || (id_len == 1 && !is_legacy_model(segs[0])))
+        {
+            return None;
+        }
+        let valid = |s: &&str| {
+            !s.is_empty()
+                && s.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        };
+        if !segs[..id_len].iter().all(valid) {
+            return None;
+        }
+        let id = &rest[..segs[..id_len].iter().map(|s| s.len()).sum::<usize>() + id_len - 1];
+        let revision = match &segs[id_len..] {
+            [action, rev, ..] if is_action(action) => Some(*rev),
+            _ => None,
+        };
+        Some(Box::new(HuggingFace { kind, id, revision }))
+    }
+
+    fn resolve(&self) -> anyhow::Result<Entry> {
+        let (api, page) = match self.kind {
+            RepoKind::Model => ("models", format!("https://huggingface.co/{}", self.id)),
+            RepoKind::Dataset => (
+                "datasets",
+                format!("https://huggingface.co/datasets/{}", self.id),
+            ),
+        };
+        let mut api_url = format!("https://huggingface.co/api/{api}/{}", self.id);
+        if let Some(rev) = self.revision {
+            api_url.push_str(&format!("/revision/{rev}"));
+        }
+        let info: Value = serde_json::from_str(
+            &fetch(&api_url)?
+                .ok_or_else(|| anyhow::anyhow!("Hugging Face repository {} not found", self.id))?,
+        )
+        .context("invalid JSON from Hugging Face API")?;
+        let sha = json_str(&info, "sha");
+
+        // The card's own citation wins; datasets also expose it directly in the API.
+        let rev = sha.as_deref().or(self.revision).unwrap_or("main");
+        let card = fetch(&format!("{page}/raw/{rev}/README.md"))?;
+        let cited = card
+            .as_deref()
+            .and_then(citation_from_card)
+            .or_else(|| json_str(&info, "citation").and_then(|c| first_entry(&c)));
+        if let Some(mut entry) = cited {
+            // The card cites the paper; a pinned revision still says which weights were used.
+            if self.revision.is_some()
+                && let Some(rev) = sha.as_deref().or(self.revision)
+            {
+                note_revision(&mut entry, rev);
+            }
+            return Ok(entry);
+        }
+
+        let bib = build_biblatex(self.kind, self.id, &info, &page);
+        let bib = Bibliography::parse(&bib)
+            .map_err(|e| anyhow::anyhow!("failed to parse generated BibLaTeX: {e}"))?;
+        bib.into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("empty bibliography"))
+    }
+}
+
+impl IdFamily for HuggingFace<'_> {
+    type For<'a> = HuggingFace<'a>;
+}
+
+fn is_legacy_model(name: &str) -> bool {
+    LEGACY_FAMILIES.iter().any(|f| {
+        name.strip_prefix(f)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('-'))
+    })
+}
+
+fn is_action(seg: &str) -> bool {
+    matches!(
+        seg,
+        "tree" | "blob" | "resolve" | "raw" | "commit" | "discussions" | "viewer"
+    )
+}
+
+/// GET `url`, returning `None` on 401/404 (gated or missing cards are not errors).
+fn fetch(url: &str) -> anyhow::Result<Option<String>> {
+    let cfg = ureq::Agent::config_builder()
+        .timeout_connect(Some(std::time::Duration::from_secs(5)))
+        .timeout_global(Some(std::time::Duration::from_secs(15)))
+        .http_status_as_error(false)
+        .build();
+    let agent = ureq::Agent::new_with_config(cfg);
+    let mut req = agent.get(url).header(
+        "User-Agent",
+        "Mozilla/5.0 (compatible; bib/0.1; +https://huggingface.co)",
+    );
+    if let Ok(token) = std::env::var("HF_TOKEN") {
+        req = req.header("Authorization", &format!("Bearer {token}"));
+    }
+    let res = req
+        .call()
+        .with_context(|| format!("failed Hugging Face request for {url}"))?;
+    if matches!(res.status().as_u16(), 401 | 404) {
+        return Ok(None);
+    }
+    if !res.status().is_success() {
+        return Err(anyhow::anyhow!("{url} returned {}", res.status()));
+    }
+    let body = res
+        .into_body()
+        .read_to_string()
+        .context("failed to read Hugging Face response body")?;
+    Ok(Some(body))
+}
+
+/// The first fenced code block in a model card that parses as BibTeX.
+fn citation_from_card(card: &str) -> Option<Entry> {
+    BIB_BLOCK_RE
+        .captures_iter(card)
+        .filter_map(|c| c.get(1))
+        .filter(|m| ENTRY_START_RE.is_match(m.as_str()))
+        .find_map(|m| first_entry(m.as_str()))
+}
+
+/// Record the Hub revision in the entry's note, after whatever note the card gave.
+fn note_revision(entry: &mut Entry, rev: &str) {
+    let revision = format!("Hugging Face revision {rev}");
+    let note = match entry.get("note") {
+        Some(n) => format!(
+            "{}. {revision}",
+            n.iter().map(|c| c.v.get()).collect::<String>()
+        ),
+        None => revision,
+    };
+    entry.set("note", vec![Spanned::zero(Chunk::Normal(note))]);
+}
+
+fn first_entry(bib: &str) -> Option<Entry> {
+    Bibliography::parse(bib).ok()?.into_iter().next()
+}
+
+fn json_str(v: &Value, key: &str) -> Option<String> {
+    v.get(key)
+        .and_then(Value::as_str)
+        .map(normalize_ws)
+        .filter(|s| !s.is_empty())
+}
+
+fn build_biblatex(kind: RepoKind, id: &str, info: &Value, page: &str) -> String {
+    let card = info.get("cardData").unwrap_or(&Value::Null);
+    let name = id.rsplit('/').next().unwrap_or(id);
+    let title = json_str(card, "pretty_name").unwrap_or_else(|| name.to_string());
+
+    let mut fields: Vec<(&str, String)> = vec![("title", escape_braces(&title))];
+    // The namespace (user or organisation) is the only authorship the Hub records.
+    let owner = json_str(info, "author").or_else(|| id.split_once('/').map(|(o, _)| o.into()));
+    if let Some(owner) = owner {
+        fields.push(("author", format!("{{{}}}", escape_braces(&owner))));
+    }
+    if let Some(d) = json_str(info, "lastModified").or_else(|| json_str(info, "createdAt")) {
+        fields.push(("date", d.chars().take(10).collect()));
+    }
+    // The commit hash pins exactly which weights or files were used.
+    if let Some(sha) = json_str(info, "sha") {
+        fields.push(("version", escape_braces(&sha)));
+        fields.push(("url", format!("{page}/tree/{sha}")));
+    } else {
+        fields.push(("url", page.to_string()));
+    }
+    fields.push(("publisher", "Hugging Face".to_string()));
+    let license = match card.get("license") {
+        Some(Value::String(l)) => Some(l.clone()),
+        Some(Value::Array(ls)) => ls.first().and_then(Value::as_str).map(String::from),
+        _ => None,
+    };
+    if let Some(license) = license {
+        fields.push(("license", escape_braces(&license)));
+    }
+    if let Some(desc) = json_str(info, "description") {
+        fields.push(("abstract", escape_braces(&desc)));
+    }
+    // Tags such as "license:mit" or "region:us" are facets, not keywords.
+    let keywords: Vec<String> = info
+        .get("tags")
+        .and_then(Value::as_array)
+        .map(|t| {
+            t.iter()
+                .filter_map(Value::as_str)
+                .filter(|t| !t.contains(':'))
+                .map(escape_braces)
+                .collect()
+        })
+        .unwrap_or_default();
+    if !keywords.is_empty() {
+        fields.push(("keywords", keywords.join(", ")));
+    }
+
+    let ty = match kind {
+        RepoKind::Model => "software",
+        RepoKind::Dataset => "dataset",
+    };
+    let prefix = match kind {
+        RepoKind::Model => "hf",
+        RepoKind::Dataset => "hf-datasets",
+    };
+    write_entry(ty, &format!("{prefix}:{id}"), fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Option<(RepoKind, &str, Option<&str>)> {
+        <HuggingFace<'_> as Identifier<'_>>::parse(s).map(|h| (h.kind, h.id, h.revision))
+    }
+
+    #[test]
+    fn parse_model_and_dataset_urls() {
+        assert_eq!(
+            parse("https://huggingface.co/google-bert/bert-base-uncased"),
+            Some((RepoKind::Model, "google-bert/bert-base-uncased", None))
+        );
+        assert_eq!(
+            parse("https://hf.co/meta-llama/Llama-3.1-8B/tree/0e9e39f"),
+            Some((RepoKind::Model, "meta-llama/Llama-3.1-8B", Some("0e9e39f")))
+        );
+        assert_eq!(
+            parse("https://huggingface.co/gpt2/blob/main/config.json"),
+            Some((RepoKind::Model, "gpt2", Some("main")))
+        );
+        assert_eq!(
+            parse("https://huggingface.co/datasets/rajpurkar/squad"),
+            Some((RepoKind::Dataset, "rajpurkar/squad", None))
+        );
+        assert_eq!(
+            parse("https://huggingface.co/bert-base-uncased"),
+            Some((RepoKind::Model, "bert-base-uncased", None))
+        );
+        for bad in [
+            "https://huggingface.co/google",
+            "https://huggingface.co/meta-llama",
+            "https://huggingface.co/spaces/owner/app",
+            "https://huggingface.co/docs/transformers",
+            "https://huggingface.co/",
+            "https://example.com/google-bert/bert-base-uncased",
+        ] {
+            assert!(parse(bad).is_none(), "should reject {bad}");
+        }
+    }
+
+    #[test]
+    fn card_citation_block_is_preferred() {
+        let card = "# Model\n\nSome text.\n\n```python\nprint(1)\n```\n\n## Citation\n\n```bibtex\n@article{devlin2018bert,\n  title={BERT},\n  author={Devlin, Jacob},\n  journal={arXiv preprint arXiv:1810.04805},\n  year={2018}\n}\n```\n";
+        let entry = citation_from_card(card).unwrap();
+        assert_eq!(entry.key, "devlin2018bert");
+        assert!(citation_from_card("# No citation here\n").is_none());
+    }
+
+    #[test]
+    fn card_citation_keeps_pinned_revision() {
+        let mut entry = first_entry("@article{x, title={BERT}, note={Preprint}}").unwrap();
+        note_revision(&mut entry, "abc123");
+        let bib = entry.to_biblatex_string();
+        assert!(
+            bib.contains("note = {Preprint. Hugging Face revision abc123}"),
+            "{bib}"
+        );
+    }
+
+    #[test]
+    fn hub_metadata_fallback_pins_revision() {
+        let info: Value = serde_json::from_str(
+            r#"{"id": "acme/tiny", "author": "acme", "sha": "abc123",
+                "lastModified": "2024-04-01T12:00:00.000Z",
+                "cardData": {"license": "apache-2.0"},
+                "tags": ["transformers", "license:apache-2.0", "region:us"]}"#,
+        )
+        .unwrap();
+        let bib = build_biblatex(
+            RepoKind::Model,
+            "acme/tiny",
+            &info,
+            "https://huggingface.co/acme/tiny",
+        );
+        assert!(bib.starts_with("@software{hf:acme/tiny,"), "{bib}");
+        assert!(bib.contains("author = {{acme}}"), "{bib}");
+        assert!(bib.contains("version = {abc123}"), "{bib}");
+        assert!(
+            bib.contains("url = {https://huggingface.co/acme/tiny/tree/abc123}"),
+            "{bib}"
+        );
+        assert!(bib.contains("keywords = {transformers}"), "{bib}");
+        assert!(Bibliography::parse(&bib).is_ok());
+    }
+}
@@ -0,0 +1,189 @@
+use anyhow::Context;
+use biblatex::{Bibliography, Chunk, Entry, Spanned};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{identifier::Identifier, resolver::IdFamily};
+
+/// Default INSPIRE-HEP root; override with `BIB_INSPIRE_BASE_URL` (e.g., for a mirror or a mock).
+const DEFAULT_BASE_URL: &str = "https://inspirehep.net";
+
+/// An INSPIRE-HEP texkey (e.g., `Maldacena:1997re`) or literature record URL.
+pub struct Inspire<'a> {
+    target: InspireTarget<'a>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum InspireTarget<'a> {
+    Texkey(&'a str),
+    /// Numeric literature record ID from a URL.
+    Recid(&'a str),
+}
+
+static TEXKEY_RE: Lazy<Regex> = Lazy::new(|| {
+    // Author or collaboration, year and two or three random letters, e.g. ATLAS:2012yve
+    Regex::new(r"^[A-Za-z][A-Za-z0-9'.-]*:(?:19|20)\d{2}[a-z]{2,3}$").unwrap()
+});
+
+impl<'a> Identifier<'a> for Inspire<'a> {
+    fn parse(identifier: &'a str) -> Option<Box<Self>> {
+        let s = identifier.trim();
+
+        // URLs:
+        //  - https://inspirehep.net/literature/451647
+        //  - https://inspirehep.net/api/literature/451647
+        //  - https://inspirehep.net/record/451647 (legacy)
+        if let Some((host, path)) = s
+            .strip_prefix("http://")
+            .or_else(|| s.strip_prefix("https://"))
+            .and_then(|rest| rest.split_once('/'))
+        {
+            if !matches!(
+                host.to_ascii_lowercase().as_str(),
+                "inspirehep.net" | "www.inspirehep.net" | "old.inspirehep.net"
+            ) {
+                return None;
+            }
+            let path = path.split(['?', '#']).next().unwrap_or(path);
+            let path = path.strip_prefix("api/").unwrap_or(path);
+            let recid = ["literature/", "record/"]
+                .iter()
+                .find_map(|p| path.strip_prefix(p))?
+                .trim_end_matches('/');
+            let recid = recid.split('/').next()?;
+            return (!recid.is_empty() && recid.bytes().all(|b| b.is_ascii_digit())).then(|| {
+                Box::new(Inspire {
+                    target: InspireTarget::Recid(recid),
+                })
+            });
+        }
+
+        let key = ["inspire:", "INSPIRE:", "texkey:"]
+            .iter()
+            .find_map(|p| s.strip_prefix(p))
+            .map(str::trim_start)
+            .unwrap_or(s);
+        TEXKEY_RE.is_match(key).then(|| {
+            Box::new(Inspire {
+                target: InspireTarget::Texkey(key),
+            })
+        })
+    }
+
+    fn resolve(&self) -> anyhow::Result<Entry> {
+        let base =
+            std::env::var("BIB_INSPIRE_BASE_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.into());
+        let base = base.trim_end_matches('/');
+        let url = match self.target {
+            InspireTarget::Texkey(key) => {
+                let mut url = url::Url::parse(&format!("{base}/api/literature"))?;
+                url.query_pairs_mut()
+                    .append_pair("q", &format!("texkeys:\"{key}\""))
+                    .append_pair("format", "bibtex");
+                url.to_string()
+            }
+            InspireTarget::Recid(recid) => format!("{base}/api/literature/{recid}?format=bibtex"),
+        };
+
+        let cfg = ureq::Agent::config_builder()
+            .timeout_connect(Some(std::time::Duration::from_secs(5)))
+            .timeout_global(Some(std::time::Duration::from_secs(15)))
+            .build();
+        let agent = ureq::Agent::new_with_config(cfg);
+        let body: String = agent
+            .get(&url)
+            .header(
+                "User-Agent",
+                "Mozilla/5.0 (compatible; bib/0.1; +https://inspirehep.net)",
+            )
+            .call()
+            .with_context(|| format!("failed INSPIRE request for {url}"))?
+            .into_body()
+            .read_to_string()
+            .context("failed to read INSPIRE response body")?;
+        parse_inspire_bibtex(&body)
+    }
+}
+
+impl IdFamily for Inspire<'_> {
+    type For<'a> = Inspire<'a>;
+}
+
+/// Parse INSPIRE's BibTeX export and keep the texkey (the entry key) in its own field.
+fn parse_inspire_bibtex(body: &str) -> anyhow::Result<Entry> {
+    let bib = Bibliography::parse(body)
+        .map_err(|e| anyhow::anyhow!("failed to parse INSPIRE BibTeX: {e}"))?;
+    let mut entry = bib
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("no INSPIRE record found"))?;
+    let texkey = entry.key.clone();
+    entry.set("texkey", vec![Spanned::zero(Chunk::Normal(texkey))]);
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Option<InspireTarget<'_>> {
+        <Inspire<'_> as Identifier<'_>>::parse(s).map(|i| i.target)
+    }
+
+    #[test]
+    fn parse_texkeys_and_urls() {
+        assert_eq!(
+            parse("Maldacena:1997re"),
+            Some(InspireTarget::Texkey("Maldacena:1997re"))
+        );
+        assert_eq!(
+            parse("inspire:ATLAS:2012yve"),
+            Some(InspireTarget::Texkey("ATLAS:2012yve"))
+        );
+        for input in [
+            "https://inspirehep.net/literature/451647",
+            "https://inspirehep.net/api/literature/451647?format=json",
+            "https://old.inspirehep.net/record/451647/",
+        ] {
+            assert_eq!(
+                parse(input),
+                Some(InspireTarget::Recid("451647")),
+                "input {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_rejects_other_inputs() {
+        for bad in [
+            "Maldacena:97re",
+            "Maldacena:1997",
+            "DBLP:conf/osdi/SmithJ20",
+            "https://inspirehep.net/authors/1010907",
+            "https://example.com/literature/451647",
+        ] {
+            assert!(parse(bad).is_none(), "should reject {bad}");
+        }
+    }
+
+    #[test]
+    fn inspire_bibtex_keeps_texkey() {
+        let body = r#"@article{Maldacena:1997re,
+    author = "Maldacena, Juan Martin",
+    title = "{The Large $N$ limit of superconformal field theories and supergravity}",
+    eprint = "hep-th/9711200",
+    archivePrefix = "arXiv",
+    doi = "10.1023/A:1026654312961",
+    journal = "Adv. Theor. Math. Phys.",
+    volume = "2",
+    pages = "231--252",
+    year = "1998"
+}
+"#;
+        let entry = parse_inspire_bibtex(body).unwrap();
+        assert_eq!(entry.key, "Maldacena:1997re");
+        let out = entry.to_biblatex_string();
+        assert!(out.contains("texkey = {Maldacena:1997re}"), "{out}");
+        assert!(entry.get("eprint").is_some());
+    }
+}
@@ -1,13 +1,34 @@
 use biblatex::Entry;
 
+pub mod acl;
+pub mod ads;
 pub mod arxiv;
+pub mod dblp;
 pub mod doi;
 pub mod embedded;
+pub mod figshare;
+pub mod huggingface;
+pub mod inspire;
+pub mod openreview;
+pub mod pubmed;
+pub mod registry;
+pub mod repository;
+pub mod rfc;
+pub mod swh;
 pub mod usenix;
+pub mod zenodo;
 
 pub trait Identifier<'a>: 'a {
     fn parse(identifier: &'a str) -> Option<Box<Self>>
     where
         Self: Sized;
     fn resolve(&self) -> anyhow::Result<Entry>;
+    /// Resolve every item behind this identifier. Listing pages (e.g., a DBLP person page)
+    /// override this; single-item identifiers yield exactly one entry.
+    ///
+    /// `limit` is how many items the caller wants (`None` for all). Sources that page their
+    /// results need not fetch more; others may return more than asked.
+    fn resolve_many(&self, _limit: Option<usize>) -> anyhow::Result<Vec<Entry>> {
+        self.resolve().map(|e| vec![e])
+    }
 }
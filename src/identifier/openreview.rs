@@ -0,0 +1,391 @@
+use anyhow::Context;
+use biblatex::{Bibliography, Entry};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{
+    bibtex::{escape_braces, normalize_ws, write_entry},
+    identifier::Identifier,
+    resolver::IdFamily,
+};
+
+/// An OpenReview submission, from a `forum?id=...` or `pdf?id=...` URL.
+pub struct OpenReview {
+    id: String,
+}
+
+impl<'a> Identifier<'a> for OpenReview {
+    fn parse(identifier: &'a str) -> Option<Box<Self>> {
+        let url = url::Url::parse(identifier.trim()).ok()?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return None;
+        }
+        let host = url.host_str()?.to_ascii_lowercase();
+        if host != "openreview.net" && host != "www.openreview.net" {
+            return None;
+        }
+        if !matches!(
+            url.path().trim_end_matches('/'),
+            "/forum" | "/pdf" | "/attachment"
+        ) {
+            return None;
+        }
+        static ID_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[A-Za-z0-9_-]{5,}$").unwrap());
+        let id = url
+            .query_pairs()
+            .find(|(k, _)| k == "id")
+            .map(|(_, v)| v.into_owned())
+            .filter(|v| ID_RE.is_match(v))?;
+        Some(Box::new(OpenReview { id }))
+    }
+
+    fn resolve(&self) -> anyhow::Result<Entry> {
+        // API v2 hosts current venues; older submissions are only served by the v1 API.
+        let note = match fetch_note("https://api2.openreview.net", &self.id)? {
+            Some(n) => n,
+            None => fetch_note("https://api.openreview.net", &self.id)?
+                .ok_or_else(|| anyhow::anyhow!("no OpenReview note found for id {}", self.id))?,
+        };
+        let meta = parse_note(&note, &self.id)?;
+
+        let bib = build_biblatex(&meta);
+        let bib = Bibliography::parse(&bib)
+            .map_err(|e| anyhow::anyhow!("failed to parse constructed BibLaTeX: {e}"))?;
+        let entry = bib.iter().next().cloned().ok_or_else(|| {
+            anyhow::anyhow!("empty bibliography from constructed OpenReview record")
+        })?;
+        Ok(entry)
+    }
+}
+
+impl IdFamily for OpenReview {
+    type For<'a> = OpenReview;
+}
+
+fn fetch_note(api_base: &str, id: &str) -> anyhow::Result<Option<serde_json::Value>> {
+    let mut url = url::Url::parse(&format!("{api_base}/notes"))?;
+    url.query_pairs_mut().append_pair("id", id);
+    let cfg = ureq::Agent::config_builder()
+        .timeout_connect(Some(std::time::Duration::from_secs(5)))
+        .timeout_global(Some(std::time::Duration::from_secs(15)))
+        .http_status_as_error(false)
+        .build();
+    let agent = ureq::Agent::new_with_config(cfg);
+    let res = agent
+        .get(url.as_str())
+        .header(
+            "User-Agent",
+            "Mozilla/5.0 (compatible; bib/0.1; +https://openreview.net)",
+        )
+        .call()
+        .with_context(|| format!("failed OpenReview request for id {id}"))?;
+    // Unknown IDs come back as 4xx from one API version; let the caller try the other.
+    if res.status().is_client_error() {
+        return Ok(None);
+    }
+    if !res.status().is_success() {
+        return Err(anyhow::anyhow!(
+            "OpenReview API returned {} for id {id}",
+            res.status()
+        ));
+    }
+    let body = res
+        .into_body()
+        .read_to_string()
+        .context("failed to read OpenReview response body")?;
+    let v: serde_json::Value =
+        serde_json::from_str(&body).context("invalid JSON from OpenReview API")?;
+    Ok(v.get("notes")
+        .and_then(|n| n.as_array())
+        .and_then(|n| n.first())
+        .cloned())
+}
+
+/// Where a submission ended up, derived from its `venueid`/`venue`.
+#[derive(Debug, PartialEq, Eq)]
+enum Decision {
+    Accepted,
+    Rejected,
+    DeskRejected,
+    Withdrawn,
+    UnderReview,
+}
+
+/// Normalised OpenReview metadata we care about.
+#[derive(Debug)]
+struct OpenReviewMeta {
+    id: String,
+    title: String,
+    authors: Vec<String>,
+    venue: Option<String>,
+    venueid: Option<String>,
+    year: Option<String>,
+    abstract_: Option<String>,
+    keywords: Vec<String>,
+    decision: Decision,
+}
+
+/// Read a content field, which is `{"value": x}` in API v2 and a bare `x` in API v1.
+fn content_field<'v>(note: &'v serde_json::Value, key: &str) -> Option<&'v serde_json::Value> {
+    let v = note.get("content")?.get(key)?;
+    Some(v.get("value").unwrap_or(v))
+}
+
+fn content_str(note: &serde_json::Value, key: &str) -> Option<String> {
+    content_field(note, key)
+        .and_then(|v| v.as_str())
+        .map(normalize_ws)
+        .filter(|s| !s.is_empty())
+}
+
+fn content_list(note: &serde_json::Value, key: &str) -> Vec<String> {
+    content_field(note, key)
+        .and_then(|v| v.as_array())
+        .map(|a| {
+            a.iter()
+                .filter_map(|x| x.as_str())
+                .map(normalize_ws)
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn parse_note(note: &serde_json::Value, id: &str) -> anyhow::Result<OpenReviewMeta> {
+    let title = content_str(note, "title")
+        .ok_or_else(|| anyhow::anyhow!("OpenReview note {id} has no title"))?;
+    let venue = content_str(note, "venue");
+    let venueid = content_str(note, "venueid");
+    let decision = classify(venue.as_deref(), venueid.as_deref());
+
+    // Prefer the publication date, then the creation date (both epoch milliseconds).
+    let year = ["pdate", "odate", "cdate", "tcdate"]
+        .iter()
+        .find_map(|k| note.get(*k).and_then(|v| v.as_i64()))
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .map(|d| d.format("%Y").to_string())
+        .or_else(|| {
+            static YEAR_RE: Lazy<Regex> =
+                Lazy::new(|| Regex::new(r"\b((?:19|20)\d{2})\b").unwrap());
+            venueid
+                .as_deref()
+                .or(venue.as_deref())
+                .and_then(|v| YEAR_RE.captures(v))
+                .map(|c| c[1].to_string())
+        });
+
+    Ok(OpenReviewMeta {
+        id: id.to_string(),
+        title,
+        authors: content_list(note, "authors"),
+        venue,
+        venueid,
+        year,
+        abstract_: content_str(note, "abstract"),
+        keywords: content_list(note, "keywords"),
+        decision,
+    })
+}
+
+fn classify(venue: Option<&str>, venueid: Option<&str>) -> Decision {
+    let vid = venueid.unwrap_or_default().to_ascii_lowercase();
+    let v = venue.unwrap_or_default().to_ascii_lowercase();
+    if vid.contains("desk_rejected") || v.contains("desk rejected") {
+        Decision::DeskRejected
+    } else if vid.contains("withdrawn") || v.contains("withdrawn") {
+        Decision::Withdrawn
+    } else if vid.contains("rejected") || v.contains("rejected") {
+        Decision::Rejected
+    } else if vid.ends_with("/submission")
+        || v.starts_with("submitted to")
+        || (vid.is_empty() && v.is_empty())
+    {
+        Decision::UnderReview
+    } else {
+        Decision::Accepted
+    }
+}
+
+/// Split "ICLR 2024 poster" into the venue ("ICLR 2024") and presentation type ("Poster").
+fn split_presentation(venue: &str) -> (String, Option<String>) {
+    static TRACK_RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"(?i)^(?P<venue>.*?)\s+(?P<track>poster|oral|spotlight|notable top \d+%|notable top-\d+%)$")
+            .unwrap()
+    });
+    match TRACK_RE.captures(venue) {
+        Some(c) => {
+            let track = &c["track"];
+            let mut cs = track.chars();
+            let track = match cs.next() {
+                Some(f) => f.to_uppercase().chain(cs).collect(),
+                None => String::new(),
+            };
+            (c["venue"].to_string(), Some(track))
+        }
+        None => (venue.to_string(), None),
+    }
+}
+
+/// Best-effort human venue name for a submission that was not accepted ("ICLR 2024").
+fn submission_venue(meta: &OpenReviewMeta) -> Option<String> {
+    if let Some(vid) = &meta.venueid {
+        // e.g. "ICLR.cc/2024/Conference/Rejected_Submission"
+        let mut parts = vid.split('/');
+        let org = parts.next()?.trim_end_matches(".cc");
+        if let Some(year) = parts
+            .next()
+            .filter(|y| y.chars().all(|c| c.is_ascii_digit()))
+        {
+            return Some(format!("{org} {year}"));
+        }
+        return Some(org.to_string());
+    }
+    meta.venue.clone()
+}
+
+fn build_biblatex(meta: &OpenReviewMeta) -> String {
+    let mut fields: Vec<(&str, String)> = Vec::new();
+    fields.push(("title", escape_braces(&meta.title)));
+    if !meta.authors.is_empty() {
+        let authors: Vec<String> = meta.authors.iter().map(|a| escape_braces(a)).collect();
+        fields.push(("author", authors.join(" and ")));
+    }
+    if let Some(y) = &meta.year {
+        fields.push(("year", escape_braces(y)));
+    }
+
+    let entry_ty = if meta.decision == Decision::Accepted {
+        if let Some(v) = &meta.venue {
+            let (booktitle, track) = split_presentation(v);
+            fields.push(("booktitle", escape_braces(&booktitle)));
+            if let Some(t) = track {
+                fields.push(("note", escape_braces(&t)));
+            }
+        }
+        "inproceedings"
+    } else {
+        // Not (yet) published anywhere: say so plainly rather than passing it off as a paper.
+        let (state, label) = match meta.decision {
+            Decision::Rejected => ("rejected", "Rejected submission"),
+            Decision::DeskRejected => ("rejected", "Desk-rejected submission"),
+            Decision::Withdrawn => ("withdrawn", "Withdrawn submission"),
+            _ => ("submitted", "Submitted"),
+        };
+        fields.push(("pubstate", state.to_string()));
+        let note = match submission_venue(meta) {
+            Some(v) => format!("{label} to {v}"),
+            None => label.to_string(),
+        };
+        fields.push(("note", escape_braces(&note)));
+        fields.push(("howpublished", "OpenReview".to_string()));
+        "unpublished"
+    };
+
+    if let Some(a) = &meta.abstract_ {
+        fields.push(("abstract", escape_braces(a)));
+    }
+    if !meta.keywords.is_empty() {
+        fields.push(("keywords", escape_braces(&meta.keywords.join(", "))));
+    }
+    fields.push((
+        "url",
+        escape_braces(&format!("https://openreview.net/forum?id={}", meta.id)),
+    ));
+
+    write_entry(entry_ty, &format!("openreview:{}", meta.id), fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(json: &str) -> serde_json::Value {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn parse_forum_and_pdf_urls() {
+        for input in [
+            "https://openreview.net/forum?id=YicbFdNTTy",
+            "https://openreview.net/pdf?id=YicbFdNTTy",
+            "https://openreview.net/forum?id=YicbFdNTTy&noteId=abcdef",
+        ] {
+            let o = <OpenReview as Identifier>::parse(input).expect("should parse");
+            assert_eq!(o.id, "YicbFdNTTy");
+        }
+        for bad in [
+            "https://openreview.net/group?id=ICLR.cc/2024/Conference",
+            "https://openreview.net/forum",
+            "https://example.com/forum?id=YicbFdNTTy",
+        ] {
+            assert!(
+                <OpenReview as Identifier>::parse(bad).is_none(),
+                "should reject {bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn accepted_v2_note_is_inproceedings() {
+        let n = note(
+            r#"{"id": "YicbFdNTTy", "pdate": 1705000000000, "content": {
+                "title": {"value": "An Image is Worth 16x16 Words"},
+                "authors": {"value": ["Alexey Dosovitskiy", "Lucas Beyer"]},
+                "venue": {"value": "ICLR 2021 Oral"},
+                "venueid": {"value": "ICLR.cc/2021/Conference"},
+                "keywords": {"value": ["vision", "transformers"]}
+            }}"#,
+        );
+        let meta = parse_note(&n, "YicbFdNTTy").unwrap();
+        assert_eq!(meta.decision, Decision::Accepted);
+        let bib = Bibliography::parse(&build_biblatex(&meta)).unwrap();
+        let e = bib.iter().next().unwrap();
+        assert_eq!(e.entry_type.to_string(), "inproceedings");
+        assert!(e.get("booktitle").is_some());
+        assert!(e.get("pubstate").is_none());
+    }
+
+    #[test]
+    fn rejected_v1_note_is_marked() {
+        let n = note(
+            r#"{"id": "abc123XYZ", "cdate": 1600000000000, "content": {
+                "title": "A Rejected Idea",
+                "authors": ["Jane Doe"],
+                "venue": "Submitted to ICLR 2021",
+                "venueid": "ICLR.cc/2021/Conference/Rejected_Submission"
+            }}"#,
+        );
+        let meta = parse_note(&n, "abc123XYZ").unwrap();
+        assert_eq!(meta.decision, Decision::Rejected);
+        let bib = build_biblatex(&meta);
+        assert!(bib.starts_with("@unpublished{openreview:abc123XYZ"));
+        assert!(bib.contains("note = {Rejected submission to ICLR 2021}"));
+        assert!(bib.contains("pubstate = {rejected}"));
+    }
+
+    #[test]
+    fn classify_decisions() {
+        assert_eq!(
+            classify(None, Some("ICLR.cc/2024/Conference/Withdrawn_Submission")),
+            Decision::Withdrawn
+        );
+        assert_eq!(
+            classify(
+                None,
+                Some("ICLR.cc/2024/Conference/Desk_Rejected_Submission")
+            ),
+            Decision::DeskRejected
+        );
+        assert_eq!(
+            classify(
+                Some("Submitted to ICLR 2025"),
+                Some("ICLR.cc/2025/Conference/Submission")
+            ),
+            Decision::UnderReview
+        );
+        assert_eq!(
+            split_presentation("ICLR 2024 poster"),
+            ("ICLR 2024".to_string(), Some("Poster".to_string()))
+        );
+    }
+}
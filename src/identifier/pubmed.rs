@@ -0,0 +1,711 @@
+use anyhow::Context;
+use biblatex::Entry;
+use once_cell::sync::Lazy;
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use regex::Regex;
+
+use crate::{
+    bibtex::{escape_braces, month_number, normalize_ws, parse_single, write_entry},
+    identifier::Identifier,
+    resolver::IdFamily,
+};
+
+const EUTILS_BASE: &str = "https://eutils.ncbi.nlm.nih.gov/entrez/eutils";
+
+/// A PubMed (PMID) or PubMed Central (PMCID) identifier or URL.
+pub struct Pubmed<'a> {
+    id: PubmedId<'a>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum PubmedId<'a> {
+    /// Bare PubMed ID digits (e.g., "31452104").
+    Pmid(&'a str),
+    /// PMC ID digits without the "PMC" prefix (e.g., "6790431").
+    Pmcid(&'a str),
+}
+
+impl<'a> Identifier<'a> for Pubmed<'a> {
+    fn parse(identifier: &'a str) -> Option<Box<Self>> {
+        let s = identifier.trim();
+
+        // URLs:
+        //  - https://pubmed.ncbi.nlm.nih.gov/31452104/
+        //  - https://www.ncbi.nlm.nih.gov/pubmed/31452104
+        //  - https://www.ncbi.nlm.nih.gov/pmc/articles/PMC6790431/
+        //  - https://pmc.ncbi.nlm.nih.gov/articles/PMC6790431/
+        if let Some((host, path)) = s
+            .strip_prefix("http://")
+            .or_else(|| s.strip_prefix("https://"))
+            .and_then(|rest| rest.split_once('/'))
+        {
+            let host = host.to_ascii_lowercase();
+            let path = path.split(['?', '#']).next().unwrap_or(path);
+            let path = path.trim_matches('/');
+            let id = if host == "pubmed.ncbi.nlm.nih.gov" {
+                PMID_RE
+                    .captures(path)
+                    .and_then(|c| c.get(1))
+                    .map(|m| PubmedId::Pmid(m.as_str()))
+            } else if host == "www.ncbi.nlm.nih.gov" || host == "ncbi.nlm.nih.gov" {
+                if let Some(rest) = path.strip_prefix("pubmed/") {
+                    PMID_RE
+                        .captures(rest)
+                        .and_then(|c| c.get(1))
+                        .map(|m| PubmedId::Pmid(m.as_str()))
+                } else if let Some(rest) = path.strip_prefix("pmc/articles/") {
+                    PMCID_RE
+                        .captures(rest)
+                        .and_then(|c| c.get(1))
+                        .map(|m| PubmedId::Pmcid(m.as_str()))
+                } else {
+                    None
+                }
+            } else if host == "pmc.ncbi.nlm.nih.gov" {
+                path.strip_prefix("articles/")
+                    .and_then(|rest| PMCID_RE.captures(rest))
+                    .and_then(|c| c.get(1))
+                    .map(|m| PubmedId::Pmcid(m.as_str()))
+            } else {
+                None
+            };
+            return id.map(|id| Box::new(Pubmed { id }));
+        }
+
+        // Textual forms: "PMID: 31452104", "pmid:31452104", "PMID 31452104".
+        static PMID_PREFIXED_RE: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"(?i)^pmid\s*[: ]\s*([0-9]{1,9})$").unwrap());
+        if let Some(c) = PMID_PREFIXED_RE.captures(s) {
+            return Some(Box::new(Pubmed {
+                id: PubmedId::Pmid(c.get(1)?.as_str()),
+            }));
+        }
+
+        // "PMC6790431" (case-sensitive so arbitrary words do not match), "PMCID: PMC6790431".
+        static PMCID_PREFIXED_RE: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"(?i)^pmcid\s*:?\s*(?:pmc)?([0-9]{1,9})$").unwrap());
+        static PMCID_BARE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^PMC([0-9]{4,9})$").unwrap());
+        if let Some(c) = PMCID_PREFIXED_RE
+            .captures(s)
+            .or_else(|| PMCID_BARE_RE.captures(s))
+        {
+            return Some(Box::new(Pubmed {
+                id: PubmedId::Pmcid(c.get(1)?.as_str()),
+            }));
+        }
+
+        None
+    }
+
+    fn resolve(&self) -> anyhow::Result<Entry> {
+        let pmid = match self.id {
+            PubmedId::Pmid(p) => p.to_string(),
+            PubmedId::Pmcid(p) => pmcid_to_pmid(p)?,
+        };
+        let xml = fetch_pubmed_xml(&pmid)?;
+        let meta = parse_pubmed_article(&xml, &pmid)?;
+
+        parse_single(&build_biblatex(&meta))
+    }
+}
+
+impl IdFamily for Pubmed<'_> {
+    type For<'a> = Pubmed<'a>;
+}
+
+static PMID_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^([0-9]{1,9})$").unwrap());
+static PMCID_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^PMC([0-9]{1,9})$").unwrap());
+
+/// Normalised PubMed metadata we care about.
+#[derive(Debug, Default)]
+struct PubmedMeta {
+    pmid: String,
+    title: String,
+    abstract_parts: Vec<String>,
+    /// Authors as "Last, First" or collective names.
+    authors: Vec<String>,
+    journal: Option<String>,
+    journal_abbrev: Option<String>,
+    issn: Option<String>,
+    volume: Option<String>,
+    issue: Option<String>,
+    pages: Option<String>,
+    year: Option<String>,
+    month: Option<String>,
+    day: Option<String>,
+    medline_date: Option<String>,
+    language: Option<String>,
+    doi: Option<String>,
+    pmcid: Option<String>,
+    mesh: Vec<String>,
+}
+
+fn agent() -> ureq::Agent {
+    let cfg = ureq::Agent::config_builder()
+        .timeout_connect(Some(std::time::Duration::from_secs(5)))
+        .timeout_global(Some(std::time::Duration::from_secs(15)))
+        .build();
+    ureq::Agent::new_with_config(cfg)
+}
+
+fn eutils_get(url: &url::Url, what: &str) -> anyhow::Result<String> {
+    let body: String = agent()
+        .get(url.as_str())
+        .header(
+            "User-Agent",
+            "Mozilla/5.0 (compatible; bib/0.1; +https://www.ncbi.nlm.nih.gov)",
+        )
+        .call()
+        .with_context(|| format!("failed E-utilities request for {what}"))?
+        .into_body()
+        .read_to_string()
+        .context("failed to read E-utilities response body")?;
+    Ok(body)
+}
+
+fn fetch_pubmed_xml(pmid: &str) -> anyhow::Result<String> {
+    let mut url = url::Url::parse(&format!("{EUTILS_BASE}/efetch.fcgi"))?;
+    url.query_pairs_mut()
+        .append_pair("db", "pubmed")
+        .append_pair("id", pmid)
+        .append_pair("retmode", "xml")
+        .append_pair("tool", "bib");
+    eutils_get(&url, &format!("PMID {pmid}"))
+}
+
+/// Map a PMC ID (digits only) to its PMID via an E-utilities search on the `pmcid` field.
+fn pmcid_to_pmid(pmcid: &str) -> anyhow::Result<String> {
+    let mut url = url::Url::parse(&format!("{EUTILS_BASE}/esearch.fcgi"))?;
+    url.query_pairs_mut()
+        .append_pair("db", "pubmed")
+        .append_pair("term", &format!("PMC{pmcid}[pmcid]"))
+        .append_pair("tool", "bib");
+    let xml = eutils_get(&url, &format!("PMC{pmcid}"))?;
+    parse_esearch_first_id(&xml)?
+        .ok_or_else(|| anyhow::anyhow!("no PubMed record found for PMC{pmcid}"))
+}
+
+fn parse_esearch_first_id(xml: &str) -> anyhow::Result<Option<String>> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+    let mut path: Vec<Vec<u8>> = Vec::new();
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Eof) => break,
+            Ok(Event::Start(e)) => path.push(e.name().as_ref().to_vec()),
+            Ok(Event::End(_)) => {
+                path.pop();
+            }
+            Ok(Event::Text(t)) if path_ends_with(&path, &["IdList", "Id"]) => {
+                let id = String::from_utf8_lossy(t.as_ref()).trim().to_string();
+                if !id.is_empty() {
+                    return Ok(Some(id));
+                }
+            }
+            Err(e) => return Err(anyhow::anyhow!("XML parse error: {e}")),
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(None)
+}
+
+fn path_ends_with(path: &[Vec<u8>], tail: &[&str]) -> bool {
+    path.len() >= tail.len()
+        && path[path.len() - tail.len()..]
+            .iter()
+            .zip(tail)
+            .all(|(a, b)| a.as_slice() == b.as_bytes())
+}
+
+/// Elements whose text content is read. Text accumulates through any other element nested in
+/// them, so inline markup (`<i>`, `<sup>`, ...) in titles and abstracts is kept.
+const TEXT_ELEMENTS: &[&[u8]] = &[
+    b"ArticleTitle",
+    b"AbstractText",
+    b"LastName",
+    b"ForeName",
+    b"CollectiveName",
+    b"Author",
+    b"Title",
+    b"ISOAbbreviation",
+    b"ISSN",
+    b"Volume",
+    b"Issue",
+    b"Year",
+    b"Month",
+    b"Day",
+    b"MedlineDate",
+    b"MedlinePgn",
+    b"Language",
+    b"DescriptorName",
+    b"ELocationID",
+    b"ArticleId",
+];
+
+fn parse_pubmed_article(xml: &str, pmid: &str) -> anyhow::Result<PubmedMeta> {
+    // Text is not trimmed here: entity references split text events, and trimming each piece
+    // would eat the spaces around them. Whitespace is normalised per element instead.
+    let mut reader = Reader::from_str(xml);
+
+    let mut meta = PubmedMeta {
+        pmid: pmid.to_string(),
+        ..Default::default()
+    };
+    let mut path: Vec<Vec<u8>> = Vec::new();
+    let mut cur_text = String::new();
+    // Attributes of the innermost element that matter to us.
+    let mut abstract_label: Option<String> = None;
+    let mut article_id_type: Option<String> = None;
+    let mut elocation_type: Option<String> = None;
+    // Author name parts, flushed at </Author>.
+    let mut last_name: Option<String> = None;
+    let mut fore_name: Option<String> = None;
+    let mut collective: Option<String> = None;
+    let mut seen_article = false;
+
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Eof) => break,
+            Ok(Event::Start(e)) => {
+                let name = e.name().as_ref().to_vec();
+                match name.as_slice() {
+                    b"PubmedArticle" if seen_article => break, // only the first article
+                    b"PubmedArticle" => seen_article = true,
+                    b"AbstractText" => abstract_label = get_attr_value(&e, b"Label"),
+                    b"ArticleId" => article_id_type = get_attr_value(&e, b"IdType"),
+                    b"ELocationID" => elocation_type = get_attr_value(&e, b"EIdType"),
+                    _ => {}
+                }
+                if TEXT_ELEMENTS.contains(&name.as_slice()) {
+                    cur_text.clear();
+                }
+                path.push(name);
+            }
+            // Closing inline markup (<i>, <sup>, ...) keeps the text gathered so far.
+            Ok(Event::End(e)) if !TEXT_ELEMENTS.contains(&e.name().as_ref()) => {
+                path.pop();
+            }
+            Ok(Event::End(e)) => {
+                let text = normalize_ws(&cur_text);
+                match e.name().as_ref() {
+                    b"ArticleTitle" if path_ends_with(&path, &["Article", "ArticleTitle"]) => {
+                        meta.title = text.trim_end_matches('.').to_string();
+                    }
+                    b"AbstractText" if !text.is_empty() => match abstract_label.take() {
+                        Some(l) => meta.abstract_parts.push(format!("{l}: {text}")),
+                        None => meta.abstract_parts.push(text),
+                    },
+                    b"LastName" => last_name = Some(text),
+                    b"ForeName" => fore_name = Some(text),
+                    b"CollectiveName" => collective = Some(text),
+                    b"Author" if path_ends_with(&path, &["AuthorList", "Author"]) => {
+                        let name = match (last_name.take(), fore_name.take(), collective.take()) {
+                            (Some(l), Some(f), _) => Some(format!("{l}, {f}")),
+                            (Some(l), None, _) => Some(l),
+                            // Collective names are protected so BibLaTeX keeps them whole.
+                            (None, _, Some(c)) => Some(format!("{{{c}}}")),
+                            _ => None,
+                        };
+                        if let Some(n) = name {
+                            meta.authors.push(n);
+                        }
+                    }
+                    b"Title" if path_ends_with(&path, &["Journal", "Title"]) => {
+                        meta.journal = Some(text);
+                    }
+                    b"ISOAbbreviation" => meta.journal_abbrev = Some(text),
+                    b"ISSN" => {
+                        meta.issn.get_or_insert(text);
+                    }
+                    b"Volume" if path_ends_with(&path, &["JournalIssue", "Volume"]) => {
+                        meta.volume = Some(text);
+                    }
+                    b"Issue" if path_ends_with(&path, &["JournalIssue", "Issue"]) => {
+                        meta.issue = Some(text);
+                    }
+                    b"Year" if path_ends_with(&path, &["JournalIssue", "PubDate", "Year"]) => {
+                        meta.year = Some(text);
+                    }
+                    b"Month" if path_ends_with(&path, &["JournalIssue", "PubDate", "Month"]) => {
+                        meta.month = Some(text);
+                    }
+                    b"Day" if path_ends_with(&path, &["JournalIssue", "PubDate", "Day"]) => {
+                        meta.day = Some(text);
+                    }
+                    b"MedlineDate" => meta.medline_date = Some(text),
+                    b"MedlinePgn" if !text.is_empty() => meta.pages = Some(expand_pages(&text)),
+                    b"Language" => {
+                        meta.language.get_or_insert(text);
+                    }
+                    b"DescriptorName" if !text.is_empty() && !meta.mesh.contains(&text) => {
+                        meta.mesh.push(text);
+                    }
+                    b"ELocationID"
+                        if elocation_type.take().as_deref() == Some("doi") && !text.is_empty() =>
+                    {
+                        meta.doi.get_or_insert(text);
+                    }
+                    b"ArticleId"
+                        if path_ends_with(&path, &["PubmedData", "ArticleIdList", "ArticleId"]) =>
+                    {
+                        match article_id_type.take().as_deref() {
+                            Some("doi") if !text.is_empty() => meta.doi = Some(text),
+                            Some("pmc") if !text.is_empty() => meta.pmcid = Some(text),
+                            _ => {}
+                        }
+                    }
+                    _ => {}
+                }
+                path.pop();
+                cur_text.clear();
+            }
+            Ok(Event::Text(t)) => {
+                let raw = String::from_utf8_lossy(t.as_ref());
+                cur_text.push_str(&unescape_xml(&raw));
+            }
+            Ok(Event::GeneralRef(r)) => {
+                // quick-xml reports entity and character references (`&#x3b1;`) separately
+                // from text.
+                match r.resolve_char_ref() {
+                    Ok(Some(c)) => cur_text.push(c),
+                    _ => {
+                        let raw = format!("&{};", String::from_utf8_lossy(r.as_ref()));
+                        cur_text.push_str(&unescape_xml(&raw));
+                    }
+                }
+            }
+            Ok(Event::CData(t)) => {
+                cur_text.push_str(&String::from_utf8_lossy(t.as_ref()));
+            }
+            Err(e) => return Err(anyhow::anyhow!("XML parse error: {e}")),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    if meta.title.is_empty() && meta.authors.is_empty() {
+        return Err(anyhow::anyhow!("no PubMed record found for PMID {pmid}"));
+    }
+    Ok(meta)
+}
+
+fn get_attr_value(e: &BytesStart<'_>, key: &[u8]) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|a| a.key.local_name().as_ref() == key)
+        .map(|a| String::from_utf8_lossy(a.value.as_ref()).to_string())
+}
+
+fn unescape_xml(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// The babel/polyglossia name of a MEDLINE language code (ISO 639-2/B, e.g. "ger").
+fn langid(code: &str) -> Option<&'static str> {
+    Some(match code.to_ascii_lowercase().as_str() {
+        "afr" => "afrikaans",
+        "bul" => "bulgarian",
+        "cat" => "catalan",
+        "cze" => "czech",
+        "dan" => "danish",
+        "dut" => "dutch",
+        "eng" => "english",
+        "est" => "estonian",
+        "fin" => "finnish",
+        "fre" => "french",
+        "ger" => "german",
+        "gre" => "greek",
+        "heb" => "hebrew",
+        "hrv" => "croatian",
+        "hun" => "hungarian",
+        "ice" => "icelandic",
+        "ita" => "italian",
+        "lat" => "latin",
+        "lav" => "latvian",
+        "lit" => "lithuanian",
+        "nor" => "norwegian",
+        "pol" => "polish",
+        "por" => "portuguese",
+        "rum" => "romanian",
+        "rus" => "russian",
+        "slo" => "slovak",
+        "slv" => "slovene",
+        "spa" => "spanish",
+        "srp" => "serbian",
+        "swe" => "swedish",
+        "tur" => "turkish",
+        "ukr" => "ukrainian",
+        _ => return None,
+    })
+}
+
+/// Expand MEDLINE's abbreviated page ranges (e.g., "1757-86" -> "1757-1786").
+fn expand_pages(pgn: &str) -> String {
+    let Some((first, last)) = pgn.split_once('-') else {
+        return pgn.to_string();
+    };
+    let (first, last) = (first.trim(), last.trim());
+    let all_digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    if all_digits(first) && all_digits(last) && last.len() < first.len() {
+        let prefix = &first[..first.len() - last.len()];
+        format!("{first}-{prefix}{last}")
+    } else {
+        format!("{first}-{last}")
+    }
+}
+
+fn build_date(meta: &PubmedMeta) -> Option<String> {
+    let year = meta.year.clone().or_else(|| {
+        // MedlineDate is free text such as "1998 Dec-1999 Jan"; take the first year.
+        static YEAR_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(\d{4})\b").unwrap());
+        meta.medline_date
+            .as_deref()
+            .and_then(|d| YEAR_RE.captures(d))
+            .map(|c| c[1].to_string())
+    })?;
+    let Some(month) = meta.month.as_deref().and_then(month_number) else {
+        return Some(year);
+    };
+    match meta.day.as_deref().and_then(|d| d.parse::<u32>().ok()) {
+        Some(day) => Some(format!("{year}-{month:02}-{day:02}")),
+        None => Some(format!("{year}-{month:02}")),
+    }
+}
+
+fn build_biblatex(meta: &PubmedMeta) -> String {
+    let key = format!("PMID:{}", meta.pmid);
+    let url = format!("https://pubmed.ncbi.nlm.nih.gov/{}/", meta.pmid);
+
+    let mut fields: Vec<(&str, String)> = Vec::new();
+    fields.push(("title", escape_braces(&meta.title)));
+    if !meta.authors.is_empty() {
+        // Author names are assembled by us (collective names are deliberately braced).
+        fields.push(("author", meta.authors.join(" and ")));
+    }
+    if let Some(j) = &meta.journal {
+        fields.push(("journaltitle", escape_braces(j)));
+    }
+    if let Some(j) = &meta.journal_abbrev {
+        fields.push(("shortjournal", escape_braces(j)));
+    }
+    if let Some(d) = build_date(meta) {
+        fields.push(("date", d));
+    }
+    if let Some(v) = &meta.volume {
+        fields.push(("volume", escape_braces(v)));
+    }
+    if let Some(n) = &meta.issue {
+        fields.push(("number", escape_braces(n)));
+    }
+    if let Some(p) = &meta.pages {
+        fields.push(("pages", escape_braces(p)));
+    }
+    if !meta.abstract_parts.is_empty() {
+        fields.push(("abstract", escape_braces(&meta.abstract_parts.join(" "))));
+    }
+    if let Some(l) = meta.language.as_deref().and_then(langid) {
+        fields.push(("langid", l.to_string()));
+    }
+    if let Some(i) = &meta.issn {
+        fields.push(("issn", escape_braces(i)));
+    }
+    if let Some(d) = &meta.doi {
+        fields.push(("doi", escape_braces(d)));
+    }
+    fields.push(("eprinttype", "pubmed".to_string()));
+    fields.push(("eprint", meta.pmid.clone()));
+    fields.push(("pmid", meta.pmid.clone()));
+    if let Some(p) = &meta.pmcid {
+        fields.push(("pmcid", escape_braces(p)));
+    }
+    fields.push(("url", url));
+    if !meta.mesh.is_empty() {
+        fields.push(("keywords", escape_braces(&meta.mesh.join(", "))));
+    }
+
+    write_entry("article", &key, fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use biblatex::Bibliography;
+
+    const SAMPLE: &str = r#"<?xml version="1.0" ?>
+<PubmedArticleSet>
+<PubmedArticle>
+  <MedlineCitation Status="MEDLINE" Owner="NLM">
+    <PMID Version="1">31452104</PMID>
+    <Article PubModel="Print-Electronic">
+      <Journal>
+        <ISSN IssnType="Electronic">1546-170X</ISSN>
+        <JournalIssue CitedMedium="Internet">
+          <Volume>25</Volume>
+          <Issue>9</Issue>
+          <PubDate><Year>2019</Year><Month>Sep</Month></PubDate>
+        </JournalIssue>
+        <Title>Nature medicine</Title>
+        <ISOAbbreviation>Nat Med</ISOAbbreviation>
+      </Journal>
+      <ArticleTitle>A &amp; B: a study of things.</ArticleTitle>
+      <Pagination><MedlinePgn>1337-42</MedlinePgn></Pagination>
+      <ELocationID EIdType="doi" ValidYN="Y">10.1038/s41591-019-0548-6</ELocationID>
+      <Abstract>
+        <AbstractText Label="BACKGROUND">Some background.</AbstractText>
+        <AbstractText Label="RESULTS">Some results.</AbstractText>
+      </Abstract>
+      <AuthorList CompleteYN="Y">
+        <Author ValidYN="Y"><LastName>Smith</LastName><ForeName>Jane A</ForeName><Initials>JA</Initials></Author>
+        <Author ValidYN="Y"><CollectiveName>Example Consortium</CollectiveName></Author>
+      </AuthorList>
+      <Language>eng</Language>
+    </Article>
+    <MeshHeadingList>
+      <MeshHeading><DescriptorName UI="D006801" MajorTopicYN="N">Humans</DescriptorName></MeshHeading>
+      <MeshHeading><DescriptorName UI="D009369" MajorTopicYN="Y">Neoplasms</DescriptorName><QualifierName UI="Q000188">drug therapy</QualifierName></MeshHeading>
+    </MeshHeadingList>
+  </MedlineCitation>
+  <PubmedData>
+    <ArticleIdList>
+      <ArticleId IdType="pubmed">31452104</ArticleId>
+      <ArticleId IdType="doi">10.1038/s41591-019-0548-6</ArticleId>
+      <ArticleId IdType="pmc">PMC6790431</ArticleId>
+    </ArticleIdList>
+  </PubmedData>
+</PubmedArticle>
+</PubmedArticleSet>"#;
+
+    #[test]
+    fn parse_textual_and_url_forms() {
+        let cases = [
+            ("PMID: 31452104", PubmedId::Pmid("31452104")),
+            ("pmid:31452104", PubmedId::Pmid("31452104")),
+            ("PMC6790431", PubmedId::Pmcid("6790431")),
+            ("PMCID: PMC6790431", PubmedId::Pmcid("6790431")),
+            (
+                "https://pubmed.ncbi.nlm.nih.gov/31452104/",
+                PubmedId::Pmid("31452104"),
+            ),
+            (
+                "https://www.ncbi.nlm.nih.gov/pubmed/31452104",
+                PubmedId::Pmid("31452104"),
+            ),
+            (
+                "https://www.ncbi.nlm.nih.gov/pmc/articles/PMC6790431/",
+                PubmedId::Pmcid("6790431"),
+            ),
+            (
+                "https://pmc.ncbi.nlm.nih.gov/articles/PMC6790431/",
+                PubmedId::Pmcid("6790431"),
+            ),
+        ];
+        for (input, expected) in cases {
+            let p = <Pubmed<'_> as Identifier<'_>>::parse(input)
+                .unwrap_or_else(|| panic!("should parse {input}"));
+            assert_eq!(p.id, expected, "input {input}");
+        }
+    }
+
+    #[test]
+    fn parse_rejects_bare_numbers_and_other_hosts() {
+        for bad in [
+            "31452104",
+            "pmc6790431",
+            "PMC12",
+            "https://example.com/pubmed/31452104",
+            "https://pubmed.ncbi.nlm.nih.gov/?term=cancer",
+        ] {
+            assert!(
+                <Pubmed<'_> as Identifier<'_>>::parse(bad).is_none(),
+                "should reject {bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn inline_markup_keeps_surrounding_text() {
+        let xml = SAMPLE
+            .replace(
+                "A &amp; B: a study of things.",
+                "Effect of <i>E. coli</i> on growth.",
+            )
+            .replace(
+                "Some results.",
+                "Levels rose 10<sup>3</sup>-fold in <i>vivo</i>.",
+            );
+        let meta = parse_pubmed_article(&xml, "31452104").unwrap();
+        assert_eq!(meta.title, "Effect of E. coli on growth");
+        assert_eq!(
+            meta.abstract_parts[1],
+            "RESULTS: Levels rose 103-fold in vivo."
+        );
+    }
+
+    #[test]
+    fn parse_article_xml() {
+        let meta = parse_pubmed_article(SAMPLE, "31452104").unwrap();
+        assert_eq!(meta.title, "A & B: a study of things");
+        assert_eq!(meta.authors, vec!["Smith, Jane A", "{Example Consortium}"]);
+        assert_eq!(meta.journal.as_deref(), Some("Nature medicine"));
+        assert_eq!(meta.pages.as_deref(), Some("1337-1342"));
+        assert_eq!(meta.doi.as_deref(), Some("10.1038/s41591-019-0548-6"));
+        assert_eq!(meta.pmcid.as_deref(), Some("PMC6790431"));
+        assert_eq!(meta.mesh, vec!["Humans", "Neoplasms"]);
+        assert_eq!(build_date(&meta).as_deref(), Some("2019-09"));
+        assert_eq!(
+            meta.abstract_parts,
+            vec!["BACKGROUND: Some background.", "RESULTS: Some results."]
+        );
+    }
+
+    #[test]
+    fn build_biblatex_round_trips() {
+        let meta = parse_pubmed_article(SAMPLE, "31452104").unwrap();
+        let bib = Bibliography::parse(&build_biblatex(&meta)).unwrap();
+        let entry = bib.iter().next().unwrap();
+        assert_eq!(entry.key, "PMID:31452104");
+        assert!(entry.get("pmcid").is_some());
+        assert!(entry.get("keywords").is_some());
+        let out = build_biblatex(&meta);
+        assert!(out.contains("langid = {english}"), "{out}");
+
+        let mut meta = meta;
+        meta.language = Some("xyz".into());
+        assert!(!build_biblatex(&meta).contains("langid"));
+    }
+
+    #[test]
+    fn character_references_are_decoded() {
+        let xml = SAMPLE.replace(
+            "A &amp; B: a study of things.",
+            "TNF-&#x3b1; signalling, 2010&#8211;2019.",
+        );
+        let meta = parse_pubmed_article(&xml, "31452104").unwrap();
+        assert_eq!(meta.title, "TNF-\u{3b1} signalling, 2010\u{2013}2019");
+    }
+
+    #[test]
+    fn esearch_first_id() {
+        let xml =
+            "<eSearchResult><Count>1</Count><IdList><Id>31452104</Id></IdList></eSearchResult>";
+        assert_eq!(
+            parse_esearch_first_id(xml).unwrap().as_deref(),
+            Some("31452104")
+        );
+    }
+
+    #[test]
+    fn expand_abbreviated_pages() {
+        assert_eq!(expand_pages("1757-86"), "1757-1786");
+        assert_eq!(expand_pages("e123"), "e123");
+        assert_eq!(expand_pages("12-345"), "12-345");
+    }
+}
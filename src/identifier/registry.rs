@@ -0,0 +1,546 @@
+use anyhow::Context;
+use biblatex::Entry;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::{
+    bibtex::{escape_braces, normalize_ws, person_name},
+    cff::{SoftwareMeta, repository_url, software_entry},
+    identifier::Identifier,
+    resolver::IdFamily,
+};
+
+/// A package on crates.io, PyPI or npm, optionally pinned to a version
+/// (`crate:serde@1.0.200`, `pypi:numpy==2.0`, `npm:react`, or a registry URL).
+pub struct Package<'a> {
+    registry: Registry,
+    name: &'a str,
+    version: Option<&'a str>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Registry {
+    Crates,
+    PyPI,
+    Npm,
+}
+
+static VERSION_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\d+(?:\.\d+)*(?:[-+.]?[0-9A-Za-z][0-9A-Za-z.+-]*)?$").unwrap());
+static DOI_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\b10\.\d{4,9}/[^\s"<>]+"#).unwrap());
+
+impl<'a> Identifier<'a> for Package<'a> {
+    fn parse(identifier: &'a str) -> Option<Box<Self>> {
+        let s = identifier.trim();
+
+        let (registry, name, version) = if let Some(spec) = s.strip_prefix("crate:") {
+            let (name, version) = split_version(spec.trim(), "@");
+            (Registry::Crates, name, version)
+        } else if let Some(spec) = s.strip_prefix("pypi:") {
+            let (name, version) = split_version(spec.trim(), "==");
+            (Registry::PyPI, name, version)
+        } else if let Some(spec) = s.strip_prefix("npm:") {
+            let (name, version) = split_version(spec.trim(), "@");
+            (Registry::Npm, name, version)
+        } else {
+            // URLs:
+            //  - https://crates.io/crates/serde(/1.0.200)
+            //  - https://pypi.org/project/numpy(/2.0.0)/
+            //  - https://www.npmjs.com/package/react(/v/18.2.0), also scoped @scope/name
+            let (host, path) = s
+                .strip_prefix("http://")
+                .or_else(|| s.strip_prefix("https://"))
+                .and_then(|rest| rest.split_once('/'))?;
+            let path = path.split(['?', '#']).next().unwrap_or(path);
+            let path = path.trim_matches('/');
+            let segs: Vec<&str> = path.split('/').collect();
+            match (host.to_ascii_lowercase().as_str(), segs.as_slice()) {
+                // Other segments there are tabs (`/versions`, `/dependencies`, ...).
+                ("crates.io" | "www.crates.io", ["crates", name, rest @ ..]) => (
+                    Registry::Crates,
+                    *name,
+                    rest.first().copied().filter(is_version),
+                ),
+                ("pypi.org" | "www.pypi.org", ["project", name, rest @ ..]) => (
+                    Registry::PyPI,
+                    *name,
+                    rest.first().copied().filter(is_version),
+                ),
+                ("npmjs.com" | "www.npmjs.com", ["package", rest @ ..]) => {
+                    // Scoped packages span two segments.
+                    let (len, rest) = match rest {
+                        [scope, _, tail @ ..] if scope.starts_with('@') => {
+                            (scope.len() + 1 + rest[1].len(), tail)
+                        }
+                        [name, tail @ ..] => (name.len(), tail),
+                        [] => return None,
+                    };
+                    let name = &path["package/".len()..][..len];
+                    let version = match rest {
+                        ["v", v, ..] => Some(*v),
+                        _ => None,
+                    };
+                    (Registry::Npm, name, version)
+                }
+                _ => return None,
+            }
+        };
+
+        valid_name(registry, name).then(|| {
+            Box::new(Package {
+                registry,
+                name,
+                version: version.filter(|v| !v.is_empty()),
+            })
+        })
+    }
+
+    fn resolve(&self) -> anyhow::Result<Entry> {
+        let meta = match self.registry {
+            Registry::Crates => resolve_crate(self.name, self.version)?,
+            Registry::PyPI => resolve_pypi(self.name, self.version)?,
+            Registry::Npm => resolve_npm(self.name, self.version)?,
+        };
+        let prefix = match self.registry {
+            Registry::Crates => "crate",
+            Registry::PyPI => "pypi",
+            Registry::Npm => "npm",
+        };
+        let mut key = format!("{prefix}:{}", self.name.trim_start_matches('@'));
+        if let Some(v) = &meta.version {
+            key.push(':');
+            key.push_str(v);
+        }
+        software_entry(&meta, &key)
+    }
+}
+
+impl IdFamily for Package<'_> {
+    type For<'a> = Package<'a>;
+}
+
+/// Whether a URL path segment is a version (`1.0.200`, `2.0.0rc1`) rather than a page tab.
+fn is_version(seg: &&str) -> bool {
+    VERSION_RE.is_match(seg)
+}
+
+/// Split "name<sep>version". npm scopes start with '@', so only a later '@' separates.
+fn split_version<'s>(spec: &'s str, sep: &str) -> (&'s str, Option<&'s str>) {
+    let start = usize::from(spec.starts_with('@'));
+    match spec[start..].find(sep) {
+        Some(i) => (
+            &spec[..start + i],
+            Some(spec[start + i + sep.len()..].trim()),
+        ),
+        None => (spec, None),
+    }
+}
+
+fn valid_name(registry: Registry, name: &str) -> bool {
+    let plain = |s: &str| {
+        !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    match registry {
+        Registry::Crates | Registry::PyPI => plain(name),
+        Registry::Npm => match name.strip_prefix('@') {
+            Some(scoped) => scoped
+                .split_once('/')
+                .is_some_and(|(scope, pkg)| plain(scope) && plain(pkg)),
+            None => plain(name),
+        },
+    }
+}
+
+fn fetch_json(url: &str) -> anyhow::Result<Value> {
+    let cfg = ureq::Agent::config_builder()
+        .timeout_connect(Some(std::time::Duration::from_secs(5)))
+        .timeout_global(Some(std::time::Duration::from_secs(15)))
+        .build();
+    let agent = ureq::Agent::new_with_config(cfg);
+    // crates.io rejects requests without an identifying User-Agent.
+    let body: String = agent
+        .get(url)
+        .header(
+            "User-Agent",
+            "Mozilla/5.0 (compatible; bib/0.1; +https://crates.io/crates/bib)",
+        )
+        .header("Accept", "application/json")
+        .call()
+        .with_context(|| format!("failed registry request for {url}"))?
+        .into_body()
+        .read_to_string()
+        .context("failed to read registry response body")?;
+    serde_json::from_str(&body).with_context(|| format!("invalid JSON from {url}"))
+}
+
+fn json_str(v: &Value, key: &str) -> Option<String> {
+    v.get(key)
+        .and_then(Value::as_str)
+        .map(normalize_ws)
+        .filter(|s| !s.is_empty())
+}
+
+fn str_list(v: Option<&Value>) -> Vec<String> {
+    v.and_then(Value::as_array)
+        .map(|a| {
+            a.iter()
+                .filter_map(|x| x.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The first DOI mentioned in any of `texts`, without trailing punctuation.
+fn find_doi<'t>(texts: impl IntoIterator<Item = &'t str>) -> Option<String> {
+    texts.into_iter().find_map(|t| {
+        DOI_RE.find(t).map(|m| {
+            m.as_str()
+                .trim_end_matches(['.', ',', ')', ';'])
+                .to_string()
+        })
+    })
+}
+
+fn resolve_crate(name: &str, version: Option<&str>) -> anyhow::Result<SoftwareMeta> {
+    let krate = fetch_json(&format!("https://crates.io/api/v1/crates/{name}"))?;
+    let owners = fetch_json(&format!("https://crates.io/api/v1/crates/{name}/owners"))?;
+    let version = version.map(String::from).or_else(|| {
+        krate.get("crate").and_then(|c| {
+            json_str(c, "max_stable_version").or_else(|| json_str(c, "newest_version"))
+        })
+    });
+    let release = match &version {
+        Some(v) => fetch_json(&format!("https://crates.io/api/v1/crates/{name}/{v}"))?,
+        None => Value::Null,
+    };
+    Ok(crate_meta(&krate, &release, &owners))
+}
+
+fn crate_meta(krate: &Value, release: &Value, owners: &Value) -> SoftwareMeta {
+    let c = krate.get("crate").unwrap_or(&Value::Null);
+    let v = release.get("version").unwrap_or(&Value::Null);
+    let authors = owners
+        .get("users")
+        .and_then(Value::as_array)
+        .map(|users| {
+            users
+                .iter()
+                .filter_map(|u| match json_str(u, "kind").as_deref() {
+                    // Teams ("github:org:team") are groups, so keep them as one name.
+                    Some("team") => {
+                        json_str(u, "name").map(|n| format!("{{{}}}", escape_braces(&n)))
+                    }
+                    _ => json_str(u, "name")
+                        .or_else(|| json_str(u, "login"))
+                        .map(|n| escape_braces(&n)),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let repository = json_str(c, "repository").map(|r| repository_url(&r));
+    let description = json_str(c, "description");
+    SoftwareMeta {
+        title: json_str(c, "name"),
+        authors,
+        version: json_str(v, "num"),
+        date: json_str(v, "created_at").map(|d| d.chars().take(10).collect()),
+        url: json_str(c, "homepage")
+            .or_else(|| repository.clone())
+            .or_else(|| json_str(c, "name").map(|n| format!("https://crates.io/crates/{n}"))),
+        repository,
+        license: json_str(v, "license"),
+        doi: find_doi(description.as_deref()),
+        abstract_: description,
+        keywords: str_list(c.get("keywords")),
+    }
+}
+
+fn resolve_pypi(name: &str, version: Option<&str>) -> anyhow::Result<SoftwareMeta> {
+    let url = match version {
+        Some(v) => format!("https://pypi.org/pypi/{name}/{v}/json"),
+        None => format!("https://pypi.org/pypi/{name}/json"),
+    };
+    Ok(pypi_meta(&fetch_json(&url)?))
+}
+
+fn pypi_meta(v: &Value) -> SoftwareMeta {
+    let info = v.get("info").unwrap_or(&Value::Null);
+    // Authors live in `author`, or only in `author_email` as "Name <mail>, Name <mail>".
+    let authors: Vec<String> = json_str(info, "author")
+        .or_else(|| json_str(info, "author_email"))
+        .or_else(|| json_str(info, "maintainer"))
+        .map(|a| {
+            a.split([',', ';'])
+                .flat_map(|a| a.split(" and "))
+                .filter(|a| !a.contains('@') || a.contains('<'))
+                .filter_map(person_name)
+                .collect()
+        })
+        .unwrap_or_default();
+    let project_urls: Vec<(String, String)> = info
+        .get("project_urls")
+        .and_then(Value::as_object)
+        .map(|m| {
+            m.iter()
+                .filter_map(|(k, v)| Some((k.to_ascii_lowercase(), v.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+    let project_url = |keys: &[&str]| {
+        project_urls
+            .iter()
+            .find(|(k, _)| keys.contains(&k.as_str()))
+            .map(|(_, v)| v.clone())
+    };
+    let repository = project_url(&["repository", "source", "source code", "code", "github"])
+        .map(|r| repository_url(&r));
+    let license = json_str(info, "license_expression").or_else(|| {
+        // Some packages paste their whole license text here; only keep short identifiers.
+        json_str(info, "license").filter(|l| l.len() <= 64)
+    });
+    let summary = json_str(info, "summary");
+    SoftwareMeta {
+        title: json_str(info, "name"),
+        authors,
+        version: json_str(info, "version"),
+        date: v
+            .get("urls")
+            .and_then(Value::as_array)
+            .and_then(|files| {
+                files
+                    .iter()
+                    .filter_map(|f| json_str(f, "upload_time_iso_8601"))
+                    .min()
+            })
+            .map(|d| d.chars().take(10).collect()),
+        url: json_str(info, "home_page")
+            .or_else(|| project_url(&["homepage", "home", "documentation"]))
+            .or_else(|| repository.clone())
+            .or_else(|| json_str(info, "project_url")),
+        repository,
+        license,
+        doi: find_doi(
+            project_urls
+                .iter()
+                .map(|(_, u)| u.as_str())
+                .chain(summary.as_deref()),
+        ),
+        abstract_: summary,
+        keywords: json_str(info, "keywords")
+            .map(|k| {
+                k.split([',', ' '])
+                    .map(str::trim)
+                    .filter(|k| !k.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+fn resolve_npm(name: &str, version: Option<&str>) -> anyhow::Result<SoftwareMeta> {
+    // Scoped names keep their '@' but need the slash escaped.
+    let doc = fetch_json(&format!(
+        "https://registry.npmjs.org/{}",
+        name.replace('/', "%2f")
+    ))?;
+    npm_meta(&doc, version)
+}
+
+fn npm_meta(doc: &Value, version: Option<&str>) -> anyhow::Result<SoftwareMeta> {
+    let version = version
+        .map(String::from)
+        .or_else(|| doc.get("dist-tags").and_then(|t| json_str(t, "latest")))
+        .ok_or_else(|| anyhow::anyhow!("npm package has no published version"))?;
+    let v = doc
+        .get("versions")
+        .and_then(|vs| vs.get(&version))
+        .ok_or_else(|| anyhow::anyhow!("npm package has no version {version}"))?;
+    let person = |p: &Value| match p {
+        Value::String(s) => person_name(s),
+        Value::Object(_) => json_str(p, "name").map(|n| escape_braces(&n)),
+        _ => None,
+    };
+    let mut authors: Vec<String> = v.get("author").and_then(person).into_iter().collect();
+    for list in ["contributors", "maintainers"] {
+        if !authors.is_empty() {
+            break;
+        }
+        if let Some(ps) = v.get(list).and_then(Value::as_array) {
+            authors.extend(ps.iter().filter_map(person));
+        }
+    }
+    let repository = match v.get("repository") {
+        Some(Value::String(s)) => Some(repository_url(s)),
+        Some(r @ Value::Object(_)) => json_str(r, "url").map(|u| repository_url(&u)),
+        _ => None,
+    };
+    let license = match v.get("license") {
+        Some(Value::String(s)) => Some(s.clone()),
+        Some(l @ Value::Object(_)) => json_str(l, "type"),
+        _ => None,
+    };
+    let description = json_str(v, "description");
+    Ok(SoftwareMeta {
+        title: json_str(v, "name"),
+        authors,
+        date: doc
+            .get("time")
+            .and_then(|t| json_str(t, &version))
+            .map(|d| d.chars().take(10).collect()),
+        version: Some(version),
+        url: json_str(v, "homepage").or_else(|| repository.clone()),
+        repository,
+        license,
+        doi: json_str(v, "doi").or_else(|| find_doi(description.as_deref())),
+        abstract_: description,
+        keywords: str_list(v.get("keywords")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Option<(Registry, &str, Option<&str>)> {
+        <Package<'_> as Identifier<'_>>::parse(s).map(|p| (p.registry, p.name, p.version))
+    }
+
+    #[test]
+    fn parse_prefixed_specs() {
+        assert_eq!(
+            parse("crate:serde@1.0.200"),
+            Some((Registry::Crates, "serde", Some("1.0.200")))
+        );
+        assert_eq!(
+            parse("pypi:numpy==2.0"),
+            Some((Registry::PyPI, "numpy", Some("2.0")))
+        );
+        assert_eq!(parse("npm:react"), Some((Registry::Npm, "react", None)));
+        assert_eq!(
+            parse("npm:@types/node@20.1.0"),
+            Some((Registry::Npm, "@types/node", Some("20.1.0")))
+        );
+    }
+
+    #[test]
+    fn parse_registry_urls() {
+        assert_eq!(
+            parse("https://crates.io/crates/serde/1.0.200"),
+            Some((Registry::Crates, "serde", Some("1.0.200")))
+        );
+        assert_eq!(
+            parse("https://pypi.org/project/numpy/"),
+            Some((Registry::PyPI, "numpy", None))
+        );
+        assert_eq!(
+            parse("https://pypi.org/project/numpy/2.0.0rc1/"),
+            Some((Registry::PyPI, "numpy", Some("2.0.0rc1")))
+        );
+        for tab in ["versions", "dependencies", "reverse_dependencies"] {
+            assert_eq!(
+                parse(&format!("https://crates.io/crates/serde/{tab}")),
+                Some((Registry::Crates, "serde", None)),
+                "tab {tab}"
+            );
+        }
+        assert_eq!(
+            parse("https://pypi.org/project/numpy/history/"),
+            Some((Registry::PyPI, "numpy", None))
+        );
+        assert_eq!(
+            parse("https://www.npmjs.com/package/react/v/18.2.0"),
+            Some((Registry::Npm, "react", Some("18.2.0")))
+        );
+        assert_eq!(
+            parse("https://www.npmjs.com/package/@types/node"),
+            Some((Registry::Npm, "@types/node", None))
+        );
+    }
+
+    #[test]
+    fn parse_rejects_other_inputs() {
+        for bad in [
+            "crate:",
+            "pypi:not a name",
+            "npm:@scope",
+            "https://crates.io/search?q=serde",
+            "https://pypi.org/search/?q=numpy",
+        ] {
+            assert!(parse(bad).is_none(), "should reject {bad}");
+        }
+    }
+
+    #[test]
+    fn pypi_json_to_software() {
+        let v: Value = serde_json::from_str(
+            r#"{"info": {"name": "numpy", "version": "2.0.0", "author": "",
+                "author_email": "Travis E. Oliphant et al. <numpy@example.org>",
+                "license": "BSD-3-Clause", "summary": "Fundamental package for array computing",
+                "home_page": "", "keywords": "",
+                "project_urls": {"Homepage": "https://numpy.org",
+                                 "Source": "https://github.com/numpy/numpy",
+                                 "Citation": "https://doi.org/10.1038/s41586-020-2649-2"}},
+                "urls": [{"upload_time_iso_8601": "2024-06-16T15:30:00.000Z"}]}"#,
+        )
+        .unwrap();
+        let meta = pypi_meta(&v);
+        assert_eq!(meta.authors, vec!["Travis E. Oliphant et al."]);
+        assert_eq!(meta.date.as_deref(), Some("2024-06-16"));
+        assert_eq!(meta.url.as_deref(), Some("https://numpy.org"));
+        assert_eq!(
+            meta.repository.as_deref(),
+            Some("https://github.com/numpy/numpy")
+        );
+        assert_eq!(meta.doi.as_deref(), Some("10.1038/s41586-020-2649-2"));
+    }
+
+    #[test]
+    fn npm_and_crate_json_to_software() {
+        let doc: Value = serde_json::from_str(
+            r#"{"dist-tags": {"latest": "18.2.0"},
+                "time": {"18.2.0": "2022-06-14T19:46:38.369Z"},
+                "versions": {"18.2.0": {"name": "react", "version": "18.2.0", "license": "MIT",
+                    "homepage": "https://reactjs.org/",
+                    "repository": {"type": "git", "url": "git+https://github.com/facebook/react.git"},
+                    "maintainers": [{"name": "fb"}]}}}"#,
+        )
+        .unwrap();
+        let meta = npm_meta(&doc, None).unwrap();
+        assert_eq!(meta.version.as_deref(), Some("18.2.0"));
+        assert_eq!(meta.date.as_deref(), Some("2022-06-14"));
+        assert_eq!(
+            meta.repository.as_deref(),
+            Some("https://github.com/facebook/react")
+        );
+        assert_eq!(meta.authors, vec!["fb"]);
+
+        let krate: Value = serde_json::from_str(
+            r#"{"crate": {"name": "serde", "description": "A serialization framework",
+                "repository": "https://github.com/serde-rs/serde", "keywords": ["serde"]}}"#,
+        )
+        .unwrap();
+        let release: Value = serde_json::from_str(
+            r#"{"version": {"num": "1.0.200", "created_at": "2024-05-01T00:00:00Z",
+                "license": "MIT OR Apache-2.0"}}"#,
+        )
+        .unwrap();
+        let owners: Value = serde_json::from_str(
+            r#"{"users": [{"login": "dtolnay", "name": "David Tolnay", "kind": "user"},
+                          {"login": "github:serde-rs:publish", "name": "publish", "kind": "team"}]}"#,
+        )
+        .unwrap();
+        let meta = crate_meta(&krate, &release, &owners);
+        assert_eq!(meta.authors, vec!["David Tolnay", "{publish}"]);
+        assert_eq!(meta.version.as_deref(), Some("1.0.200"));
+        assert_eq!(meta.license.as_deref(), Some("MIT OR Apache-2.0"));
+        let out = software_entry(&meta, "crate:serde:1.0.200")
+            .unwrap()
+            .to_biblatex_string();
+        assert!(out.starts_with("@software{crate:serde:1.0.200,"), "{out}");
+        assert!(out.contains("date = {2024-05-01}"), "{out}");
+    }
+}
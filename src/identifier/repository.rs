@@ -0,0 +1,423 @@
+use anyhow::Context;
+use biblatex::Entry;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+
+use crate::{
+    bibtex::{escape_braces, normalize_ws},
+    cff::{SoftwareMeta, entry_from_cff, software_entry},
+    identifier::Identifier,
+    resolver::IdFamily,
+};
+
+/// A GitHub or GitLab repository URL (or `github:owner/repo`, `gitlab:group/project`).
+pub struct Repository<'a> {
+    forge: Forge<'a>,
+    /// `owner/repo` on GitHub; the full (possibly nested) project path on GitLab.
+    path: &'a str,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Forge<'a> {
+    GitHub,
+    /// GitLab.com or a self-hosted instance, by host name.
+    GitLab(&'a str),
+}
+
+/// First path segments on github.com that are site pages rather than owners.
+const GITHUB_RESERVED: &[&str] = &[
+    "about",
+    "apps",
+    "collections",
+    "enterprise",
+    "events",
+    "explore",
+    "features",
+    "issues",
+    "login",
+    "marketplace",
+    "new",
+    "notifications",
+    "orgs",
+    "pricing",
+    "pulls",
+    "search",
+    "settings",
+    "sponsors",
+    "topics",
+    "trending",
+];
+
+impl<'a> Identifier<'a> for Repository<'a> {
+    fn parse(identifier: &'a str) -> Option<Box<Self>> {
+        let s = identifier.trim();
+
+        if let Some(path) = s.strip_prefix("github:") {
+            let path = path.trim().trim_end_matches('/');
+            return github_path(path).map(|path| {
+                Box::new(Repository {
+                    forge: Forge::GitHub,
+                    path,
+                })
+            });
+        }
+        if let Some(path) = s.strip_prefix("gitlab:") {
+            let path = path.trim().trim_end_matches('/');
+            return gitlab_path(path).map(|path| {
+                Box::new(Repository {
+                    forge: Forge::GitLab("gitlab.com"),
+                    path,
+                })
+            });
+        }
+
+        // URLs:
+        //  - https://github.com/owner/repo(.git)(/tree/main/...)
+        //  - https://gitlab.com/group/subgroup/project(/-/tree/main/...)
+        //  - https://gitlab.example.org/group/project (hosts named gitlab.*)
+        let (host, path) = s
+            .strip_prefix("http://")
+            .or_else(|| s.strip_prefix("https://"))
+            .and_then(|rest| rest.split_once('/'))?;
+        let path = path.split(['?', '#']).next().unwrap_or(path);
+        let host_lc = host.to_ascii_lowercase();
+        let (forge, path) = match host_lc.as_str() {
+            "github.com" | "www.github.com" => (Forge::GitHub, github_path(path)?),
+            h if h == "gitlab.com" || h.starts_with("gitlab.") => {
+                let path = path.split("/-/").next().unwrap_or(path);
+                (Forge::GitLab(host), gitlab_path(path)?)
+            }
+            _ => return None,
+        };
+        Some(Box::new(Repository { forge, path }))
+    }
+
+    fn resolve(&self) -> anyhow::Result<Entry> {
+        match self.forge {
+            Forge::GitHub => resolve_github(self.path),
+            Forge::GitLab(host) => resolve_gitlab(host, self.path),
+        }
+    }
+}
+
+impl IdFamily for Repository<'_> {
+    type For<'a> = Repository<'a>;
+}
+
+/// The `owner/repo` part of a GitHub path, ignoring anything after it.
+fn github_path(path: &str) -> Option<&str> {
+    let mut segs = path.trim_start_matches('/').splitn(3, '/');
+    let owner = segs.next().filter(|o| !o.is_empty())?;
+    let repo = segs.next().filter(|r| !r.is_empty())?;
+    if GITHUB_RESERVED.contains(&owner) {
+        return None;
+    }
+    let valid = |s: &str| {
+        s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    if !valid(owner) || !valid(repo) {
+        return None;
+    }
+    let end = owner.len() + 1 + repo.strip_suffix(".git").unwrap_or(repo).len();
+    Some(&path.trim_start_matches('/')[..end])
+}
+
+/// A GitLab project path: at least a namespace and a project name.
+fn gitlab_path(path: &str) -> Option<&str> {
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let segs: Vec<&str> = path.split('/').collect();
+    let valid = segs.len() >= 2
+        && segs.iter().all(|s| {
+            !s.is_empty()
+                && s.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        });
+    // Top-level pages such as /explore/projects or /users/sign_in are not projects.
+    (valid
+        && !matches!(
+            segs[0],
+            "explore" | "users" | "dashboard" | "help" | "search"
+        ))
+    .then_some(path)
+}
+
+fn agent() -> ureq::Agent {
+    let cfg = ureq::Agent::config_builder()
+        .timeout_connect(Some(std::time::Duration::from_secs(5)))
+        .timeout_global(Some(std::time::Duration::from_secs(15)))
+        .http_status_as_error(false)
+        .build();
+    ureq::Agent::new_with_config(cfg)
+}
+
+/// GET `url`, returning `None` on 404 (a missing file or release is not an error).
+fn fetch(url: &str, auth: Option<(&str, String)>) -> anyhow::Result<Option<String>> {
+    let mut req = agent().get(url).header(
+        "User-Agent",
+        "Mozilla/5.0 (compatible; bib/0.1; +https://github.com)",
+    );
+    if let Some((name, value)) = auth {
+        req = req.header(name, &value);
+    }
+    let res = req
+        .call()
+        .with_context(|| format!("failed request for {url}"))?;
+    if res.status() == 404 {
+        return Ok(None);
+    }
+    if !res.status().is_success() {
+        return Err(anyhow::anyhow!("{url} returned {}", res.status()));
+    }
+    let body = res
+        .into_body()
+        .read_to_string()
+        .with_context(|| format!("failed to read response body from {url}"))?;
+    Ok(Some(body))
+}
+
+fn fetch_json(
+    url: &str,
+    auth: Option<(&str, String)>,
+) -> anyhow::Result<Option<serde_json::Value>> {
+    fetch(url, auth)?
+        .map(|b| serde_json::from_str(&b).with_context(|| format!("invalid JSON from {url}")))
+        .transpose()
+}
+
+fn json_str(v: &serde_json::Value, key: &str) -> Option<String> {
+    v.get(key)
+        .and_then(|x| x.as_str())
+        .map(normalize_ws)
+        .filter(|s| !s.is_empty())
+}
+
+/// A repository owner as a BibLaTeX name: organisations braced so that BibLaTeX does not split
+/// them into first and last names, people left for it to parse.
+fn owner_name(name: &str, organisation: bool) -> String {
+    if organisation {
+        format!("{{{}}}", escape_braces(name))
+    } else {
+        escape_braces(name)
+    }
+}
+
+fn github_auth() -> Option<(&'static str, String)> {
+    std::env::var("GITHUB_TOKEN")
+        .ok()
+        .map(|t| ("Authorization", format!("Bearer {t}")))
+}
+
+fn resolve_github(path: &str) -> anyhow::Result<Entry> {
+    let api = format!("https://api.github.com/repos/{path}");
+    let repo = fetch_json(&api, github_auth())?
+        .ok_or_else(|| anyhow::anyhow!("GitHub repository {path} not found"))?;
+    let branch = json_str(&repo, "default_branch").unwrap_or_else(|| "HEAD".into());
+
+    let mut meta = SoftwareMeta {
+        title: json_str(&repo, "name"),
+        url: json_str(&repo, "html_url"),
+        repository: json_str(&repo, "html_url"),
+        abstract_: json_str(&repo, "description"),
+        license: repo
+            .get("license")
+            .and_then(|l| json_str(l, "spdx_id"))
+            .filter(|l| l != "NOASSERTION"),
+        keywords: repo
+            .get("topics")
+            .and_then(|t| t.as_array())
+            .map(|t| {
+                t.iter()
+                    .filter_map(|x| x.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        date: json_str(&repo, "pushed_at").map(|d| d.chars().take(10).collect()),
+        ..Default::default()
+    };
+
+    // The owner's display name, if they set one; organisations are cited as a single entity.
+    // This and the release lookup below only enrich the entry, so their failures (e.g., a
+    // rate limit) are ignored.
+    if let Some(owner) = repo.get("owner")
+        && let Some(login) = json_str(owner, "login")
+    {
+        let name = fetch_json(
+            &format!("https://api.github.com/users/{login}"),
+            github_auth(),
+        )
+        .ok()
+        .flatten()
+        .and_then(|u| json_str(&u, "name"))
+        .unwrap_or(login);
+        let organisation = json_str(owner, "type").as_deref() == Some("Organization");
+        meta.authors.push(owner_name(&name, organisation));
+    }
+
+    if let Some(release) = fetch_json(&format!("{api}/releases/latest"), github_auth())
+        .ok()
+        .flatten()
+    {
+        meta.version = json_str(&release, "tag_name");
+        if let Some(d) = json_str(&release, "published_at") {
+            meta.date = Some(d.chars().take(10).collect());
+        }
+    }
+
+    let key = format!("github:{path}");
+    let cff_url = format!(
+        "https://raw.githubusercontent.com/{path}/{}/CITATION.cff",
+        encode_branch(&branch)
+    );
+    match fetch(&cff_url, None)? {
+        Some(cff) => entry_from_cff(&cff, meta, &key),
+        None => software_entry(&meta, &key),
+    }
+}
+
+/// A branch name as URL path segments: branches may contain `/`, which stays a separator, but
+/// also characters such as `#`, `?` or `%` that must not be read as URL syntax.
+fn encode_branch(branch: &str) -> String {
+    branch
+        .split('/')
+        .map(|s| utf8_percent_encode(s, NON_ALPHANUMERIC).to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn gitlab_auth() -> Option<(&'static str, String)> {
+    std::env::var("GITLAB_TOKEN")
+        .ok()
+        .map(|t| ("PRIVATE-TOKEN", t))
+}
+
+fn resolve_gitlab(host: &str, path: &str) -> anyhow::Result<Entry> {
+    let project = utf8_percent_encode(path, NON_ALPHANUMERIC).to_string();
+    let api = format!("https://{host}/api/v4/projects/{project}");
+    let repo = fetch_json(&format!("{api}?license=true"), gitlab_auth())?
+        .ok_or_else(|| anyhow::anyhow!("GitLab project {path} not found on {host}"))?;
+    let branch = json_str(&repo, "default_branch").unwrap_or_else(|| "HEAD".into());
+
+    let mut meta = SoftwareMeta {
+        title: json_str(&repo, "name"),
+        url: json_str(&repo, "web_url"),
+        repository: json_str(&repo, "web_url"),
+        abstract_: json_str(&repo, "description"),
+        license: repo.get("license").and_then(|l| {
+            json_str(l, "key")
+                .map(|k| k.to_uppercase())
+                .or_else(|| json_str(l, "name"))
+        }),
+        keywords: repo
+            .get("topics")
+            .and_then(|t| t.as_array())
+            .map(|t| {
+                t.iter()
+                    .filter_map(|x| x.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        date: json_str(&repo, "last_activity_at").map(|d| d.chars().take(10).collect()),
+        ..Default::default()
+    };
+    // Groups are cited as a single entity, user namespaces by the user's name.
+    if let Some(namespace) = repo.get("namespace")
+        && let Some(name) = json_str(namespace, "name")
+    {
+        let group = json_str(namespace, "kind").as_deref() != Some("user");
+        meta.authors.push(owner_name(&name, group));
+    }
+
+    // Like on GitHub, a failed release lookup leaves the entry without a version.
+    if let Some(release) = fetch_json(&format!("{api}/releases?per_page=1"), gitlab_auth())
+        .ok()
+        .flatten()
+        .and_then(|r| r.as_array().and_then(|a| a.first()).cloned())
+    {
+        meta.version = json_str(&release, "tag_name");
+        if let Some(d) = json_str(&release, "released_at") {
+            meta.date = Some(d.chars().take(10).collect());
+        }
+    }
+
+    let key = format!("gitlab:{path}");
+    let branch = utf8_percent_encode(&branch, NON_ALPHANUMERIC);
+    let cff_url = format!("{api}/repository/files/CITATION.cff/raw?ref={branch}");
+    match fetch(&cff_url, gitlab_auth())? {
+        Some(cff) => entry_from_cff(&cff, meta, &key),
+        None => software_entry(&meta, &key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Option<(Forge<'_>, &str)> {
+        <Repository<'_> as Identifier<'_>>::parse(s).map(|r| (r.forge, r.path))
+    }
+
+    #[test]
+    fn parse_github_forms() {
+        for input in [
+            "github:BurntSushi/ripgrep",
+            "https://github.com/BurntSushi/ripgrep",
+            "https://github.com/BurntSushi/ripgrep.git",
+            "https://github.com/BurntSushi/ripgrep/tree/master/crates",
+            "https://www.github.com/BurntSushi/ripgrep?tab=readme",
+        ] {
+            assert_eq!(
+                parse(input),
+                Some((Forge::GitHub, "BurntSushi/ripgrep")),
+                "input {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_gitlab_forms() {
+        assert_eq!(
+            parse("https://gitlab.com/gitlab-org/cli/-/tree/main"),
+            Some((Forge::GitLab("gitlab.com"), "gitlab-org/cli"))
+        );
+        assert_eq!(
+            parse("https://gitlab.com/group/sub/project.git"),
+            Some((Forge::GitLab("gitlab.com"), "group/sub/project"))
+        );
+        assert_eq!(
+            parse("https://gitlab.example.org/team/tool"),
+            Some((Forge::GitLab("gitlab.example.org"), "team/tool"))
+        );
+        assert_eq!(
+            parse("gitlab:gitlab-org/cli"),
+            Some((Forge::GitLab("gitlab.com"), "gitlab-org/cli"))
+        );
+    }
+
+    #[test]
+    fn parse_rejects_non_repositories() {
+        for bad in [
+            "https://github.com/BurntSushi",
+            "https://github.com/topics/rust",
+            "https://github.com/orgs/rust-lang/people",
+            "https://gitlab.com/explore/projects",
+            "https://example.com/owner/repo",
+            "github:owner",
+        ] {
+            assert!(parse(bad).is_none(), "should reject {bad}");
+        }
+    }
+
+    #[test]
+    fn only_organisations_are_braced() {
+        assert_eq!(owner_name("Andrew Gallant", false), "Andrew Gallant");
+        assert_eq!(owner_name("The Rust Project", true), "{The Rust Project}");
+    }
+
+    #[test]
+    fn branches_are_percent_encoded() {
+        assert_eq!(encode_branch("main"), "main");
+        assert_eq!(encode_branch("release/1.x"), "release/1%2Ex");
+        assert_eq!(encode_branch("fix#12?a=b"), "fix%2312%3Fa%3Db");
+    }
+}
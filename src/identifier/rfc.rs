@@ -0,0 +1,514 @@
+use anyhow::Context;
+use biblatex::{Bibliography, Entry};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{
+    bibtex::{escape_braces, month_number, normalize_ws, write_entry},
+    identifier::Identifier,
+    resolver::IdFamily,
+};
+
+/// An IETF RFC number or Internet-Draft name, from bare text or an RFC Editor / IETF URL.
+pub struct Rfc<'a> {
+    doc: RfcDoc<'a>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum RfcDoc<'a> {
+    /// RFC number without leading zeros (e.g., "9110").
+    Rfc(&'a str),
+    /// Internet-Draft name without revision, plus the revision when pinned.
+    Draft { name: &'a str, rev: Option<&'a str> },
+}
+
+pub(crate) static RFC_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?:RFC|rfc)[\s:-]*0*(?P<n>[1-9][0-9]{0,4})$").unwrap());
+static RFC_PATH_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^rfc0*(?P<n>[1-9][0-9]{0,4})(?:\.(?:html|txt|pdf|xml|json))?$").unwrap()
+});
+static DRAFT_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^(?P<name>draft-[a-z0-9]+(?:-[a-z0-9]+)*?)(?:-(?P<rev>[0-9]{2}))?(?:\.(?:html|txt|pdf|xml))?$",
+    )
+    .unwrap()
+});
+
+impl<'a> Identifier<'a> for Rfc<'a> {
+    fn parse(identifier: &'a str) -> Option<Box<Self>> {
+        let s = identifier.trim();
+
+        // URLs:
+        //  - https://www.rfc-editor.org/rfc/rfc9110(.html|.txt|.pdf)
+        //  - https://www.rfc-editor.org/info/rfc9110
+        //  - https://datatracker.ietf.org/doc/rfc9110/ or /doc/html/rfc9110
+        //  - https://datatracker.ietf.org/doc/draft-ietf-httpbis-semantics/ (optionally -19)
+        //  - https://tools.ietf.org/html/rfc9110, https://www.ietf.org/rfc/rfc9110.txt
+        //  - https://www.ietf.org/archive/id/draft-ietf-httpbis-semantics-19.txt
+        if let Some((host, path)) = s
+            .strip_prefix("http://")
+            .or_else(|| s.strip_prefix("https://"))
+            .and_then(|rest| rest.split_once('/'))
+        {
+            let host = host.to_ascii_lowercase();
+            if !matches!(
+                host.as_str(),
+                "www.rfc-editor.org"
+                    | "rfc-editor.org"
+                    | "datatracker.ietf.org"
+                    | "tools.ietf.org"
+                    | "www.ietf.org"
+                    | "ietf.org"
+            ) {
+                return None;
+            }
+            let path = path.split(['?', '#']).next().unwrap_or(path);
+            let last = path.trim_matches('/').rsplit('/').next()?;
+            return parse_doc_name(last).map(|doc| Box::new(Rfc { doc }));
+        }
+
+        if let Some(c) = RFC_RE.captures(s) {
+            return Some(Box::new(Rfc {
+                doc: RfcDoc::Rfc(c.name("n")?.as_str()),
+            }));
+        }
+        if s.starts_with("draft-") {
+            return parse_doc_name(s).map(|doc| Box::new(Rfc { doc }));
+        }
+        None
+    }
+
+    fn resolve(&self) -> anyhow::Result<Entry> {
+        let bib = match self.doc {
+            RfcDoc::Rfc(n) => {
+                let json = fetch_json(&format!("https://www.rfc-editor.org/rfc/rfc{n}.json"))?;
+                build_rfc_biblatex(&parse_rfc_json(&json, n)?)
+            }
+            RfcDoc::Draft { name, rev } => {
+                let url = match rev {
+                    Some(r) => format!("https://datatracker.ietf.org/doc/{name}-{r}/doc.json"),
+                    None => format!("https://datatracker.ietf.org/doc/{name}/doc.json"),
+                };
+                let json = fetch_json(&url)?;
+                build_draft_biblatex(&parse_draft_json(&json, name, rev)?)
+            }
+        };
+        let bib = Bibliography::parse(&bib)
+            .map_err(|e| anyhow::anyhow!("failed to parse constructed BibLaTeX: {e}"))?;
+        let entry = bib
+            .iter()
+            .next()
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("empty bibliography from constructed RFC record"))?;
+        Ok(entry)
+    }
+}
+
+impl IdFamily for Rfc<'_> {
+    type For<'a> = Rfc<'a>;
+}
+
+/// Parse the last path segment of an IETF URL (or a bare draft name) into a document.
+fn parse_doc_name(seg: &str) -> Option<RfcDoc<'_>> {
+    if let Some(c) = RFC_PATH_RE.captures(seg) {
+        return Some(RfcDoc::Rfc(c.name("n")?.as_str()));
+    }
+    let c = DRAFT_RE.captures(seg)?;
+    Some(RfcDoc::Draft {
+        name: c.name("name")?.as_str(),
+        rev: c.name("rev").map(|m| m.as_str()),
+    })
+}
+
+fn fetch_json(url: &str) -> anyhow::Result<serde_json::Value> {
+    let cfg = ureq::Agent::config_builder()
+        .timeout_connect(Some(std::time::Duration::from_secs(5)))
+        .timeout_global(Some(std::time::Duration::from_secs(15)))
+        .build();
+    let agent = ureq::Agent::new_with_config(cfg);
+    let body: String = agent
+        .get(url)
+        .header(
+            "User-Agent",
+            "Mozilla/5.0 (compatible; bib/0.1; +https://www.rfc-editor.org)",
+        )
+        .header("Accept", "application/json")
+        .call()
+        .with_context(|| format!("failed request for {url}"))?
+        .into_body()
+        .read_to_string()
+        .context("failed to read response body")?;
+    serde_json::from_str(&body).with_context(|| format!("invalid JSON from {url}"))
+}
+
+/// Normalised RFC Editor metadata we care about.
+#[derive(Debug)]
+struct RfcMeta {
+    number: String,
+    title: String,
+    authors: Vec<String>,
+    month: Option<u32>,
+    year: Option<String>,
+    status: Option<String>,
+    stream: Option<String>,
+    abstract_: Option<String>,
+    keywords: Vec<String>,
+    pages: Option<String>,
+    doi: String,
+}
+
+/// Normalised datatracker metadata for an Internet-Draft.
+#[derive(Debug)]
+struct DraftMeta {
+    name: String,
+    rev: Option<String>,
+    title: String,
+    authors: Vec<String>,
+    date: Option<String>,
+    status: Option<String>,
+    abstract_: Option<String>,
+    pages: Option<String>,
+}
+
+fn json_str(v: &serde_json::Value, key: &str) -> Option<String> {
+    v.get(key)
+        .and_then(|x| x.as_str())
+        .map(normalize_ws)
+        .filter(|s| !s.is_empty())
+}
+
+fn parse_rfc_json(v: &serde_json::Value, number: &str) -> anyhow::Result<RfcMeta> {
+    let title = json_str(v, "title")
+        .ok_or_else(|| anyhow::anyhow!("no RFC Editor record found for RFC {number}"))?;
+    let authors = v
+        .get("authors")
+        .and_then(|a| a.as_array())
+        .map(|a| {
+            a.iter()
+                .filter_map(|x| x.as_str())
+                .map(strip_author_role)
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    // pub_date is "Month YYYY" (e.g., "June 2022").
+    let (month, year) = match json_str(v, "pub_date") {
+        Some(d) => {
+            let mut parts = d.split_whitespace();
+            let (m, y) = (parts.next(), parts.next());
+            match y {
+                Some(y) => (m.and_then(month_number), Some(y.to_string())),
+                None => (None, m.map(str::to_string)),
+            }
+        }
+        None => (None, None),
+    };
+    let keywords = v
+        .get("keywords")
+        .and_then(|k| k.as_array())
+        .map(|k| {
+            k.iter()
+                .filter_map(|x| x.as_str())
+                .map(normalize_ws)
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    let pages = v.get("page_count").and_then(|p| {
+        p.as_u64()
+            .map(|n| n.to_string())
+            .or_else(|| p.as_str().map(str::to_string))
+    });
+
+    Ok(RfcMeta {
+        number: number.to_string(),
+        title,
+        authors,
+        month,
+        year,
+        status: json_str(v, "status").map(|s| title_case(&s)),
+        stream: json_str(v, "source"),
+        abstract_: json_str(v, "abstract"),
+        keywords,
+        pages,
+        doi: json_str(v, "doi").unwrap_or_else(|| {
+            let n: u32 = number.parse().unwrap_or_default();
+            format!("10.17487/RFC{n:04}")
+        }),
+    })
+}
+
+fn parse_draft_json(
+    v: &serde_json::Value,
+    name: &str,
+    rev: Option<&str>,
+) -> anyhow::Result<DraftMeta> {
+    let title = json_str(v, "title")
+        .ok_or_else(|| anyhow::anyhow!("no datatracker record found for {name}"))?;
+    let authors = v
+        .get("authors")
+        .and_then(|a| a.as_array())
+        .map(|a| {
+            a.iter()
+                .filter_map(|x| x.as_str().map(normalize_ws).or_else(|| json_str(x, "name")))
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    let date = json_str(v, "time").and_then(|t| t.get(..10).map(str::to_string));
+    let pages = v
+        .get("pages")
+        .and_then(|p| p.as_u64())
+        .map(|n| n.to_string());
+    Ok(DraftMeta {
+        name: name.to_string(),
+        rev: rev.map(str::to_string).or_else(|| json_str(v, "rev")),
+        title,
+        authors,
+        date,
+        status: json_str(v, "state"),
+        abstract_: json_str(v, "abstract"),
+        pages,
+    })
+}
+
+/// Drop role suffixes such as ", Ed." from RFC Editor author strings.
+fn strip_author_role(s: &str) -> String {
+    let s = normalize_ws(s);
+    s.trim_end_matches(", Ed.")
+        .trim_end_matches(", Editor")
+        .trim()
+        .to_string()
+}
+
+/// "PROPOSED STANDARD" -> "Proposed Standard"
+fn title_case(s: &str) -> String {
+    s.split_whitespace()
+        .map(|w| {
+            let lower = w.to_lowercase();
+            let mut cs = lower.chars();
+            match cs.next() {
+                Some(f) => f.to_uppercase().chain(cs).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Nothing in RFC metadata is BibLaTeX markup, so every value is escaped.
+fn write_plain_entry(ty: &str, key: &str, fields: Vec<(&str, String)>) -> String {
+    let fields = fields
+        .into_iter()
+        .map(|(k, v)| (k, escape_braces(&v)))
+        .collect();
+    write_entry(ty, key, fields)
+}
+
+fn build_rfc_biblatex(meta: &RfcMeta) -> String {
+    let mut fields: Vec<(&str, String)> = Vec::new();
+    fields.push(("title", meta.title.clone()));
+    if !meta.authors.is_empty() {
+        fields.push(("author", meta.authors.join(" and ")));
+    }
+    fields.push(("type", "RFC".to_string()));
+    fields.push(("series", "Request for Comments".to_string()));
+    fields.push(("number", meta.number.clone()));
+    fields.push(("institution", "RFC Editor".to_string()));
+    if let Some(m) = meta.month {
+        fields.push(("month", m.to_string()));
+    }
+    if let Some(y) = &meta.year {
+        fields.push(("year", y.clone()));
+    }
+    if let Some(p) = &meta.pages {
+        fields.push(("pagetotal", p.clone()));
+    }
+    fields.push(("doi", meta.doi.clone()));
+    fields.push((
+        "url",
+        format!("https://www.rfc-editor.org/info/rfc{}", meta.number),
+    ));
+    if let Some(s) = &meta.status {
+        fields.push(("status", s.clone()));
+    }
+    if let Some(s) = &meta.stream {
+        fields.push(("organization", s.clone()));
+    }
+    if let Some(a) = &meta.abstract_ {
+        fields.push(("abstract", a.clone()));
+    }
+    if !meta.keywords.is_empty() {
+        fields.push(("keywords", meta.keywords.join(", ")));
+    }
+    write_plain_entry("techreport", &format!("rfc{}", meta.number), fields)
+}
+
+fn build_draft_biblatex(meta: &DraftMeta) -> String {
+    let full_name = match &meta.rev {
+        Some(r) => format!("{}-{}", meta.name, r),
+        None => meta.name.clone(),
+    };
+    let mut fields: Vec<(&str, String)> = Vec::new();
+    fields.push(("title", meta.title.clone()));
+    if !meta.authors.is_empty() {
+        fields.push(("author", meta.authors.join(" and ")));
+    }
+    fields.push(("type", "Internet-Draft".to_string()));
+    fields.push(("series", "Internet-Draft".to_string()));
+    fields.push(("number", full_name.clone()));
+    fields.push(("institution", "Internet Engineering Task Force".to_string()));
+    if let Some(d) = &meta.date {
+        fields.push(("date", d.clone()));
+    }
+    if let Some(p) = &meta.pages {
+        fields.push(("pagetotal", p.clone()));
+    }
+    fields.push((
+        "url",
+        format!("https://datatracker.ietf.org/doc/{}/", full_name),
+    ));
+    if let Some(s) = &meta.status {
+        fields.push(("status", s.clone()));
+    }
+    fields.push(("pubstate", "Work in Progress".to_string()));
+    if let Some(a) = &meta.abstract_ {
+        fields.push(("abstract", a.clone()));
+    }
+    write_plain_entry("techreport", &full_name, fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Option<RfcDoc<'_>> {
+        <Rfc<'_> as Identifier<'_>>::parse(s).map(|r| r.doc)
+    }
+
+    #[test]
+    fn parse_rfc_forms() {
+        for input in [
+            "RFC 9110",
+            "rfc9110",
+            "RFC-9110",
+            "RFC: 9110",
+            "RFC  9110",
+            "rfc:9110",
+            "https://www.rfc-editor.org/rfc/rfc9110",
+            "https://www.rfc-editor.org/rfc/rfc9110.html",
+            "https://www.rfc-editor.org/info/rfc9110",
+            "https://datatracker.ietf.org/doc/html/rfc9110",
+            "https://datatracker.ietf.org/doc/rfc9110/",
+            "https://tools.ietf.org/html/rfc9110#section-3",
+        ] {
+            assert_eq!(parse(input), Some(RfcDoc::Rfc("9110")), "input {input}");
+        }
+        assert_eq!(parse("RFC 0791"), Some(RfcDoc::Rfc("791")));
+    }
+
+    #[test]
+    fn parse_draft_forms() {
+        assert_eq!(
+            parse("draft-ietf-httpbis-semantics-19"),
+            Some(RfcDoc::Draft {
+                name: "draft-ietf-httpbis-semantics",
+                rev: Some("19")
+            })
+        );
+        assert_eq!(
+            parse("https://datatracker.ietf.org/doc/draft-ietf-quic-transport/"),
+            Some(RfcDoc::Draft {
+                name: "draft-ietf-quic-transport",
+                rev: None
+            })
+        );
+        assert_eq!(
+            parse("https://www.ietf.org/archive/id/draft-ietf-tls-esni-18.txt"),
+            Some(RfcDoc::Draft {
+                name: "draft-ietf-tls-esni",
+                rev: Some("18")
+            })
+        );
+    }
+
+    #[test]
+    fn parse_rejects_non_rfc() {
+        for bad in [
+            "RFC",
+            "Rfc9110",
+            "rfc 0",
+            "https://example.com/rfc/rfc9110",
+            "https://www.rfc-editor.org/search/",
+        ] {
+            assert!(parse(bad).is_none(), "should reject {bad}");
+        }
+    }
+
+    #[test]
+    fn rfc_json_to_techreport() {
+        let v: serde_json::Value = serde_json::from_str(
+            r#"{
+                "draft": "draft-ietf-httpbis-semantics-19",
+                "doc_id": "RFC9110",
+                "title": "HTTP Semantics",
+                "authors": ["R. Fielding, Ed.", "M. Nottingham, Ed.", "J. Reschke, Ed."],
+                "format": ["HTML", "TEXT", "PDF", "XML"],
+                "page_count": "194",
+                "pub_status": "INTERNET STANDARD",
+                "status": "INTERNET STANDARD",
+                "source": "HTTP",
+                "abstract": "The Hypertext Transfer Protocol (HTTP) is ...",
+                "pub_date": "June 2022",
+                "keywords": ["Hypertext Transfer Protocol", "HTTP"],
+                "doi": "10.17487/RFC9110"
+            }"#,
+        )
+        .unwrap();
+        let meta = parse_rfc_json(&v, "9110").unwrap();
+        assert_eq!(meta.authors[0], "R. Fielding");
+        assert_eq!(meta.month, Some(6));
+        assert_eq!(meta.year.as_deref(), Some("2022"));
+        assert_eq!(meta.status.as_deref(), Some("Internet Standard"));
+
+        let bib = Bibliography::parse(&build_rfc_biblatex(&meta)).unwrap();
+        let entry = bib.iter().next().unwrap();
+        assert_eq!(entry.key, "rfc9110");
+        assert_eq!(entry.entry_type.to_string(), "techreport");
+        for field in ["series", "number", "doi", "status", "month", "year"] {
+            assert!(entry.get(field).is_some(), "missing {field}");
+        }
+    }
+
+    #[test]
+    fn rfc_doi_falls_back_to_zero_padded_number() {
+        let v: serde_json::Value =
+            serde_json::from_str(r#"{"title": "Internet Protocol", "pub_date": "September 1981"}"#)
+                .unwrap();
+        let meta = parse_rfc_json(&v, "791").unwrap();
+        assert_eq!(meta.doi, "10.17487/RFC0791");
+    }
+
+    #[test]
+    fn draft_json_to_techreport() {
+        let v: serde_json::Value = serde_json::from_str(
+            r#"{
+                "name": "draft-ietf-quic-transport",
+                "rev": "34",
+                "title": "QUIC: A UDP-Based Multiplexed and Secure Transport",
+                "pages": 151,
+                "time": "2021-01-14 23:03:52",
+                "state": "RFC",
+                "authors": [{"name": "Jana Iyengar"}, {"name": "Martin Thomson"}]
+            }"#,
+        )
+        .unwrap();
+        let meta = parse_draft_json(&v, "draft-ietf-quic-transport", None).unwrap();
+        assert_eq!(meta.rev.as_deref(), Some("34"));
+        assert_eq!(meta.date.as_deref(), Some("2021-01-14"));
+        let bib = Bibliography::parse(&build_draft_biblatex(&meta)).unwrap();
+        let entry = bib.iter().next().unwrap();
+        assert_eq!(entry.key, "draft-ietf-quic-transport-34");
+        assert!(entry.get("status").is_some());
+    }
+}
@@ -0,0 +1,231 @@
+use anyhow::Context;
+use biblatex::{Bibliography, Entry};
+use once_cell::sync::Lazy;
+use percent_encoding::percent_decode_str;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::{
+    bibtex::{person_name, scalar, write_entry},
+    identifier::Identifier,
+    resolver::IdFamily,
+};
+
+/// A Software Heritage identifier (e.g., `swh:1:rev:<sha1>;origin=https://...`).
+pub struct SoftwareHeritage {
+    /// Core SWHID without qualifiers, e.g. `swh:1:rev:309cf2674ee7a0749978cf8265ab91a60aea0f7d`.
+    core: String,
+    /// Everything after the first ';', kept verbatim for the `eprint` field.
+    qualifiers: Option<String>,
+}
+
+static SWHID_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^swh:1:(cnt|dir|rev|rel|snp):[0-9a-f]{40}$").unwrap());
+
+impl<'a> Identifier<'a> for SoftwareHeritage {
+    fn parse(identifier: &'a str) -> Option<Box<Self>> {
+        let s = identifier.trim();
+
+        // URLs: https://archive.softwareheritage.org/swh:1:rev:...(;origin=...)
+        let swhid = match s
+            .strip_prefix("http://")
+            .or_else(|| s.strip_prefix("https://"))
+        {
+            Some(rest) => {
+                let (host, path) = rest.split_once('/')?;
+                if !matches!(
+                    host.to_ascii_lowercase().as_str(),
+                    "archive.softwareheritage.org" | "softwareheritage.org"
+                ) {
+                    return None;
+                }
+                percent_decode_str(path.trim_end_matches('/'))
+                    .decode_utf8()
+                    .ok()?
+                    .into_owned()
+            }
+            None => s.to_string(),
+        };
+
+        let (core, qualifiers) = match swhid.split_once(';') {
+            Some((core, q)) => (core, Some(q.to_string()).filter(|q| !q.is_empty())),
+            None => (swhid.as_str(), None),
+        };
+        SWHID_RE.is_match(core).then(|| {
+            Box::new(SoftwareHeritage {
+                core: core.to_string(),
+                qualifiers,
+            })
+        })
+    }
+
+    fn resolve(&self) -> anyhow::Result<Entry> {
+        let (kind, hash) = self.kind_and_hash();
+        // Only revisions and releases carry authorship and dates.
+        let object = match kind {
+            "rev" => Some(fetch_json(&format!(
+                "https://archive.softwareheritage.org/api/1/revision/{hash}/"
+            ))?),
+            "rel" => Some(fetch_json(&format!(
+                "https://archive.softwareheritage.org/api/1/release/{hash}/"
+            ))?),
+            _ => None,
+        };
+        let bib = build_biblatex(self, object.as_ref());
+        let bib = Bibliography::parse(&bib)
+            .map_err(|e| anyhow::anyhow!("failed to parse generated BibLaTeX: {e}"))?;
+        bib.into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("empty bibliography"))
+    }
+}
+
+impl IdFamily for SoftwareHeritage {
+    type For<'a> = SoftwareHeritage;
+}
+
+impl SoftwareHeritage {
+    fn kind_and_hash(&self) -> (&str, &str) {
+        // Validated by SWHID_RE: "swh", "1", kind, hash.
+        let mut parts = self.core.splitn(4, ':').skip(2);
+        (parts.next().unwrap_or(""), parts.next().unwrap_or(""))
+    }
+
+    fn full(&self) -> String {
+        match &self.qualifiers {
+            Some(q) => format!("{};{q}", self.core),
+            None => self.core.clone(),
+        }
+    }
+
+    fn qualifier(&self, name: &str) -> Option<&str> {
+        self.qualifiers.as_deref()?.split(';').find_map(|q| {
+            let (k, v) = q.split_once('=')?;
+            (k == name).then_some(v)
+        })
+    }
+}
+
+fn fetch_json(url: &str) -> anyhow::Result<Value> {
+    let cfg = ureq::Agent::config_builder()
+        .timeout_connect(Some(std::time::Duration::from_secs(5)))
+        .timeout_global(Some(std::time::Duration::from_secs(15)))
+        .build();
+    let agent = ureq::Agent::new_with_config(cfg);
+    let body: String = agent
+        .get(url)
+        .header(
+            "User-Agent",
+            "Mozilla/5.0 (compatible; bib/0.1; +https://www.softwareheritage.org)",
+        )
+        .call()
+        .with_context(|| format!("failed Software Heritage request for {url}"))?
+        .into_body()
+        .read_to_string()
+        .context("failed to read Software Heritage response body")?;
+    serde_json::from_str(&body).context("invalid JSON from Software Heritage API")
+}
+
+/// Build an `@software` entry; `object` is the archived revision or release, when there is one.
+fn build_biblatex(swh: &SoftwareHeritage, object: Option<&Value>) -> String {
+    let (kind, hash) = swh.kind_and_hash();
+    let origin = swh.qualifier("origin").map(str::to_string);
+    let release_name = object
+        .filter(|_| kind == "rel")
+        .and_then(|o| scalar(o, "name"));
+
+    // Name the software after its origin repository, falling back to the release name.
+    let title = origin
+        .as_deref()
+        .and_then(|o| o.trim_end_matches('/').rsplit('/').next())
+        .map(|n| n.trim_end_matches(".git").to_string())
+        .filter(|n| !n.is_empty())
+        .or_else(|| release_name.clone())
+        .unwrap_or_else(|| format!("Software Heritage {kind} {}", &hash[..hash.len().min(7)]));
+
+    let mut fields: Vec<(&str, String)> = vec![("title", title)];
+    if let Some(author) = object
+        .and_then(|o| o.get("author"))
+        .and_then(|a| scalar(a, "name").or_else(|| scalar(a, "fullname")))
+        .and_then(|a| person_name(&a))
+    {
+        fields.push(("author", author));
+    }
+    if let Some(date) = object.and_then(|o| scalar(o, "date")) {
+        fields.push(("date", date.chars().take(10).collect()));
+    }
+    if let Some(version) = release_name {
+        fields.push(("version", version));
+    }
+    if let Some(origin) = origin {
+        fields.push(("repository", origin));
+    }
+    fields.push((
+        "url",
+        format!("https://archive.softwareheritage.org/{}", swh.full()),
+    ));
+    fields.push(("eprinttype", "swhid".to_string()));
+    fields.push(("eprint", swh.full()));
+    // Author names are escaped by `person_name`; nothing else here can carry braces.
+    write_entry("software", &swh.core, fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REV: &str = "swh:1:rev:309cf2674ee7a0749978cf8265ab91a60aea0f7d";
+
+    fn parse(s: &str) -> Option<Box<SoftwareHeritage>> {
+        <SoftwareHeritage as Identifier<'_>>::parse(s)
+    }
+
+    #[test]
+    fn parse_swhids_and_urls() {
+        let swh = parse(REV).unwrap();
+        assert_eq!(swh.core, REV);
+        assert!(swh.qualifiers.is_none());
+
+        let swh = parse(&format!(
+            "https://archive.softwareheritage.org/{REV}%3Borigin=https://github.com/acme/tool"
+        ))
+        .unwrap();
+        assert_eq!(swh.core, REV);
+        assert_eq!(
+            swh.qualifier("origin"),
+            Some("https://github.com/acme/tool")
+        );
+
+        for bad in [
+            "swh:1:rev:309cf26",
+            "swh:2:rev:309cf2674ee7a0749978cf8265ab91a60aea0f7d",
+            "swh:1:xyz:309cf2674ee7a0749978cf8265ab91a60aea0f7d",
+            "https://example.com/swh:1:rev:309cf2674ee7a0749978cf8265ab91a60aea0f7d",
+        ] {
+            assert!(parse(bad).is_none(), "should reject {bad}");
+        }
+    }
+
+    #[test]
+    fn revision_entry_carries_swhid_as_eprint() {
+        let swh = parse(&format!("{REV};origin=https://github.com/acme/tool.git")).unwrap();
+        let rev: Value = serde_json::from_str(
+            r#"{"author": {"fullname": "Jane Doe <jane@example.org>", "name": "Jane Doe"},
+                "date": "2024-02-03T10:00:00+01:00"}"#,
+        )
+        .unwrap();
+        let bib = build_biblatex(&swh, Some(&rev));
+        assert!(bib.starts_with(&format!("@software{{{REV},")), "{bib}");
+        assert!(bib.contains("title = {tool}"), "{bib}");
+        assert!(bib.contains("author = {Jane Doe}"), "{bib}");
+        assert!(bib.contains("date = {2024-02-03}"), "{bib}");
+        assert!(bib.contains("eprinttype = {swhid}"), "{bib}");
+        assert!(
+            bib.contains(&format!(
+                "eprint = {{{REV};origin=https://github.com/acme/tool.git}}"
+            )),
+            "{bib}"
+        );
+        assert!(Bibliography::parse(&bib).is_ok());
+    }
+}
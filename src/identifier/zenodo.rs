@@ -0,0 +1,251 @@
+use anyhow::Context;
+use biblatex::{Bibliography, Entry};
+use serde_json::Value;
+
+use crate::{
+    bibtex::{escape_braces, html_to_text, normalize_ws, scalar, write_entry},
+    identifier::Identifier,
+    resolver::IdFamily,
+};
+
+/// A Zenodo record URL (e.g., `https://zenodo.org/records/1234567`).
+pub struct Zenodo<'a> {
+    record: &'a str,
+}
+
+impl<'a> Identifier<'a> for Zenodo<'a> {
+    fn parse(identifier: &'a str) -> Option<Box<Self>> {
+        let s = identifier.trim();
+
+        // URLs:
+        //  - https://zenodo.org/records/1234567 (also /record/, /files/..., /api/records/)
+        //  - https://zenodo.org/doi/10.5281/zenodo.1234566 (concept DOI landing page)
+        let (host, path) = s
+            .strip_prefix("http://")
+            .or_else(|| s.strip_prefix("https://"))
+            .and_then(|rest| rest.split_once('/'))?;
+        if !matches!(
+            host.to_ascii_lowercase().as_str(),
+            "zenodo.org" | "www.zenodo.org"
+        ) {
+            return None;
+        }
+        let path = path.split(['?', '#']).next().unwrap_or(path);
+        let path = path.strip_prefix("api/").unwrap_or(path);
+        let record = if let Some(rest) = path
+            .strip_prefix("records/")
+            .or_else(|| path.strip_prefix("record/"))
+        {
+            rest.split('/').next()?
+        } else {
+            path.strip_prefix("doi/10.5281/zenodo.")?
+                .trim_end_matches('/')
+        };
+        (!record.is_empty() && record.bytes().all(|b| b.is_ascii_digit()))
+            .then(|| Box::new(Zenodo { record }))
+    }
+
+    fn resolve(&self) -> anyhow::Result<Entry> {
+        let url = format!("https://zenodo.org/api/records/{}", self.record);
+        let cfg = ureq::Agent::config_builder()
+            .timeout_connect(Some(std::time::Duration::from_secs(5)))
+            .timeout_global(Some(std::time::Duration::from_secs(15)))
+            .build();
+        let agent = ureq::Agent::new_with_config(cfg);
+        // Concept-record IDs redirect to the latest version, which is what we want to cite.
+        let body: String = agent
+            .get(&url)
+            .header(
+                "User-Agent",
+                "Mozilla/5.0 (compatible; bib/0.1; +https://zenodo.org)",
+            )
+            .header("Accept", "application/json")
+            .call()
+            .with_context(|| format!("failed Zenodo request for record {}", self.record))?
+            .into_body()
+            .read_to_string()
+            .context("failed to read Zenodo response body")?;
+        let v: Value = serde_json::from_str(&body).context("invalid JSON from Zenodo API")?;
+        let bib = build_biblatex(&v)?;
+        let bib = Bibliography::parse(&bib)
+            .map_err(|e| anyhow::anyhow!("failed to parse generated BibLaTeX: {e}"))?;
+        bib.into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("empty bibliography"))
+    }
+}
+
+impl IdFamily for Zenodo<'_> {
+    type For<'a> = Zenodo<'a>;
+}
+
+/// Map Zenodo's `resource_type.type` to a BibLaTeX entry type.
+fn entry_type(resource_type: &str) -> &'static str {
+    match resource_type {
+        "software" => "software",
+        "dataset" => "dataset",
+        "publication" => "article",
+        "poster" | "presentation" | "lesson" => "unpublished",
+        _ => "misc",
+    }
+}
+
+fn build_biblatex(v: &Value) -> anyhow::Result<String> {
+    let meta = v
+        .get("metadata")
+        .ok_or_else(|| anyhow::anyhow!("Zenodo record has no metadata"))?;
+    let id = scalar(v, "id").ok_or_else(|| anyhow::anyhow!("Zenodo record has no id"))?;
+    let title =
+        scalar(meta, "title").ok_or_else(|| anyhow::anyhow!("Zenodo record {id} has no title"))?;
+    let resource_type = meta
+        .get("resource_type")
+        .and_then(|r| scalar(r, "type"))
+        .unwrap_or_default();
+    let ty = entry_type(&resource_type);
+
+    let mut fields: Vec<(&str, String)> = vec![("title", title)];
+    // Creators come as "Last, First" already; organisations as a plain name.
+    let authors: Vec<String> = meta
+        .get("creators")
+        .and_then(Value::as_array)
+        .map(|cs| {
+            cs.iter()
+                .filter_map(|c| scalar(c, "name"))
+                .map(|n| {
+                    if n.contains(',') {
+                        escape_braces(&n)
+                    } else {
+                        format!("{{{}}}", escape_braces(&n))
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if !authors.is_empty() {
+        fields.push(("author", authors.join(" and ")));
+    }
+    if let Some(d) = scalar(meta, "publication_date") {
+        fields.push(("date", d));
+    }
+    if let Some(version) = scalar(meta, "version") {
+        fields.push(("version", version));
+    }
+    if let Some(doi) = scalar(v, "doi").or_else(|| scalar(meta, "doi")) {
+        fields.push(("doi", doi));
+    }
+    // The concept DOI always points at the latest version of the record.
+    if let Some(concept) = scalar(v, "conceptdoi") {
+        fields.push(("conceptdoi", concept));
+    }
+    fields.push(("publisher", "Zenodo".to_string()));
+    let url = v
+        .get("links")
+        .and_then(|l| scalar(l, "html"))
+        .unwrap_or_else(|| format!("https://zenodo.org/records/{id}"));
+    fields.push(("url", url));
+    if let Some(license) = meta.get("license").and_then(|l| scalar(l, "id")) {
+        fields.push(("license", license));
+    }
+    if let Some(desc) = scalar(meta, "description") {
+        let text = html_to_text(&desc);
+        if !text.is_empty() {
+            fields.push(("abstract", text));
+        }
+    }
+    let keywords: Vec<String> = meta
+        .get("keywords")
+        .and_then(Value::as_array)
+        .map(|k| {
+            k.iter()
+                .filter_map(|x| x.as_str().map(normalize_ws))
+                .collect()
+        })
+        .unwrap_or_default();
+    if !keywords.is_empty() {
+        fields.push(("keywords", keywords.join(", ")));
+    }
+    // Author lists carry intentional braces around organisation names; the rest is escaped here.
+    let fields = fields
+        .into_iter()
+        .map(|(k, v)| {
+            if k == "author" {
+                (k, v)
+            } else {
+                (k, escape_braces(&v))
+            }
+        })
+        .collect();
+    Ok(write_entry(ty, &format!("zenodo:{id}"), fields))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Option<&str> {
+        <Zenodo<'_> as Identifier<'_>>::parse(s).map(|z| z.record)
+    }
+
+    #[test]
+    fn parse_record_urls() {
+        for (input, id) in [
+            ("https://zenodo.org/records/1234567", "1234567"),
+            ("https://zenodo.org/record/1234567#.Y", "1234567"),
+            (
+                "https://zenodo.org/records/1234567/files/data.zip",
+                "1234567",
+            ),
+            ("https://zenodo.org/api/records/1234567", "1234567"),
+            ("https://zenodo.org/doi/10.5281/zenodo.1234566", "1234566"),
+        ] {
+            assert_eq!(parse(input), Some(id), "input {input}");
+        }
+        for bad in [
+            "https://zenodo.org/communities/foo",
+            "https://zenodo.org/records/abc",
+            "https://example.com/records/1",
+        ] {
+            assert!(parse(bad).is_none(), "should reject {bad}");
+        }
+    }
+
+    #[test]
+    fn software_record_keeps_version_and_concept_doi() {
+        let v: Value = serde_json::from_str(
+            r#"{"id": 1234567, "doi": "10.5281/zenodo.1234567",
+                "conceptdoi": "10.5281/zenodo.1234566",
+                "links": {"html": "https://zenodo.org/records/1234567"},
+                "metadata": {"title": "tool: v1.2.0", "version": "v1.2.0",
+                    "publication_date": "2024-03-01",
+                    "creators": [{"name": "Doe, Jane"}, {"name": "Acme Lab"}],
+                    "resource_type": {"type": "software"},
+                    "license": {"id": "mit"},
+                    "description": "<p>Does &amp; things</p>"}}"#,
+        )
+        .unwrap();
+        let bib = build_biblatex(&v).unwrap();
+        assert!(bib.starts_with("@software{zenodo:1234567,"), "{bib}");
+        assert!(bib.contains("author = {Doe, Jane and {Acme Lab}}"), "{bib}");
+        assert!(bib.contains("version = {v1.2.0}"), "{bib}");
+        assert!(bib.contains("doi = {10.5281/zenodo.1234567}"), "{bib}");
+        assert!(
+            bib.contains("conceptdoi = {10.5281/zenodo.1234566}"),
+            "{bib}"
+        );
+        assert!(bib.contains("abstract = {Does & things}"), "{bib}");
+        assert!(Bibliography::parse(&bib).is_ok());
+    }
+
+    #[test]
+    fn dataset_record_is_dataset() {
+        let v: Value = serde_json::from_str(
+            r#"{"id": 42, "metadata": {"title": "Data", "resource_type": {"type": "dataset"}}}"#,
+        )
+        .unwrap();
+        assert!(
+            build_biblatex(&v)
+                .unwrap()
+                .starts_with("@dataset{zenodo:42,")
+        );
+    }
+}
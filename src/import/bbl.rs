@@ -0,0 +1,558 @@
+//! Compiled bibliographies: biblatex `.bbl` files and `thebibliography` environments.
+
+use std::collections::HashSet;
+
+use biblatex::{Chunk, Entry, Spanned};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::bibtex::{escape_braces, parse_single, write_entry};
+
+use super::refstring::{self, ARXIV_RE, DOI_RE, URL_RE, YEAR_RE, split_authors, trim_punct};
+
+/// Parse the references of a `.bbl` file or a TeX document.
+///
+/// biblatex `.bbl` files carry structured `\entry` blocks, which map onto entries of the same type.
+/// `\bibitem`s (BibTeX styles and hand-written `thebibliography`) only carry typeset text, so each
+/// becomes a best-effort `@misc`, with any DOI, arXiv ID or URL in the text kept in its fields.
+pub fn parse(text: &str) -> Vec<Entry> {
+    let mut seen = HashSet::new();
+    let entries = if text.contains("\\entry{") {
+        parse_entries(text)
+    } else {
+        parse_bibitems(text)
+    };
+    // biblatex repeats entries once per sorting list.
+    entries
+        .into_iter()
+        .filter(|e| seen.insert(e.key.clone()))
+        .collect()
+}
+
+/// Skip whitespace and `%` comments.
+fn skip_space(s: &str) -> &str {
+    let mut s = s.trim_start();
+    while let Some(rest) = s.strip_prefix('%') {
+        s = rest.split_once('\n').map_or("", |(_, r)| r).trim_start();
+    }
+    s
+}
+
+/// Split a delimited group (e.g., `{...}` or `[...]`) off the start of `s`, honouring nested braces.
+fn delimited(s: &str, open: char, close: char) -> Option<(&str, &str)> {
+    let body = skip_space(s).strip_prefix(open)?;
+    let mut depth = 0usize;
+    let mut escaped = false;
+    for (i, c) in body.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '{' => depth += 1,
+            '}' if depth > 0 => depth -= 1,
+            c if c == close && depth == 0 => return Some((&body[..i], &body[i + 1..])),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn group(s: &str) -> Option<(&str, &str)> {
+    delimited(s, '{', '}')
+}
+
+/// Every top-level `{...}` group in `s`.
+fn groups(mut s: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    while let Some((g, rest)) = group(s) {
+        out.push(g);
+        s = rest;
+    }
+    out
+}
+
+static BIB_MACRO_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\\bib(rangedash|initperiod|initdelim|inithyphendelim|namedelim[a-di]|dateeraprefix|dateuncertain)\b ?")
+        .unwrap()
+});
+
+/// Replace biblatex's internal punctuation macros with what they typeset as.
+fn unmacro(s: &str) -> String {
+    BIB_MACRO_RE
+        .replace_all(s, |c: &regex::Captures| match &c[1] {
+            "rangedash" => "--",
+            "initperiod" => ".",
+            "inithyphendelim" => ".-",
+            "dateeraprefix" | "dateuncertain" => "",
+            _ => " ",
+        })
+        .into_owned()
+}
+
+/// Fields biber computes for typesetting, which are not part of the data.
+fn is_bookkeeping(field: &str) -> bool {
+    ["sort", "label", "extra", "unique", "single"]
+        .iter()
+        .any(|p| field.starts_with(p))
+        || field.ends_with("dateera")
+}
+
+fn parse_entries(text: &str) -> Vec<Entry> {
+    text.split("\\entry")
+        .skip(1)
+        .filter_map(|chunk| parse_entry(chunk.split("\\endentry").next()?))
+        .collect()
+}
+
+/// Parse one `\entry{key}{type}{options} ... ` block (without `\entry` and `\endentry`).
+fn parse_entry(chunk: &str) -> Option<Entry> {
+    let (key, rest) = group(chunk)?;
+    let (kind, rest) = group(rest)?;
+    let (_, mut rest) = group(rest)?;
+    let mut fields: Vec<(String, String)> = Vec::new();
+
+    while let Some(i) = rest.find('\\') {
+        rest = &rest[i + 1..];
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let (cmd, after) = rest.split_at(len);
+        rest = after;
+        match cmd {
+            "field" => {
+                let Some((name, r)) = group(rest) else {
+                    continue;
+                };
+                let Some((value, r)) = group(r) else { continue };
+                rest = r;
+                if !is_bookkeeping(name) {
+                    fields.push((name.to_string(), unmacro(value)));
+                }
+            }
+            "name" => {
+                let Some((role, r)) = group(rest) else {
+                    continue;
+                };
+                let Some((_, r)) = group(r) else { continue };
+                let Some((_, r)) = group(r) else { continue };
+                let Some((list, r)) = group(r) else { continue };
+                rest = r;
+                let names: Vec<String> = groups(list).into_iter().filter_map(parse_name).collect();
+                if !names.is_empty() {
+                    fields.push((role.to_string(), names.join(" and ")));
+                }
+            }
+            "list" => {
+                let Some((name, r)) = group(rest) else {
+                    continue;
+                };
+                let Some((_, r)) = group(r) else { continue };
+                let Some((list, r)) = group(r) else { continue };
+                rest = r;
+                let items: Vec<String> = groups(list).into_iter().map(unmacro).collect();
+                if !items.is_empty() {
+                    fields.push((name.to_string(), items.join(" and ")));
+                }
+            }
+            // \verb{doi}
+            // \verb 10.18653/v1/N19-1423
+            // \endverb
+            "verb" if skip_space(rest).starts_with('{') => {
+                let Some((name, r)) = group(rest) else {
+                    continue;
+                };
+                let (body, r) = r.split_once("\\endverb").unwrap_or((r, ""));
+                rest = r;
+                let value: String = body
+                    .lines()
+                    .filter_map(|l| l.trim().strip_prefix("\\verb"))
+                    .map(str::trim)
+                    .collect();
+                if !value.is_empty() {
+                    fields.push((name.to_string(), value));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let fields = fields
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.clone()))
+        .collect();
+    parse_single(&write_entry(kind, key, fields)).ok()
+}
+
+/// One name of a `\name` list, as `{{options}{family={...},given={...},...}}`, or in the older
+/// positional form `{{options}{family}{familyi}{given}{giveni}{prefix}{prefixi}{suffix}{suffixi}}`.
+fn parse_name(item: &str) -> Option<String> {
+    let (_, rest) = group(item)?;
+    let parts = groups(rest);
+    let part = |key: &str| -> Option<String> {
+        let value = if parts.len() == 1 {
+            name_part(parts[0], key)?
+        } else {
+            let idx = [
+                "family", "familyi", "given", "giveni", "prefix", "prefixi", "suffix",
+            ]
+            .iter()
+            .position(|k| *k == key)?;
+            parts.get(idx)?.to_string()
+        };
+        let value = unmacro(&value).trim().to_string();
+        (!value.is_empty()).then_some(value)
+    };
+    let family = part("family")?;
+    let (given, prefix, suffix) = (part("given"), part("prefix"), part("suffix"));
+
+    let mut name = match &prefix {
+        Some(p) => format!("{p} {family}"),
+        // Organisations come through as a family name with no given name.
+        None if given.is_none() && family.contains(' ') => format!("{{{family}}}"),
+        None => family,
+    };
+    if let Some(s) = suffix {
+        name.push_str(&format!(", {s}"));
+    }
+    if let Some(g) = given {
+        name.push_str(&format!(", {g}"));
+    }
+    Some(name)
+}
+
+/// Value of `key` in a `key={value},key=value,...` list.
+fn name_part(list: &str, key: &str) -> Option<String> {
+    let mut rest = list;
+    loop {
+        rest = skip_space(rest.trim_start_matches([',', ' ', '\n', '\t', '\r']));
+        let (k, after) = rest.split_once('=')?;
+        let after = skip_space(after);
+        let (value, r) = match group(after) {
+            Some((v, r)) => (v, r),
+            None => after.split_once(',').unwrap_or((after, "")),
+        };
+        if k.trim() == key {
+            return Some(value.trim().to_string());
+        }
+        rest = r;
+    }
+}
+
+fn parse_bibitems(text: &str) -> Vec<Entry> {
+    let body = match text.split_once("\\begin{thebibliography}") {
+        // The environment takes the widest label as an argument.
+        Some((_, rest)) => group(rest).map_or(rest, |(_, r)| r),
+        None => text,
+    };
+    let body = body
+        .split_once("\\end{thebibliography}")
+        .map_or(body, |(b, _)| b);
+
+    body.split("\\bibitem")
+        .skip(1)
+        .filter_map(|item| {
+            // natbib puts the citation label in an optional argument.
+            let item = delimited(item, '[', ']').map_or(item, |(_, rest)| rest);
+            let (key, text) = group(item)?;
+            Some(misc_entry(key.trim(), text))
+        })
+        .collect()
+}
+
+/// A best-effort entry for a `\bibitem`: authors and title when the `\newblock`s make them
+/// clear, or when the reference parser is confident about a single block; otherwise a `@misc`
+/// with the full text as a `note`. Any identifiers found in the text are kept.
+fn misc_entry(key: &str, raw: &str) -> Entry {
+    let blocks: Vec<String> = raw
+        .split("\\newblock")
+        .map(latex_to_text)
+        .filter(|b| !b.is_empty())
+        .collect();
+    let text = blocks.join(" ");
+    if blocks.len() == 1 {
+        let parsed = refstring::parse(&text);
+        if parsed.title.is_some()
+            && parsed.confidence() >= refstring::CONFIDENT
+            && let Ok(entry) = parsed.to_entry(key)
+        {
+            return entry;
+        }
+    }
+
+    let mut fields: Vec<(&str, String)> = Vec::new();
+    let authors = blocks.first().and_then(|b| split_authors(trim_punct(b)));
+    match (authors, blocks.get(1)) {
+        (Some(authors), Some(title)) if blocks.len() >= 2 => {
+            let authors: Vec<String> = authors.iter().map(|a| escape_braces(a)).collect();
+            fields.push(("author", authors.join(" and ")));
+            let title = trim_punct(title.trim_matches(['"', '“', '”', '`', '\'']));
+            fields.push(("title", escape_braces(title)));
+            if blocks.len() > 2 {
+                fields.push((
+                    "howpublished",
+                    escape_braces(trim_punct(&blocks[2..].join(" "))),
+                ));
+            }
+        }
+        _ => fields.push(("note", escape_braces(&text))),
+    }
+    if let Some(year) = YEAR_RE.captures_iter(&text).last() {
+        fields.push(("year", year[1].to_string()));
+    }
+    if let Some(doi) = DOI_RE.find(&text) {
+        fields.push(("doi", escape_braces(trim_punct(doi.as_str()))));
+    }
+    if let Some(c) = ARXIV_RE.captures(&text) {
+        fields.push(("eprint", escape_braces(&c[1])));
+        fields.push(("eprinttype", "arxiv".to_string()));
+    }
+    if let Some(url) = URL_RE.find(&text) {
+        fields.push(("url", escape_braces(trim_punct(url.as_str()))));
+    }
+
+    parse_single(&write_entry("misc", key, fields)).unwrap_or_else(|_| {
+        // Whatever the key, the reference itself is not lost.
+        let mut entry = Entry::new(key.to_string(), biblatex::EntryType::Misc);
+        entry.set("note", vec![Spanned::zero(Chunk::Normal(text))]);
+        entry
+    })
+}
+
+/// Flatten typeset LaTeX to plain text, keeping URLs and DOIs intact.
+fn latex_to_text(s: &str) -> String {
+    let mut out = String::new();
+    let mut rest = s;
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        match c {
+            '%' => rest = rest.split_once('\n').map_or("", |(_, r)| r),
+            '{' | '}' => {}
+            '~' => out.push(' '),
+            '\\' => {
+                let len = rest
+                    .find(|c: char| !c.is_ascii_alphabetic())
+                    .unwrap_or(rest.len());
+                if len == 0 {
+                    // Escaped characters (e.g., \&) and accents stay as they are.
+                    let Some(next) = rest.chars().next() else {
+                        break;
+                    };
+                    out.push('\\');
+                    out.push(next);
+                    rest = &rest[next.len_utf8()..];
+                    continue;
+                }
+                let (cmd, after) = rest.split_at(len);
+                rest = after;
+                match cmd {
+                    "url" | "path" | "doi" | "eprint" => {
+                        if let Some((arg, r)) = group(rest) {
+                            if cmd == "doi" && !arg.starts_with("10.") {
+                                out.push_str("doi:");
+                            }
+                            out.push_str(arg);
+                            rest = r;
+                        }
+                    }
+                    // \href{url}{text}; the URL is kept so it can be found later.
+                    "href" => {
+                        if let Some((url, r)) = group(rest)
+                            && let Some((text, r)) = group(r)
+                        {
+                            out.push_str(&latex_to_text(text));
+                            out.push_str(&format!(" {url}"));
+                            rest = r;
+                        }
+                    }
+                    // \bibinfo{field}{value} and \bibfield{field}{value} (revtex and others).
+                    "bibinfo" | "bibfield" => {
+                        if let Some((_, r)) = group(rest) {
+                            rest = r;
+                        }
+                    }
+                    "penalty" => rest = rest.trim_start_matches(|c: char| c.is_ascii_digit()),
+                    // Font switches and spacing hints, which only affect typesetting.
+                    "em"
+                    | "it"
+                    | "bf"
+                    | "sc"
+                    | "sl"
+                    | "rm"
+                    | "tt"
+                    | "emph"
+                    | "textit"
+                    | "textbf"
+                    | "textsc"
+                    | "textsl"
+                    | "textrm"
+                    | "texttt"
+                    | "textsf"
+                    | "textup"
+                    | "mbox"
+                    | "natexlab"
+                    | "urlprefix"
+                    | "doibase"
+                    | "relax"
+                    | "BIBentryALTinterwordspacing"
+                    | "BIBentrySTDinterwordspacing" => {}
+                    // Anything else (e.g., \TeX{}) is kept as LaTeX, with its argument.
+                    _ => {
+                        out.push('\\');
+                        out.push_str(cmd);
+                        if let Some((arg, r)) = group(rest) {
+                            out.push_str(&format!("{{{}}}", latex_to_text(arg)));
+                            rest = r;
+                        }
+                    }
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use biblatex::ChunksExt;
+
+    fn field(entry: &Entry, key: &str) -> Option<String> {
+        entry.get(key).map(|c| c.format_verbatim())
+    }
+
+    const BIBLATEX_BBL: &str = r#"\refsection{0}
+  \datalist[entry]{nty/global//global/global}
+    \entry{devlin2019}{inproceedings}{}
+      \name{author}{2}{}{%
+        {{un=0,uniquepart=base,hash=abc}{%
+           family={Devlin},
+           familyi={D\bibinitperiod},
+           given={Jacob},
+           giveni={J\bibinitperiod},
+           givenun=0}}%
+        {{hash=def}{%
+           family={Berg},
+           familyi={B\bibinitperiod},
+           given={Anna\bibnamedelima Maria},
+           giveni={A\bibinitperiod\bibinitdelim M\bibinitperiod},
+           prefix={van\bibnamedelima den},
+           prefixi={v\bibinitperiod\bibinitdelim d\bibinitperiod}}}%
+      }
+      \list{publisher}{1}{%
+        {Association for Computational Linguistics}%
+      }
+      \strng{namehash}{abc}
+      \field{sortinit}{D}
+      \field{labeltitlesource}{title}
+      \field{booktitle}{Proceedings of NAACL}
+      \field{title}{{BERT}: Pre-training of Deep Bidirectional Transformers}
+      \field{year}{2019}
+      \field{pages}{4171\bibrangedash 4186}
+      \range{pages}{16}
+      \verb{doi}
+      \verb 10.18653/v1/N19-1423
+      \endverb
+    \endentry
+    \entry{old}{article}{}
+      \name{author}{1}{}{%
+        {{hash=x}{Knuth}{K\bibinitperiod}{Donald\bibnamedelima E.}{D\bibinitperiod}{}{}{}{}}%
+      }
+      \field{title}{Literate Programming}
+      \field{journaltitle}{The Computer Journal}
+    \endentry
+  \enddatalist
+\endrefsection
+"#;
+
+    #[test]
+    fn parse_biblatex_entries() {
+        let entries = parse(BIBLATEX_BBL);
+        assert_eq!(entries.len(), 2);
+        let e = &entries[0];
+        assert_eq!(e.key, "devlin2019");
+        assert_eq!(e.entry_type.to_string(), "inproceedings");
+        assert_eq!(
+            field(e, "author").as_deref(),
+            Some("Devlin, Jacob and van den Berg, Anna Maria")
+        );
+        assert_eq!(
+            field(e, "publisher").as_deref(),
+            Some("Association for Computational Linguistics")
+        );
+        assert_eq!(field(e, "pages").as_deref(), Some("4171–4186"));
+        assert_eq!(field(e, "doi").as_deref(), Some("10.18653/v1/N19-1423"));
+        assert!(field(e, "sortinit").is_none());
+        assert!(field(e, "labeltitlesource").is_none());
+
+        assert_eq!(
+            field(&entries[1], "author").as_deref(),
+            Some("Knuth, Donald E.")
+        );
+    }
+
+    #[test]
+    fn parse_thebibliography_items() {
+        let text = r#"\begin{thebibliography}{10}
+
+\bibitem[Devlin et~al.(2019)]{devlin2019}
+J.~Devlin, M.-W. Chang, K.~Lee, and K.~Toutanova.
+\newblock {BERT}: Pre-training of deep bidirectional transformers.
+\newblock In \emph{Proceedings of NAACL}, pages 4171--4186, 2019.
+\newblock \doi{10.18653/v1/N19-1423}.
+
+\bibitem{vaswani}
+Vaswani, A., Shazeer, N., et al.
+\newblock Attention is all you need.
+\newblock \emph{arXiv preprint arXiv:1706.03762}, 2017.
+
+\bibitem{web}
+The Rust Project. \url{https://www.rust-lang.org/}, 2024.
+\end{thebibliography}
+"#;
+        let entries = parse(text);
+        assert_eq!(entries.len(), 3);
+
+        let e = &entries[0];
+        assert_eq!(e.key, "devlin2019");
+        assert_eq!(
+            field(e, "author").as_deref(),
+            Some("J. Devlin and M.-W. Chang and K. Lee and K. Toutanova")
+        );
+        assert_eq!(
+            field(e, "title").as_deref(),
+            Some("BERT: Pre-training of deep bidirectional transformers")
+        );
+        assert_eq!(field(e, "year").as_deref(), Some("2019"));
+        assert_eq!(field(e, "doi").as_deref(), Some("10.18653/v1/N19-1423"));
+
+        let e = &entries[1];
+        assert_eq!(
+            field(e, "author").as_deref(),
+            Some("Vaswani, A. and Shazeer, N. and others")
+        );
+        assert_eq!(field(e, "eprint").as_deref(), Some("1706.03762"));
+
+        let e = &entries[2];
+        assert!(field(e, "title").is_none());
+        assert_eq!(
+            field(e, "note").as_deref(),
+            Some("The Rust Project. https://www.rust-lang.org/, 2024.")
+        );
+        assert_eq!(
+            field(e, "url").as_deref(),
+            Some("https://www.rust-lang.org/")
+        );
+    }
+
+    #[test]
+    fn latex_to_text_flattens_markup() {
+        assert_eq!(
+            latex_to_text(
+                "In {\\em Proc.~of X}, \\href{https://x.org/a}{link} \\& more\\penalty0 text"
+            ),
+            "In Proc. of X, link https://x.org/a \\& more text"
+        );
+        assert_eq!(latex_to_text("G\\\"{o}del"), "G\\\"odel");
+        assert_eq!(latex_to_text("The \\TeX{}book"), "The \\TeX{}book");
+    }
+}
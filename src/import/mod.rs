@@ -0,0 +1,151 @@
+//! Reference lists of existing documents, re-resolved through the identifiers they carry.
+
+use std::path::Path;
+use std::sync::{Mutex, mpsc};
+
+use anyhow::Context;
+use biblatex::{Bibliography, ChunksExt, Entry};
+
+use crate::resolver;
+
+pub mod bbl;
+pub mod refstring;
+
+/// How many entries are resolved at once.
+const WORKERS: usize = 8;
+
+/// Read the references of a bibliography file: a `.bib` database, a `.bbl` file, or a TeX
+/// document with a `thebibliography` environment.
+pub fn read(path: &Path) -> anyhow::Result<Vec<Entry>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let entries: Vec<Entry> = if path.extension().is_some_and(|e| e == "bib") {
+        Bibliography::parse(&text)
+            .map_err(|e| anyhow::anyhow!("failed to parse {}: {e}", path.display()))?
+            .into_iter()
+            .collect()
+    } else {
+        bbl::parse(&text)
+    };
+    if entries.is_empty() {
+        return Err(anyhow::anyhow!("no references found in {}", path.display()));
+    }
+    Ok(entries)
+}
+
+/// Re-resolve entries through the identifiers they carry, so a reference list comes back with
+/// clean metadata. Resolved entries keep their citation key, so documents citing them still
+/// compile; entries without an identifier, or whose identifier fails to resolve, are kept as
+/// they are.
+pub fn resolve(entries: Vec<Entry>) -> Vec<Entry> {
+    let ids: Vec<Option<String>> = entries.iter().map(identifier_of).collect();
+    resolver::prefetch(&ids.iter().flatten().map(String::as_str).collect::<Vec<_>>());
+
+    // A small pool of workers, so a long reference list does not hit the registries with
+    // hundreds of requests at once.
+    let total = entries.len();
+    let (job_tx, job_rx) = mpsc::channel::<(usize, Entry, Option<String>)>();
+    for (idx, (entry, id)) in entries.into_iter().zip(ids).enumerate() {
+        let _ = job_tx.send((idx, entry, id));
+    }
+    drop(job_tx);
+    let job_rx = Mutex::new(job_rx);
+    let (done_tx, done_rx) = mpsc::channel::<(usize, Entry)>();
+
+    std::thread::scope(|scope| {
+        for _ in 0..WORKERS.min(total) {
+            let done_tx = done_tx.clone();
+            let job_rx = &job_rx;
+            scope.spawn(move || {
+                loop {
+                    let Ok((idx, entry, id)) = job_rx.lock().unwrap().recv() else {
+                        break;
+                    };
+                    let entry = match id.map(|id| resolver::resolve(&id)) {
+                        Some(Ok(mut resolved)) => {
+                            resolved.key = entry.key;
+                            resolved
+                        }
+                        _ => entry,
+                    };
+                    let _ = done_tx.send((idx, entry));
+                }
+            });
+        }
+    });
+    drop(done_tx);
+
+    let mut out: Vec<Option<Entry>> = vec![None; total];
+    for (idx, entry) in done_rx {
+        out[idx] = Some(entry);
+    }
+    out.into_iter().flatten().collect()
+}
+
+/// The identifier an entry can be resolved by: its DOI, its arXiv eprint, or its URL.
+fn identifier_of(entry: &Entry) -> Option<String> {
+    let field = |key: &str| {
+        entry
+            .fields
+            .get(key)
+            .map(|chunks| chunks.format_verbatim().trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    if let Some(doi) = field("doi") {
+        return Some(doi);
+    }
+    let eprint_type = field("eprinttype")
+        .or_else(|| field("archiveprefix"))
+        .unwrap_or_default();
+    if let Some(eprint) = field("eprint")
+        && eprint_type.eq_ignore_ascii_case("arxiv")
+    {
+        return Some(format!("arXiv:{eprint}"));
+    }
+    field("url")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifier_of_prefers_doi_then_eprint_then_url() {
+        let bib = Bibliography::parse(
+            "@article{a, doi = {10.1000/xyz}, eprint = {1810.04805}, eprinttype = {arXiv}}
+             @misc{b, eprint = {1810.04805}, archiveprefix = {arXiv}, url = {https://x.org}}
+             @misc{c, url = {https://example.org/paper}}
+             @misc{d, title = {Nothing to go by}}",
+        )
+        .unwrap();
+        let ids: Vec<Option<String>> = bib.iter().map(identifier_of).collect();
+        assert_eq!(
+            ids,
+            vec![
+                Some("10.1000/xyz".to_string()),
+                Some("arXiv:1810.04805".to_string()),
+                Some("https://example.org/paper".to_string()),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn read_picks_parser_by_extension() {
+        let tmp = tempfile::tempdir().unwrap();
+        let bib = tmp.path().join("refs.bib");
+        std::fs::write(&bib, "@misc{a, title = {A}}").unwrap();
+        assert_eq!(read(&bib).unwrap()[0].key, "a");
+
+        let bbl = tmp.path().join("main.bbl");
+        std::fs::write(
+            &bbl,
+            "\\begin{thebibliography}{1}\n\\bibitem{b} B.\n\\end{thebibliography}",
+        )
+        .unwrap();
+        assert_eq!(read(&bbl).unwrap()[0].key, "b");
+
+        std::fs::write(&bbl, "\\begin{thebibliography}{1}\\end{thebibliography}").unwrap();
+        assert!(read(&bbl).is_err());
+    }
+}
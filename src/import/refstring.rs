@@ -0,0 +1,583 @@
+//! Rule-based parsing of free-text references, such as those pasted from a paper's reference list.
+
+use biblatex::Entry;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::bibtex::{citation_key, escape_text, parse_single, write_entry};
+
+/// A parsed value and how sure the parser is about it, from 0 to 1.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scored<T> {
+    pub value: T,
+    pub confidence: f32,
+}
+
+fn scored<T>(value: T, confidence: f32) -> Option<Scored<T>> {
+    Some(Scored { value, confidence })
+}
+
+/// What could be made out of a reference string.
+#[derive(Clone, Debug, Default)]
+pub struct ParsedRef {
+    /// Names in BibLaTeX form ("Family, Given" or "Given Family"), with "others" for "et al.".
+    pub authors: Option<Scored<Vec<String>>>,
+    pub year: Option<Scored<String>>,
+    pub title: Option<Scored<String>>,
+    /// The journal, proceedings or other publication the work appeared in.
+    pub container: Option<Scored<String>>,
+    pub volume: Option<Scored<String>>,
+    pub issue: Option<Scored<String>>,
+    /// Page range in BibLaTeX form (e.g., "122--126").
+    pub pages: Option<Scored<String>>,
+    // Identifiers are taken verbatim from the text, so they carry no score.
+    pub doi: Option<String>,
+    pub arxiv: Option<String>,
+    pub url: Option<String>,
+}
+
+/// Overall confidence from which a parsed reference can be used without checking it.
+pub const CONFIDENT: f32 = 0.7;
+
+pub(crate) static DOI_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"\b10\.\d{4,9}/[^\s{}"<>\\]+"#).unwrap());
+pub(crate) static ARXIV_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)(?:arxiv(?:\s*preprint)?\s*:?\s*(?:abs/)?|arxiv\.org/(?:abs|pdf)/|\babs/)(\d{4}\.\d{4,5}(?:v\d+)?|[a-z-]+(?:\.[a-z]{2})?/\d{7}(?:v\d+)?)",
+    )
+    .unwrap()
+});
+pub(crate) static URL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"https?://[^\s{}\\]+").unwrap());
+pub(crate) static YEAR_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b(1[89]\d{2}|20\d{2})[a-z]?\b").unwrap());
+
+pub(crate) fn trim_punct(s: &str) -> &str {
+    s.trim_end_matches(['.', ',', ';', ':'])
+}
+
+// "Massalin, H." / "van der Berg, A.-M." and "H. Massalin" / "M.-W. Chang".
+const FAMILY: &str = r"(?:(?:van|von|de|der|den|del|la|le|di|da|du|dos)\s+)*\p{Lu}[\p{L}'’-]+(?:\s+\p{Lu}[\p{L}'’-]+)?";
+const INITIALS: &str = r"(?:\p{Lu}\.\s?-?\s?)+";
+const SEP: &str = r"(?:,\s*(?:and\s+|&\s*)?|\s+(?:and|&)\s+)";
+const ET_AL: &str = r"(?:,?\s*et\s+al\.?)?";
+
+static FAMILY_FIRST_RE: Lazy<Regex> = Lazy::new(|| {
+    let name = format!(r"{FAMILY},\s*{INITIALS}");
+    Regex::new(&format!(r"^(?:{name}{SEP})*{name}{ET_AL}")).unwrap()
+});
+static INITIALS_FIRST_RE: Lazy<Regex> = Lazy::new(|| {
+    let name = format!(r"{INITIALS}\s*{FAMILY}");
+    Regex::new(&format!(r"^(?:{name}{SEP})*{name}{ET_AL}")).unwrap()
+});
+/// The end of a sentence: a period not ending an initial, or a question or exclamation mark.
+static BOUNDARY_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:[^\s.]{2}\.|[?!])(?:\s|$)").unwrap());
+/// The end of an author list: a sentence boundary, or an author-year style "(2019)".
+static AUTHORS_END_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:[^\s.]{2}\.|[?!])(?:\s|$)|\s\((?:1[89]|20)\d{2}[a-z]?\)").unwrap()
+});
+static DOI_PREFIX_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)(?:https?://(?:dx\.)?doi\.org/|\bdoi:?\s*)$").unwrap());
+static PAREN_YEAR_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[\s.,]*\(((?:1[89]|20)\d{2})[a-z]?\)").unwrap());
+static PAGES_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:pp?\.|pages?)\s*(\d+)\s*[-–—]+\s*(\d+)|:\s*(\d+)\s*[-–—]+\s*(\d+)\b")
+        .unwrap()
+});
+static BARE_PAGES_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b(\d+)\s*[-–—]+\s*(\d+)\b").unwrap());
+static VOLUME_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\b(?:vol\.|volume)\s*(\d+)").unwrap());
+static ISSUE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\b(?:no\.|nr\.|issue)\s*(\d+)").unwrap());
+static VOLUME_ISSUE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b(\d+)\s*\((\d+(?:[-–/]\d+)?)\)").unwrap());
+static NUMBERING_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*(?:\[\d+\]|\(\d+\)|\d+\.\s)\s*").unwrap());
+static VENUE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i:\b(?:proc\.|proceedings|conference|symposium|workshop|journal|trans\.|transactions|letters|review|annals|ACM|IEEE)\b)|\b\p{Lu}{3,}\b")
+        .unwrap()
+});
+static PROCEEDINGS_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i:\b(?:proc\.|proceedings|conference|symposium|workshop)\b)|^\p{Lu}{3,}(?:\s*'?\d{2,4})?$")
+        .unwrap()
+});
+
+/// Parse a free-text reference (e.g., "Massalin, H. Superoptimizer: a look at the smallest
+/// program. ASPLOS 1987, pp. 122–126.") into its parts.
+pub fn parse(text: &str) -> ParsedRef {
+    let mut r = ParsedRef::default();
+    let mut s = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace(['“', '”', '„'], "\"");
+    s = NUMBERING_RE.replace(&s, "").into_owned();
+
+    // Identifiers first, as they contain digits and punctuation the other rules would pick at.
+    if let Some(m) = DOI_RE.find(&s) {
+        r.doi = Some(trim_punct(m.as_str()).to_string());
+        let start = DOI_PREFIX_RE
+            .find(&s[..m.start()])
+            .map_or(m.start(), |p| p.start());
+        s.replace_range(start..m.end(), "");
+    }
+    if let Some(c) = ARXIV_RE.captures(&s) {
+        r.arxiv = Some(c[1].to_string());
+        let range = c.get(0).unwrap().range();
+        s.replace_range(range, "");
+    }
+    if let Some(m) = URL_RE.find(&s) {
+        r.url = Some(trim_punct(m.as_str()).to_string());
+        s.replace_range(m.range(), "");
+    }
+
+    // Numbers with explicit markers.
+    if let Some(c) = PAGES_RE.captures(&s) {
+        let (first, last) = match (c.get(1), c.get(3)) {
+            (Some(a), _) => (a.as_str(), &c[2]),
+            (_, Some(a)) => (a.as_str(), &c[4]),
+            _ => unreachable!(),
+        };
+        r.pages = scored(format!("{first}--{last}"), 0.95);
+        let range = c.get(0).unwrap().range();
+        s.replace_range(range, "");
+    }
+    if let Some(c) = VOLUME_RE.captures(&s) {
+        r.volume = scored(c[1].to_string(), 0.95);
+        let range = c.get(0).unwrap().range();
+        s.replace_range(range, "");
+    }
+    if let Some(c) = ISSUE_RE.captures(&s) {
+        r.issue = scored(c[1].to_string(), 0.95);
+        let range = c.get(0).unwrap().range();
+        s.replace_range(range, "");
+    }
+    // "25(9)", but not "Nature (2015)".
+    if r.volume.is_none()
+        && let Some(c) = VOLUME_ISSUE_RE.captures(&s)
+        && !YEAR_RE.is_match(&c[2])
+    {
+        r.volume = scored(c[1].to_string(), 0.8);
+        r.issue = scored(c[2].to_string(), 0.8);
+        let range = c.get(0).unwrap().range();
+        s.replace_range(range, "");
+    }
+    if r.pages.is_none()
+        && let Some(c) = BARE_PAGES_RE.captures(&s)
+    {
+        let (first, last) = (&c[1], &c[2]);
+        let is_range = first.parse::<u64>().ok() < last.parse::<u64>().ok();
+        if is_range && !(YEAR_RE.is_match(first) && YEAR_RE.is_match(last)) {
+            r.pages = scored(format!("{first}--{last}"), 0.6);
+            let range = c.get(0).unwrap().range();
+            s.replace_range(range, "");
+        }
+    }
+
+    // Authors lead the reference.
+    let mut rest = s.trim().to_string();
+    if let Some(m) = FAMILY_FIRST_RE
+        .find(&rest)
+        .or_else(|| INITIALS_FIRST_RE.find(&rest))
+        && let Some(names) = split_authors(trim_name_list(m.as_str()))
+    {
+        r.authors = scored(names, 0.9);
+        rest = rest[m.end()..].to_string();
+    } else if let Some(b) = AUTHORS_END_RE.find(&rest) {
+        let end = if b.as_str().starts_with(char::is_whitespace) {
+            b.start()
+        } else {
+            b.end()
+        };
+        if let Some(names) = split_authors(trim_name_list(&rest[..end])) {
+            r.authors = scored(names, 0.6);
+            rest = rest[end..].to_string();
+        }
+    }
+
+    // Author-year styles put the year right after the authors.
+    if let Some(c) = PAREN_YEAR_RE.captures(&rest) {
+        r.year = scored(c[1].to_string(), 0.95);
+        rest = rest[c.get(0).unwrap().end()..].to_string();
+    }
+    let rest = rest.trim_start_matches(|c: char| c.is_whitespace() || ".,;:".contains(c));
+
+    // Then the title, up to the end of its sentence, unless it is quoted.
+    let sure = if r.authors.is_some() { 0.8 } else { 0.5 };
+    let (title, mut rest) = if let Some(quoted) = rest.strip_prefix('"')
+        && let Some((title, after)) = quoted.split_once('"')
+    {
+        (
+            scored(trim_punct(title.trim()).to_string(), 0.95),
+            after.to_string(),
+        )
+    } else if let Some(b) = BOUNDARY_RE.find(rest) {
+        let title = rest[..b.end()].trim().trim_end_matches('.');
+        (scored(title.to_string(), sure), rest[b.end()..].to_string())
+    } else {
+        (
+            scored(trim_punct(rest.trim()).to_string(), 0.4),
+            String::new(),
+        )
+    };
+    r.title = title.filter(|t| !t.value.is_empty());
+
+    if r.year.is_none()
+        && let Some(c) = YEAR_RE.captures_iter(&rest).last()
+    {
+        r.year = scored(c[1].to_string(), 0.7);
+        let range = c.get(0).unwrap().range();
+        rest.replace_range(range, "");
+    }
+
+    // What remains names the publication, possibly with its publisher.
+    let rest = rest.trim_start_matches(|c: char| c.is_whitespace() || ".,;:".contains(c));
+    let rest = rest
+        .strip_prefix("In:")
+        .or_else(|| rest.strip_prefix("In "))
+        .or_else(|| rest.strip_prefix("in "))
+        .unwrap_or(rest);
+    let container = tidy(rest);
+    if !container.is_empty() && container.chars().any(char::is_alphabetic) {
+        let confidence = if VENUE_RE.is_match(&container) || r.volume.is_some() {
+            0.85
+        } else {
+            0.6
+        };
+        r.container = scored(container, confidence);
+    }
+    r
+}
+
+/// Trim an author list's trailing punctuation, keeping the period of a final initial.
+fn trim_name_list(s: &str) -> &str {
+    let s = s.trim().trim_end_matches([',', ';', ':']).trim_end();
+    match s.strip_suffix('.') {
+        Some(rest) if !rest.ends_with(char::is_uppercase) => rest,
+        _ => s,
+    }
+}
+
+/// Drop the punctuation left behind by removed parts (e.g., "ASPLOS , ." becomes "ASPLOS").
+fn tidy(s: &str) -> String {
+    static SPACE_BEFORE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+([,.;:])").unwrap());
+    static REPEATED_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"([,.;:])[,.;:\s]+").unwrap());
+    let s = SPACE_BEFORE_RE.replace_all(s, "$1");
+    let s = REPEATED_RE.replace_all(&s, "$1 ");
+    s.trim()
+        .trim_matches(|c: char| ",.;:()".contains(c))
+        .trim()
+        .to_string()
+}
+
+impl ParsedRef {
+    /// How much of the reference was made out, weighted towards what identifies a work.
+    pub fn confidence(&self) -> f32 {
+        fn c<T>(f: &Option<Scored<T>>) -> f32 {
+            f.as_ref().map_or(0.0, |f| f.confidence)
+        }
+        (2.0 * c(&self.title) + 1.5 * c(&self.authors) + c(&self.year) + 0.5 * c(&self.container))
+            / 5.0
+    }
+
+    /// Per-field confidences, for display.
+    pub fn scores(&self) -> Vec<(&'static str, f32)> {
+        [
+            ("authors", self.authors.as_ref().map(|f| f.confidence)),
+            ("title", self.title.as_ref().map(|f| f.confidence)),
+            ("year", self.year.as_ref().map(|f| f.confidence)),
+            ("container", self.container.as_ref().map(|f| f.confidence)),
+            ("volume", self.volume.as_ref().map(|f| f.confidence)),
+            ("issue", self.issue.as_ref().map(|f| f.confidence)),
+            ("pages", self.pages.as_ref().map(|f| f.confidence)),
+        ]
+        .into_iter()
+        .filter_map(|(k, v)| Some((k, v?)))
+        .collect()
+    }
+
+    /// A citation key for the reference; see [`citation_key`].
+    pub fn key(&self) -> String {
+        citation_key(
+            self.authors.as_ref().map_or(&[], |a| a.value.as_slice()),
+            self.year.as_ref().map(|y| y.value.as_str()),
+            self.title.as_ref().map(|t| t.value.as_str()),
+        )
+    }
+
+    /// Build an entry: `@inproceedings` or `@article` when the publication looks like one,
+    /// `@misc` otherwise.
+    pub fn to_entry(&self, key: &str) -> anyhow::Result<Entry> {
+        let container = self.container.as_ref().map(|c| c.value.as_str());
+        let (kind, container_field) = match container {
+            Some(c) if PROCEEDINGS_RE.is_match(c) => ("inproceedings", "booktitle"),
+            Some(_) if self.volume.is_some() || self.issue.is_some() => ("article", "journaltitle"),
+            Some(c) if VENUE_RE.is_match(c) => ("article", "journaltitle"),
+            _ => ("misc", "howpublished"),
+        };
+
+        let mut fields: Vec<(&str, String)> = Vec::new();
+        if let Some(a) = &self.authors {
+            let authors: Vec<String> = a.value.iter().map(|n| escape_text(n)).collect();
+            fields.push(("author", authors.join(" and ")));
+        }
+        if let Some(t) = &self.title {
+            fields.push(("title", escape_text(&t.value)));
+        }
+        if let Some(c) = container {
+            fields.push((container_field, escape_text(c)));
+        }
+        for (name, value) in [
+            ("year", &self.year),
+            ("volume", &self.volume),
+            ("number", &self.issue),
+            ("pages", &self.pages),
+        ] {
+            if let Some(v) = value {
+                fields.push((name, escape_text(&v.value)));
+            }
+        }
+        if let Some(doi) = &self.doi {
+            fields.push(("doi", escape_text(doi)));
+        }
+        if let Some(id) = &self.arxiv {
+            fields.push(("eprint", escape_text(id)));
+            fields.push(("eprinttype", "arxiv".to_string()));
+        }
+        if let Some(url) = &self.url {
+            fields.push(("url", escape_text(url)));
+        }
+        parse_single(&write_entry(kind, key, fields))
+    }
+}
+
+/// Split an author block ("A. Smith, B. Jones, and C. Doe" or "Smith, A., Jones, B.") into
+/// names, or `None` when it does not look like a list of names.
+pub(crate) fn split_authors(block: &str) -> Option<Vec<String>> {
+    static SEP_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r",?\s+and\s+|\s*(?:[,;]|\\?&)\s*").unwrap());
+    let is_initials = |s: &str| {
+        s.split([' ', '-'])
+            .all(|w| w.ends_with('.') || (w.len() == 1 && w.chars().all(char::is_uppercase)))
+    };
+    // Every word of a name is capitalised, but for particles such as "van" or "de".
+    let is_name = |s: &str| {
+        s.split(' ').all(|w| {
+            w.chars().next().is_some_and(char::is_uppercase)
+                || matches!(
+                    w,
+                    "van"
+                        | "von"
+                        | "de"
+                        | "der"
+                        | "den"
+                        | "del"
+                        | "la"
+                        | "le"
+                        | "di"
+                        | "da"
+                        | "du"
+                        | "dos"
+                )
+        })
+    };
+
+    let mut pieces: Vec<&str> = SEP_RE
+        .split(block)
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect();
+    let et_al = pieces
+        .last()
+        .is_some_and(|p| p.trim_end_matches('.').ends_with("et al"));
+    if et_al {
+        let last = pieces.pop()?;
+        let name = last.trim_end_matches('.').trim_end_matches("et al").trim();
+        if !name.is_empty() {
+            pieces.push(name);
+        }
+    }
+    if pieces.is_empty()
+        || pieces
+            .iter()
+            .any(|p| p.split(' ').count() > 4 || !is_name(p))
+    {
+        return None;
+    }
+
+    let mut names: Vec<String> = if pieces.len().is_multiple_of(2)
+        && pieces.iter().skip(1).step_by(2).all(|p| is_initials(p))
+        && pieces.iter().step_by(2).all(|p| !is_initials(p))
+    {
+        // "Family, Given" pairs.
+        pieces
+            .chunks(2)
+            .map(|c| format!("{}, {}", c[0], c[1]))
+            .collect()
+    } else if pieces.iter().all(|p| p.contains(' ') && !is_initials(p)) {
+        pieces.iter().map(|p| p.to_string()).collect()
+    } else {
+        return None;
+    };
+    if et_al {
+        names.push("others".to_string());
+    }
+    Some(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use biblatex::ChunksExt;
+
+    fn value<T: Clone>(f: &Option<Scored<T>>) -> Option<T> {
+        f.as_ref().map(|f| f.value.clone())
+    }
+
+    #[test]
+    fn parse_conference_reference() {
+        let r = parse(
+            "Massalin, H. Superoptimizer: a look at the smallest program. ASPLOS 1987, pp. 122–126.",
+        );
+        assert_eq!(value(&r.authors), Some(vec!["Massalin, H.".to_string()]));
+        assert_eq!(
+            value(&r.title).as_deref(),
+            Some("Superoptimizer: a look at the smallest program")
+        );
+        assert_eq!(value(&r.container).as_deref(), Some("ASPLOS"));
+        assert_eq!(value(&r.year).as_deref(), Some("1987"));
+        assert_eq!(value(&r.pages).as_deref(), Some("122--126"));
+        assert!(r.confidence() >= CONFIDENT, "{}", r.confidence());
+
+        let entry = r.to_entry(&r.key()).unwrap();
+        assert_eq!(entry.key, "massalin1987superoptimizer");
+        assert_eq!(entry.entry_type.to_string(), "inproceedings");
+    }
+
+    #[test]
+    fn parse_journal_reference_in_author_year_style() {
+        let r = parse(
+            "[3] Topol, E. J., & Smith, A. (2019). High-performance medicine: the convergence of \
+             human and artificial intelligence. Nature Medicine, 25(1), 44-56. \
+             https://doi.org/10.1038/s41591-018-0300-7",
+        );
+        assert_eq!(
+            value(&r.authors),
+            Some(vec!["Topol, E. J.".to_string(), "Smith, A.".to_string()])
+        );
+        assert_eq!(r.year.as_ref().map(|y| y.confidence), Some(0.95));
+        assert_eq!(
+            value(&r.title).as_deref(),
+            Some("High-performance medicine: the convergence of human and artificial intelligence")
+        );
+        assert_eq!(value(&r.container).as_deref(), Some("Nature Medicine"));
+        assert_eq!(value(&r.volume).as_deref(), Some("25"));
+        assert_eq!(value(&r.issue).as_deref(), Some("1"));
+        assert_eq!(value(&r.pages).as_deref(), Some("44--56"));
+        assert_eq!(r.doi.as_deref(), Some("10.1038/s41591-018-0300-7"));
+        assert!(r.url.is_none());
+        assert_eq!(r.to_entry("k").unwrap().entry_type.to_string(), "article");
+    }
+
+    #[test]
+    fn parse_full_names_and_quoted_title() {
+        let r = parse(
+            "Jacob Devlin, Ming-Wei Chang, Kenton Lee, and Kristina Toutanova. \"BERT: Pre-training \
+             of Deep Bidirectional Transformers for Language Understanding.\" In Proceedings of \
+             NAACL-HLT, 2019. arXiv:1810.04805",
+        );
+        assert_eq!(value(&r.authors).map(|a| a.len()), Some(4));
+        assert_eq!(r.title.as_ref().map(|t| t.confidence), Some(0.95));
+        assert_eq!(
+            value(&r.container).as_deref(),
+            Some("Proceedings of NAACL-HLT")
+        );
+        assert_eq!(value(&r.year).as_deref(), Some("2019"));
+        assert_eq!(r.arxiv.as_deref(), Some("1810.04805"));
+    }
+
+    #[test]
+    fn to_entry_keeps_backslashes_literal() {
+        let r = parse("Foo {bar} baz\\. In Proc. of X.");
+        let entry = r.to_entry(&r.key()).unwrap();
+        let text: Vec<String> = entry.fields.values().map(|c| c.format_verbatim()).collect();
+        assert!(
+            text.iter().any(|v| v.contains("baz\\")),
+            "{}",
+            entry.to_biblatex_string()
+        );
+        assert!(!text.iter().any(|v| v.contains('İ')), "{text:?}");
+    }
+
+    #[test]
+    fn one_word_journals_are_articles() {
+        let r = parse("LeCun, Y., Bengio, Y. Deep learning. Nature 521(7553):436–444, 2015.");
+        assert_eq!(value(&r.container).as_deref(), Some("Nature"));
+        let entry = r.to_entry("k").unwrap();
+        assert_eq!(entry.entry_type.to_string(), "article");
+        assert_eq!(
+            entry
+                .get("journaltitle")
+                .map(|c| c.format_verbatim())
+                .as_deref(),
+            Some("Nature")
+        );
+
+        let r = ParsedRef {
+            title: scored("A title".to_string(), 0.9),
+            container: scored("Science".to_string(), 0.85),
+            volume: scored("313".to_string(), 0.9),
+            ..Default::default()
+        };
+        let entry = r.to_entry("k").unwrap();
+        assert_eq!(entry.entry_type.to_string(), "article");
+        assert!(entry.get("journaltitle").is_some());
+        assert!(!PROCEEDINGS_RE.is_match("Science"));
+        assert!(PROCEEDINGS_RE.is_match("ICML 2019"));
+    }
+
+    #[test]
+    fn unstructured_text_has_low_confidence() {
+        let r = parse("some notes about a talk we heard");
+        assert!(r.authors.is_none());
+        assert!(r.confidence() < CONFIDENT);
+        assert_eq!(r.to_entry(&r.key()).unwrap().entry_type.to_string(), "misc");
+    }
+
+    #[test]
+    fn to_entry_keeps_identifiers_next_to_stray_braces() {
+        let r = ParsedRef {
+            title: scored("Sets } and {maps".to_string(), 0.9),
+            doi: Some("10.1000/xyz".to_string()),
+            url: Some("https://example.org/paper".to_string()),
+            ..Default::default()
+        };
+        let entry = r.to_entry("k").unwrap();
+        let field = |k: &str| entry.get(k).map(|c| c.format_verbatim());
+        assert_eq!(field("title").as_deref(), Some("Sets } and {maps"));
+        assert_eq!(field("doi").as_deref(), Some("10.1000/xyz"));
+        assert_eq!(field("url").as_deref(), Some("https://example.org/paper"));
+    }
+
+    #[test]
+    fn split_authors_shapes() {
+        assert_eq!(
+            split_authors("J. Devlin, M.-W. Chang, and K. Toutanova"),
+            Some(vec![
+                "J. Devlin".to_string(),
+                "M.-W. Chang".to_string(),
+                "K. Toutanova".to_string()
+            ])
+        );
+        assert_eq!(
+            split_authors("Vaswani, A., Shazeer, N., et al"),
+            Some(vec![
+                "Vaswani, A.".to_string(),
+                "Shazeer, N.".to_string(),
+                "others".to_string()
+            ])
+        );
+        assert_eq!(split_authors("Attention is all you need"), None);
+    }
+}
@@ -2,27 +2,63 @@ use biblatex::Entry;
 use clap::Parser;
 use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
 use owo_colors::OwoColorize;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
 use std::sync::mpsc;
 use std::time::{Duration, Instant};
 
 use crate::cli::{Cli, Source};
-use crate::resolver::resolve;
+use crate::identifier::arxiv;
+use crate::import::refstring;
+use crate::resolver::resolve_many;
 
+mod bibtex;
+mod cff;
 mod cli;
 mod identifier;
+mod import;
+mod pdf;
+mod picker;
+mod project;
+mod pull;
 mod resolver;
+mod search;
 
 fn main() -> anyhow::Result<()> {
     let args = Cli::parse();
     match args.command {
-        cli::Command::Fetch { from } => {
+        cli::Command::Fetch {
+            from,
+            prefer_published,
+            max_items,
+            all,
+            search,
+            interactive,
+        } => {
+            let max_items = (!all).then_some(max_items);
+            resolver::configure(resolver::Options {
+                prefer_published,
+                search,
+            });
             let start = Instant::now();
-            // Collect only identifier sources for now (ignore files for the moment).
-            let jobs: Vec<String> = from
-                .iter()
-                .filter_map(|s| match s {
-                    Source::Identifier(i) => Some(i.clone()),
-                    Source::File(_) => None,
+            // Collect identifier, project, PDF and `.bib` sources, looking into directories for
+            // PDFs. Other files are skipped.
+            let jobs: Vec<Source> = from
+                .into_iter()
+                .flat_map(|s| match s {
+                    Source::File(dir) if dir.is_dir() => pdfs_in(&dir),
+                    Source::File(path) if path.extension().is_some_and(|e| e == "bib") => {
+                        vec![Source::File(path)]
+                    }
+                    Source::File(path) => {
+                        eprintln!(
+                            "{} {}: not an identifier, project, PDF or .bib file; skipped",
+                            "!".yellow().bold(),
+                            path.display()
+                        );
+                        Vec::new()
+                    }
+                    other => vec![other],
                 })
                 .collect();
             let total = jobs.len();
@@ -38,50 +74,71 @@ fn main() -> anyhow::Result<()> {
             );
             // We'll update this message with the first non-completed item ID.
             if let Some(first) = jobs.first() {
-                root.set_message(format!("Resolving: {}", first));
+                root.set_message(format!("Resolving: {}", label(first)));
             } else {
                 root.set_message("Resolving items...");
             }
 
+            // Batch what the translators can batch (e.g., arXiv Atom queries) before fanning out.
+            let ids: Vec<&str> = jobs
+                .iter()
+                .filter_map(|s| match s {
+                    Source::Identifier(id) => Some(id.as_str()),
+                    _ => None,
+                })
+                .collect();
+            resolver::prefetch(&ids);
+
             // Spawn resolver threads; we remove per-item bars and only update the root bar.
             let mut handles = Vec::with_capacity(total);
-            let (tx, rx) = mpsc::channel::<(usize, anyhow::Result<String>)>();
-            for (idx, id) in jobs.iter().cloned().enumerate() {
+            let (tx, rx) = mpsc::channel::<(usize, anyhow::Result<Vec<Entry>>)>();
+            for (idx, src) in jobs.iter().cloned().enumerate() {
                 let txc = tx.clone();
                 let handle = std::thread::spawn(move || {
                     // Ensure translator panics do not take down the worker thread.
-                    let result: anyhow::Result<Entry> = match std::panic::catch_unwind(
-                        std::panic::AssertUnwindSafe(|| resolve(&id)),
+                    // One identifier may stand for several items (e.g., a DBLP person page).
+                    let result: anyhow::Result<Vec<Entry>> = match std::panic::catch_unwind(
+                        std::panic::AssertUnwindSafe(|| match &src {
+                            // Ask for one item past the cap, so that a cut listing shows.
+                            Source::Identifier(id) => {
+                                resolve_many(id, max_items.map(|cap| cap + 1))
+                            }
+                            Source::Project(path) => project::resolve(path).map(|e| vec![e]),
+                            Source::Pdf(path) => pdf::resolve(path).map(|e| vec![e]),
+                            // A bibliography lists several items, each resolved again
+                            // through the identifier it carries, as `bib import` does.
+                            Source::File(path) => import::read(path).map(import::resolve),
+                        }),
                     ) {
                         Ok(r) => r,
                         Err(_) => Err(anyhow::anyhow!(
                             "resolver panicked for identifier: {}",
-                            id
+                            label(&src)
                         )),
                     };
                     // Report back to main regardless of success/failure.
-                    let _ = txc.send((idx, result.map(|e| e.to_biblatex_string())));
+                    let _ = txc.send((idx, result));
                 });
                 handles.push(handle);
             }
             drop(tx); // Close the channel in main
 
-            // Collect results in input order.
-            let mut ok_results: Vec<Option<String>> = vec![None; total];
-            let mut errors: Vec<anyhow::Error> = Vec::new();
+            // Collect results in input order; each input contributes zero or more entries.
+            let mut ok_results: Vec<Option<Vec<Entry>>> = vec![None; total];
+            let mut errors: Vec<(usize, anyhow::Error)> = Vec::new();
             let mut done: Vec<bool> = vec![false; total];
             for _ in 0..total {
                 if let Ok((idx, res)) = rx.recv() {
                     match res {
                         Ok(s) => ok_results[idx] = Some(s),
-                        Err(e) => errors.push(e),
+                        Err(e) => errors.push((idx, e)),
                     }
                     done[idx] = true;
                     // Update the root message to the first non-completed item, if any.
                     if let Some(next_idx) = done.iter().position(|&d| !d)
                         && let Some(next) = jobs.get(next_idx)
                     {
-                        root.set_message(format!("Resolving: {}", next));
+                        root.set_message(format!("Resolving: {}", label(next)));
                     }
                     root.inc(1);
                 }
@@ -96,10 +153,95 @@ fn main() -> anyhow::Result<()> {
             root.disable_steady_tick();
             root.finish_and_clear();
 
+            // Trim multi-item sources to the cap, asking first when someone is at the terminal,
+            // and only then offer what is left to the picker. Bibliography files are exempt:
+            // every entry in them was asked for.
+            if let Some(cap) = max_items {
+                let at_terminal = std::io::stdin().is_terminal() && std::io::stderr().is_terminal();
+                for (idx, items) in ok_results.iter_mut().enumerate() {
+                    if let Some(items) = items
+                        && items.len() > cap
+                        && !matches!(jobs[idx], Source::File(_))
+                    {
+                        let src = label(&jobs[idx]);
+                        // Sources that page their results stop one past the cap, so all we
+                        // know is that they list more.
+                        let cut_short = items.len() == cap + 1;
+                        let (total, count) = if cut_short {
+                            (usize::MAX, format!("more than {cap}"))
+                        } else {
+                            (items.len(), items.len().to_string())
+                        };
+                        let keep = if at_terminal {
+                            ask_how_many(&src, &count, total, cap)
+                        } else {
+                            eprintln!(
+                                "{} {}: kept the first {} of {} items (pass --all to keep every one)",
+                                "!".yellow().bold(),
+                                src,
+                                cap,
+                                count
+                            );
+                            cap
+                        };
+                        if keep > items.len()
+                            && cut_short
+                            && let Source::Identifier(id) = &jobs[idx]
+                        {
+                            let limit = (keep != usize::MAX).then_some(keep);
+                            match resolve_many(id, limit) {
+                                Ok(more) => *items = more,
+                                Err(e) => eprintln!("{} {}: {}", "✗".red().bold(), src, e.red()),
+                            }
+                        }
+                        items.truncate(keep);
+                    }
+                }
+            }
+
+            if interactive && picker::available() {
+                // Let the user choose among the items of multi-item sources, and among the
+                // candidates of searches without a confident match.
+                for (idx, items) in ok_results.iter_mut().enumerate() {
+                    if let Some(items) = items
+                        && items.len() > 1
+                    {
+                        let src = label(&jobs[idx]);
+                        let choices: Vec<picker::Choice> = items
+                            .iter()
+                            .map(|e| picker::Choice::from_entry(e, &src))
+                            .collect();
+                        let picked = picker::pick(
+                            &format!("{src} lists {} items", items.len()),
+                            &choices,
+                            &mut |i| Ok(items[i].to_biblatex_string()),
+                        );
+                        *items = picked.into_iter().map(|i| items[i].clone()).collect();
+                    }
+                }
+                let mut unresolved = Vec::new();
+                for (idx, e) in errors {
+                    let Some(m) = e.downcast_ref::<search::NoConfidentMatch>() else {
+                        unresolved.push((idx, e));
+                        continue;
+                    };
+                    match picker::pick_candidates(
+                        &format!("No confident match for {:?}", m.text),
+                        &m.candidates,
+                        "Crossref",
+                    ) {
+                        Ok(entries) => ok_results[idx] = Some(entries),
+                        Err(e) => unresolved.push((idx, e)),
+                    }
+                }
+                errors = unresolved;
+            }
+
+            // Counted per input, like `total` and the failures, however many entries each gave.
+            let ok_count = ok_results.iter().filter(|r| r.is_some()).count();
             let mut output = String::new();
-            let mut ok_count: usize = 0;
-            for s in ok_results.into_iter().flatten() {
-                ok_count += 1;
+            for entry in ok_results.into_iter().flatten().flatten() {
+                let s = entry.to_biblatex_string();
                 output.push_str(&s);
                 if !s.ends_with('\n') {
                     output.push('\n');
@@ -109,7 +251,7 @@ fn main() -> anyhow::Result<()> {
                 print!("{}", output);
             }
             // Print errors after clearing progress, to stderr.
-            for e in &errors {
+            for (_, e) in &errors {
                 eprintln!("{} {}", "✗".red().bold(), e.red());
             }
             let fail_count = errors.len();
@@ -137,11 +279,240 @@ fn main() -> anyhow::Result<()> {
                 eprintln!("{}  •  {}  •  {}  •  {}", ok_s, fail_s, total_s, time_s);
             }
         }
-        cli::Command::Pull { from: _ } => todo!(),
+        cli::Command::Pull {
+            from,
+            source,
+            output,
+            resolve,
+        } => {
+            if !source {
+                return Err(anyhow::anyhow!(
+                    "only `pull --source` (arXiv e-print sources) is supported so far"
+                ));
+            }
+            let mut failed = 0;
+            for src in &from {
+                let Source::Identifier(id) = src else {
+                    eprintln!(
+                        "{} {}: only arXiv identifiers have e-print sources",
+                        "✗".red().bold(),
+                        label(src)
+                    );
+                    failed += 1;
+                    continue;
+                };
+                let files = match pull::pull_arxiv_source(id, &output) {
+                    Ok(files) => files,
+                    Err(e) => {
+                        eprintln!("{} {}", "✗".red().bold(), e.red());
+                        failed += 1;
+                        continue;
+                    }
+                };
+                eprintln!(
+                    "{} {}: unpacked into {}",
+                    "✓".green().bold(),
+                    id,
+                    files.dir.display()
+                );
+                if files.bibliographies.is_empty() {
+                    eprintln!("  no .bbl or .bib files found");
+                }
+                for path in &files.bibliographies {
+                    eprintln!("  {}", path.display());
+                }
+                if !resolve {
+                    continue;
+                }
+                // A .bbl holds exactly the references the paper cites; a .bib database may hold
+                // many more, so it is only used when no .bbl was shipped.
+                let is_bbl = |p: &&PathBuf| p.extension().is_some_and(|e| e == "bbl");
+                let paths: Vec<&PathBuf> = if files.bibliographies.iter().any(|p| is_bbl(&p)) {
+                    files.bibliographies.iter().filter(is_bbl).collect()
+                } else {
+                    files.bibliographies.iter().collect()
+                };
+                for path in paths {
+                    match import::read(path) {
+                        Ok(entries) => print_entries(&import::resolve(entries)),
+                        Err(e) => eprintln!("{} {}", "✗".red().bold(), e.red()),
+                    }
+                }
+            }
+            if failed > 0 {
+                return Err(anyhow::anyhow!(
+                    "{failed} of {} sources could not be pulled",
+                    from.len()
+                ));
+            }
+        }
+        cli::Command::Import { file } => {
+            print_entries(&import::resolve(import::read(&file)?));
+        }
+        cli::Command::Search {
+            query,
+            year,
+            authors,
+            rows,
+            interactive,
+        } => {
+            let query = search::Query {
+                title: Some(query.clone()),
+                text: query,
+                year,
+                authors,
+            };
+            let ranked = search::search(&query, rows)?;
+            match ranked.first() {
+                Some(_) if interactive && picker::available() => {
+                    print_entries(&picker::pick_candidates(
+                        &format!("Candidates for {:?}, best first", query.text),
+                        &ranked,
+                        "Crossref",
+                    )?);
+                }
+                Some((score, c)) if *score >= search::ACCEPT => {
+                    eprintln!("{} {}", "✓".green().bold(), search::describe(*score, c));
+                    print_entries(&[resolver::resolve(&c.doi)?]);
+                }
+                Some(_) => {
+                    eprintln!(
+                        "{} no confident match; candidates, best first:",
+                        "!".yellow().bold()
+                    );
+                    for (score, c) in &ranked {
+                        println!("{}", search::describe(*score, c));
+                    }
+                }
+                None => return Err(anyhow::anyhow!("no search results for {:?}", query.text)),
+            }
+        }
+        cli::Command::ParseRef { text, confirm } => {
+            let parsed = refstring::parse(&text);
+            let scores: Vec<String> = parsed
+                .scores()
+                .iter()
+                .map(|(field, score)| format!("{field} {score:.2}"))
+                .collect();
+            eprintln!(
+                "{} {:.2} ({})",
+                "confidence".dimmed(),
+                parsed.confidence(),
+                scores.join(", ")
+            );
+            let key = parsed.key();
+            let mut entry = parsed.to_entry(&key)?;
+            if confirm && parsed.confidence() < refstring::CONFIDENT {
+                match search::confirm(&text, &parsed) {
+                    Ok(Some(mut found)) => {
+                        found.key = key;
+                        entry = found;
+                    }
+                    Ok(None) => eprintln!(
+                        "{} no confident match found online; printing the parsed reference",
+                        "!".yellow().bold()
+                    ),
+                    Err(e) => eprintln!("{} {}", "✗".red().bold(), e.red()),
+                }
+            }
+            print_entries(&[entry]);
+        }
+        cli::Command::Arxiv { command } => match command {
+            cli::ArxivCommand::Versions { id } => {
+                for v in arxiv::versions(&id)? {
+                    let mut line = format!("v{}\t{}", v.version, v.date);
+                    if let Some(size) = &v.size {
+                        line.push_str(&format!("\t({size})"));
+                    }
+                    if let Some(comment) = &v.comment {
+                        line.push_str(&format!("\t{comment}"));
+                    }
+                    println!("{line}");
+                }
+            }
+            cli::ArxivCommand::Search {
+                query,
+                categories,
+                since,
+                until,
+                max,
+            } => {
+                let filters = arxiv::SearchFilters {
+                    categories,
+                    since,
+                    until,
+                };
+                print_entries(
+                    &arxiv::ArxivQuery::search(query.as_deref().unwrap_or(""), &filters)?
+                        .fetch(Some(max))?,
+                );
+            }
+        },
     }
     Ok(())
 }
 
+fn print_entries(entries: &[Entry]) {
+    for entry in entries {
+        let s = entry.to_biblatex_string();
+        print!("{s}");
+        if !s.ends_with('\n') {
+            println!();
+        }
+    }
+}
+
+/// Ask on the terminal how many of a source's `total` items (described as `count`) to keep;
+/// Enter keeps `cap`.
+fn ask_how_many(src: &str, count: &str, total: usize, cap: usize) -> usize {
+    loop {
+        eprint!("{src} lists {count} items. How many to keep? [number, all, Enter for {cap}] ");
+        let _ = std::io::stderr().flush();
+        let mut line = String::new();
+        match std::io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => return cap,
+            Ok(_) => {
+                if let Some(n) = parse_choice(&line, total, cap) {
+                    return n;
+                }
+            }
+        }
+    }
+}
+
+/// Interpret an answer to [`ask_how_many`]: a count, `all`, or nothing for the default.
+fn parse_choice(input: &str, total: usize, cap: usize) -> Option<usize> {
+    match input.trim().to_ascii_lowercase().as_str() {
+        "" => Some(cap.min(total)),
+        "a" | "all" => Some(total),
+        n => n.parse::<usize>().ok().map(|n| n.min(total)),
+    }
+}
+
+/// How a source is shown in progress and error messages.
+fn label(src: &Source) -> String {
+    match src {
+        Source::Identifier(id) => id.clone(),
+        Source::File(path) | Source::Project(path) | Source::Pdf(path) => {
+            path.display().to_string()
+        }
+    }
+}
+
+/// The PDF files directly inside `dir`, in name order.
+fn pdfs_in(dir: &std::path::Path) -> Vec<Source> {
+    let mut pdfs: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| pdf::is_pdf(p))
+                .collect()
+        })
+        .unwrap_or_default();
+    pdfs.sort();
+    pdfs.into_iter().map(Source::Pdf).collect()
+}
+
 fn format_duration(d: Duration) -> String {
     if d.as_secs() >= 60 {
         let m = d.as_secs() / 60;
@@ -207,6 +578,15 @@ mod tests {
         })
     }
 
+    #[test]
+    fn parse_choice_answers() {
+        assert_eq!(parse_choice("\n", 120, 50), Some(50));
+        assert_eq!(parse_choice("All\n", 120, 50), Some(120));
+        assert_eq!(parse_choice(" 10 ", 120, 50), Some(10));
+        assert_eq!(parse_choice("500", 120, 50), Some(120));
+        assert_eq!(parse_choice("some", 120, 50), None);
+    }
+
     #[test]
     fn boundary_checks() {
         let d59 = Duration::from_millis(59_999);
@@ -0,0 +1,546 @@
+//! PDF files, cited through the identifiers and metadata they carry.
+
+use std::path::Path;
+
+use anyhow::Context;
+use biblatex::{ChunksExt, Entry};
+use lopdf::{Dictionary, Document, decode_text_string};
+use once_cell::sync::Lazy;
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use regex::Regex;
+
+use crate::bibtex::{citation_key, escape_braces, normalize_ws, parse_single, write_entry};
+use crate::import::refstring::{self, ARXIV_RE, DOI_RE, YEAR_RE};
+use crate::{resolver, search};
+
+/// How many pages are searched for identifiers; DOIs further in are mostly references.
+const PAGES_SCANNED: u32 = 2;
+
+// The stamp arXiv puts in the margin of its PDFs: "arXiv:1810.04805v2 [cs.CL] 24 May 2019".
+static ARXIV_STAMP_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"arXiv:(\d{4}\.\d{4,5}(?:v\d+)?|[a-z-]+(?:\.[A-Z]{2})?/\d{7}(?:v\d+)?)\s*\[[A-Za-z.-]+\]",
+    )
+    .unwrap()
+});
+
+/// What a PDF says about itself.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PdfMeta {
+    pub title: Option<String>,
+    pub authors: Vec<String>,
+    pub year: Option<String>,
+    /// A DOI from the document metadata, or else from the first pages.
+    pub doi: Option<String>,
+    pub arxiv: Option<String>,
+    /// Whether the arXiv identifier comes from the metadata or arXiv's own stamp, and so
+    /// names this document rather than one it cites.
+    arxiv_is_own: bool,
+    /// Whether the DOI comes from the metadata rather than the text.
+    doi_is_own: bool,
+}
+
+impl PdfMeta {
+    /// The identifier to resolve the document by: a DOI or arXiv identifier from its metadata
+    /// first, then arXiv's stamp, then a DOI or arXiv identifier found in its first pages.
+    pub fn identifier(&self) -> Option<String> {
+        let doi = self.doi.clone();
+        let arxiv = self.arxiv.as_ref().map(|id| format!("arXiv:{id}"));
+        match (self.doi_is_own, self.arxiv_is_own) {
+            (true, _) => doi,
+            (false, true) => arxiv,
+            _ => doi.or(arxiv),
+        }
+    }
+
+    /// Whether [`PdfMeta::identifier`] comes from the metadata or arXiv's stamp, rather than
+    /// from the text.
+    pub fn identifier_is_own(&self) -> bool {
+        self.doi_is_own || self.arxiv_is_own
+    }
+
+    /// Whether `entry`, resolved from [`PdfMeta::identifier`], is this document. An identifier
+    /// from the text may belong to a work the document cites, so if the document has a title,
+    /// the entry's must match it.
+    pub fn describes(&self, entry: &Entry) -> bool {
+        if self.identifier_is_own() {
+            return true;
+        }
+        let Some(title) = &self.title else {
+            return true;
+        };
+        entry.get("title").is_some_and(|t| {
+            search::title_similarity(title, &t.format_verbatim()) >= search::ACCEPT
+        })
+    }
+
+    /// A best-effort `@misc` entry from the document's title and authors, for documents without
+    /// an identifier. `untitled` stands in for a missing title (e.g., the file name), and `link`
+    /// is a field pointing at the document, such as `("url", …)` or `("file", …)`.
+    pub fn to_entry(&self, untitled: &str, link: (&str, &str)) -> anyhow::Result<Entry> {
+        let title = self.title.clone().unwrap_or_else(|| untitled.to_string());
+        let key = citation_key(&self.authors, self.year.as_deref(), Some(&title));
+
+        let mut fields: Vec<(&str, String)> = Vec::new();
+        if !self.authors.is_empty() {
+            let authors: Vec<String> = self.authors.iter().map(|a| escape_braces(a)).collect();
+            fields.push(("author", authors.join(" and ")));
+        }
+        fields.push(("title", escape_braces(&title)));
+        if let Some(year) = &self.year {
+            fields.push(("year", escape_braces(year)));
+        }
+        fields.push((link.0, escape_braces(link.1)));
+
+        parse_single(&write_entry("misc", &key, fields))
+            .context("failed to build an entry from PDF metadata")
+    }
+}
+
+/// Whether `path` is a PDF file, going by its extension.
+pub fn is_pdf(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("pdf"))
+}
+
+/// Cite a PDF file: through the DOI or arXiv identifier it carries when there is one, and
+/// otherwise as a `@misc` from its title and authors.
+pub fn resolve(path: &Path) -> anyhow::Result<Entry> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let meta = inspect(&bytes).with_context(|| format!("failed to read {}", path.display()))?;
+    if let Some(id) = meta.identifier() {
+        match resolver::resolve(&id) {
+            Ok(entry) if meta.describes(&entry) => return Ok(entry),
+            Err(e) if meta.identifier_is_own() => {
+                return Err(e).with_context(|| {
+                    format!("failed to resolve {id}, found in {}", path.display())
+                });
+            }
+            // One found in the text may be mangled, or belong to a work the document cites.
+            _ => {}
+        }
+    }
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().replace(['_', '-'], " "))
+        .unwrap_or_default();
+    meta.to_entry(&stem, ("file", &path.display().to_string()))
+}
+
+/// Read a PDF's metadata: its Info dictionary and XMP packet, and DOIs and arXiv identifiers
+/// in the text of its first pages.
+///
+/// PDFs that cannot be parsed (e.g., encrypted ones) still have their XMP packet searched for,
+/// since it is usually stored uncompressed.
+pub fn inspect(bytes: &[u8]) -> anyhow::Result<PdfMeta> {
+    let doc = Document::load_mem(bytes);
+    let xmp = doc
+        .as_ref()
+        .ok()
+        .and_then(catalog_xmp)
+        .or_else(|| raw_xmp(bytes))
+        .map(|x| parse_xmp(&x))
+        .unwrap_or_default();
+    let doc = match doc {
+        Ok(doc) => doc,
+        Err(e) if xmp == PdfMeta::default() => {
+            return Err(anyhow::anyhow!("not a readable PDF: {e}"));
+        }
+        Err(_) => return Ok(xmp),
+    };
+
+    let mut meta = xmp;
+    let info = info_dict(&doc);
+    let info_str = |key: &[u8]| {
+        info.and_then(|d| d.get(key).ok())
+            .and_then(|o| decode_text_string(o).ok())
+            .map(|s| normalize_ws(&s))
+            .filter(|s| !s.is_empty())
+    };
+    if meta.title.is_none() {
+        meta.title = info_str(b"Title").filter(|t| !is_placeholder_title(t));
+    }
+    if meta.authors.is_empty()
+        && let Some(author) = info_str(b"Author")
+    {
+        meta.authors = if author.contains(';') {
+            author
+                .split(';')
+                .map(|a| a.trim().to_string())
+                .filter(|a| !a.is_empty())
+                .collect()
+        } else {
+            refstring::split_authors(&author).unwrap_or_else(|| vec![author])
+        };
+    }
+    if meta.year.is_none() {
+        meta.year = info_str(b"CreationDate").and_then(|d| year_of(&d));
+    }
+    // Some publishers put the DOI in a custom Info key, or in the subject line
+    // ("Nature 586, 1–2 (2020). doi:10.1038/…").
+    if meta.doi.is_none()
+        && let Some(doi) = [&b"doi"[..], b"DOI", b"Subject", b"Keywords"]
+            .into_iter()
+            .filter_map(info_str)
+            .find_map(|s| find_doi(&s))
+    {
+        meta.doi = Some(doi);
+        meta.doi_is_own = true;
+    }
+
+    for page in 1..=PAGES_SCANNED.min(doc.get_pages().len() as u32) {
+        let Ok(text) = doc.extract_text(&[page]) else {
+            continue;
+        };
+        if !meta.arxiv_is_own
+            && let Some(c) = ARXIV_STAMP_RE.captures(&text)
+        {
+            meta.arxiv = Some(c[1].to_string());
+            meta.arxiv_is_own = true;
+        }
+        if meta.doi.is_none() {
+            meta.doi = find_doi(&text);
+        }
+        if meta.arxiv.is_none() {
+            meta.arxiv = ARXIV_RE.captures(&text).map(|c| c[1].to_string());
+        }
+    }
+    Ok(meta)
+}
+
+fn info_dict(doc: &Document) -> Option<&Dictionary> {
+    let info = doc.trailer.get(b"Info").ok()?;
+    doc.dereference(info).ok()?.1.as_dict().ok()
+}
+
+/// The XMP packet the document catalog points to.
+fn catalog_xmp(doc: &Document) -> Option<String> {
+    let metadata = doc.catalog().ok()?.get(b"Metadata").ok()?;
+    let stream = doc.dereference(metadata).ok()?.1.as_stream().ok()?;
+    let content = stream
+        .decompressed_content()
+        .unwrap_or_else(|_| stream.content.clone());
+    Some(String::from_utf8_lossy(&content).into_owned())
+}
+
+/// The first XMP packet found in the raw bytes of a file.
+fn raw_xmp(bytes: &[u8]) -> Option<String> {
+    let find = |needle: &[u8], from: usize| {
+        bytes[from..]
+            .windows(needle.len())
+            .position(|w| w == needle)
+            .map(|i| i + from)
+    };
+    let start = find(b"<x:xmpmeta", 0)?;
+    let end = find(b"</x:xmpmeta>", start)? + b"</x:xmpmeta>".len();
+    Some(String::from_utf8_lossy(&bytes[start..end]).into_owned())
+}
+
+/// Read the Dublin Core title, creators and date, and the DOI that PRISM, CrossMark or the
+/// `pdfx` schema record, from an XMP packet.
+fn parse_xmp(xml: &str) -> PdfMeta {
+    let mut meta = PdfMeta::default();
+    let mut dates: Vec<(u8, String)> = Vec::new();
+    let mut path: Vec<String> = Vec::new();
+    let mut text = String::new();
+
+    let mut reader = Reader::from_str(xml);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Eof) | Err(_) => break,
+            Ok(Event::Start(e)) => {
+                read_attrs(&e, &mut meta, &mut dates);
+                path.push(String::from_utf8_lossy(e.local_name().as_ref()).to_lowercase());
+                text.clear();
+            }
+            Ok(Event::Empty(e)) => read_attrs(&e, &mut meta, &mut dates),
+            Ok(Event::Text(t)) => text.push_str(&String::from_utf8_lossy(t.as_ref())),
+            Ok(Event::CData(t)) => text.push_str(&String::from_utf8_lossy(t.as_ref())),
+            Ok(Event::GeneralRef(r)) => {
+                // quick-xml reports entity and character references (`&amp;`, `&#x3b1;`)
+                // separately from text.
+                match r.resolve_char_ref() {
+                    Ok(Some(c)) => text.push(c),
+                    _ => {
+                        let raw = format!("&{};", String::from_utf8_lossy(r.as_ref()));
+                        match quick_xml::escape::unescape(&raw) {
+                            Ok(s) => text.push_str(&s),
+                            Err(_) => text.push_str(&raw),
+                        }
+                    }
+                }
+            }
+            Ok(Event::End(_)) => {
+                let value = normalize_ws(&text);
+                if !value.is_empty() {
+                    read_property(&path, &value, &mut meta, &mut dates);
+                }
+                path.pop();
+                text.clear();
+            }
+            _ => {}
+        }
+    }
+
+    dates.sort_by_key(|(rank, _)| *rank);
+    meta.year = dates.iter().find_map(|(_, d)| year_of(d));
+    meta
+}
+
+/// Take in the properties written as attributes, as `rdf:Description` may have them.
+fn read_attrs(e: &BytesStart<'_>, meta: &mut PdfMeta, dates: &mut Vec<(u8, String)>) {
+    for attr in e.attributes().flatten() {
+        let name = String::from_utf8_lossy(attr.key.local_name().as_ref()).to_lowercase();
+        let value = match attr.unescape_value() {
+            Ok(v) => v.into_owned(),
+            Err(_) => String::from_utf8_lossy(&attr.value).into_owned(),
+        };
+        read_property(&[name], &value, meta, dates);
+    }
+}
+
+/// Take in one XMP property, given the local names of the elements leading to its value.
+fn read_property(path: &[String], value: &str, meta: &mut PdfMeta, dates: &mut Vec<(u8, String)>) {
+    let within = |name: &str| path.iter().any(|p| p == name);
+    let leaf = path.last().map(String::as_str).unwrap_or_default();
+    if within("title") && meta.title.is_none() && !is_placeholder_title(value) {
+        meta.title = Some(value.to_string());
+    } else if within("creator") && leaf == "li" {
+        meta.authors.push(value.to_string());
+    } else if leaf == "doi" || within("identifier") {
+        if meta.doi.is_none()
+            && let Some(doi) = find_doi(value)
+        {
+            meta.doi = Some(doi);
+            meta.doi_is_own = true;
+        } else if meta.arxiv.is_none()
+            && let Some(c) = ARXIV_RE.captures(value)
+        {
+            meta.arxiv = Some(c[1].to_string());
+            meta.arxiv_is_own = true;
+        }
+    } else {
+        // Publication dates before the date the file was made.
+        let rank = match leaf {
+            "coverdate" | "publicationdate" => 0,
+            _ if within("date") => 1,
+            "createdate" => 2,
+            _ => return,
+        };
+        dates.push((rank, value.to_string()));
+    }
+}
+
+fn find_doi(s: &str) -> Option<String> {
+    let doi = refstring::trim_punct(DOI_RE.find(s)?.as_str());
+    // A closing parenthesis belongs to the DOI only when it opened one, as in
+    // "10.1016/0001-8708(79)90025-3".
+    let doi = if doi.ends_with(')') && doi.matches('(').count() < doi.matches(')').count() {
+        &doi[..doi.len() - 1]
+    } else {
+        doi
+    };
+    Some(doi.to_string())
+}
+
+fn year_of(date: &str) -> Option<String> {
+    // PDF dates look like "D:20200131120000Z", which a word boundary would not split.
+    let date = date.strip_prefix("D:").unwrap_or(date);
+    YEAR_RE
+        .captures(date)
+        .map(|c| c[1].to_string())
+        .or_else(|| {
+            date.get(..4)
+                .filter(|y| YEAR_RE.is_match(y))
+                .map(str::to_string)
+        })
+}
+
+/// Titles that name the file or the program that made it rather than the work.
+fn is_placeholder_title(title: &str) -> bool {
+    let lower = title.to_lowercase();
+    lower == "untitled"
+        || lower.starts_with("microsoft word - ")
+        || [".pdf", ".dvi", ".doc", ".docx", ".tex", ".ps"]
+            .iter()
+            .any(|ext| lower.ends_with(ext))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use biblatex::Bibliography;
+    use lopdf::{Object, Stream, dictionary};
+
+    /// A one-page PDF with `text` on its page and the given Info dictionary.
+    fn pdf(text: &str, info: Dictionary) -> Vec<u8> {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "Encoding" => "WinAnsiEncoding",
+        });
+        let content = format!("BT /F1 10 Tf 50 700 Td ({text}) Tj ET");
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content.into_bytes()));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+            "Resources" => dictionary! { "Font" => dictionary! { "F1" => font_id } },
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_id.into()],
+                "Count" => 1,
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+        let info_id = doc.add_object(info);
+        doc.trailer.set("Info", info_id);
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn inspect_reads_info_and_text() {
+        let bytes = pdf(
+            "Published in SOSP 2017. https://doi.org/10.1145/3132747.3132763.",
+            dictionary! {
+                "Title" => Object::string_literal("Microsoft Word - draft.docx"),
+                "Author" => Object::string_literal("Jane Smith; John Jones"),
+                "CreationDate" => Object::string_literal("D:20170901120000Z"),
+            },
+        );
+        let meta = inspect(&bytes).unwrap();
+        assert_eq!(meta.title, None);
+        assert_eq!(meta.authors, vec!["Jane Smith", "John Jones"]);
+        assert_eq!(meta.year.as_deref(), Some("2017"));
+        assert_eq!(
+            meta.identifier().as_deref(),
+            Some("10.1145/3132747.3132763")
+        );
+    }
+
+    #[test]
+    fn arxiv_stamp_wins_over_cited_dois() {
+        let bytes = pdf(
+            "arXiv:1810.04805v2 [cs.CL] 24 May 2019 see doi:10.18653/v1/N18-1202",
+            dictionary! {},
+        );
+        let meta = inspect(&bytes).unwrap();
+        assert_eq!(meta.identifier().as_deref(), Some("arXiv:1810.04805v2"));
+    }
+
+    #[test]
+    fn xmp_metadata() {
+        let xmp = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF
+            xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+          <rdf:Description xmlns:dc="http://purl.org/dc/elements/1.1/"
+              xmlns:prism="http://prismstandard.org/namespaces/basic/2.0/"
+              xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+              prism:doi="10.1038/s41586-020-2649-2" xmp:CreateDate="2020-09-10T11:00:00Z">
+            <dc:title><rdf:Alt><rdf:li xml:lang="x-default">Array programming
+              with NumPy</rdf:li></rdf:Alt></dc:title>
+            <dc:creator><rdf:Seq><rdf:li>Charles R. Harris</rdf:li>
+              <rdf:li>K. Jarrod Millman</rdf:li></rdf:Seq></dc:creator>
+            <prism:coverDate>2020-09-16</prism:coverDate>
+          </rdf:Description></rdf:RDF></x:xmpmeta>"#;
+        let bytes = format!("%PDF-1.7\n% not a parsable body\n{xmp}\n%%EOF");
+        let meta = inspect(bytes.as_bytes()).unwrap();
+        assert_eq!(meta.title.as_deref(), Some("Array programming with NumPy"));
+        assert_eq!(meta.authors, vec!["Charles R. Harris", "K. Jarrod Millman"]);
+        assert_eq!(meta.year.as_deref(), Some("2020"));
+        assert_eq!(
+            meta.identifier().as_deref(),
+            Some("10.1038/s41586-020-2649-2")
+        );
+        assert!(inspect(b"%PDF-1.7\nnothing here").is_err());
+    }
+
+    #[test]
+    fn xmp_references_are_decoded() {
+        let xmp = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF
+            xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+          <rdf:Description xmlns:dc="http://purl.org/dc/elements/1.1/"
+              xmlns:prism="http://prismstandard.org/namespaces/basic/2.0/"
+              prism:doi="10.1000/a&amp;b">
+            <dc:title><rdf:Alt><rdf:li>Ethics &amp; AI: the &#x3b1; case</rdf:li></rdf:Alt></dc:title>
+          </rdf:Description></rdf:RDF></x:xmpmeta>"#;
+        let meta = parse_xmp(xmp);
+        assert_eq!(meta.title.as_deref(), Some("Ethics & AI: the α case"));
+        assert_eq!(meta.doi.as_deref(), Some("10.1000/a&b"));
+    }
+
+    #[test]
+    fn misc_fallback_without_identifiers() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("a_local_note.pdf");
+        std::fs::write(
+            &path,
+            pdf(
+                "Nothing to resolve here.",
+                dictionary! { "Author" => Object::string_literal("Ada Lovelace") },
+            ),
+        )
+        .unwrap();
+        assert!(is_pdf(&path));
+        let entry = resolve(&path).unwrap();
+        assert_eq!(entry.entry_type, biblatex::EntryType::Misc);
+        let field = |k: &str| entry.get(k).map(|c| c.format_verbatim());
+        assert_eq!(field("title").as_deref(), Some("a local note"));
+        assert_eq!(field("author").as_deref(), Some("Ada Lovelace"));
+        assert_eq!(entry.key, "lovelacelocal");
+    }
+
+    #[test]
+    fn cited_identifiers_must_match_the_title() {
+        let entry = |title: &str| {
+            let bib = format!("@article{{k, title = {{{title}}}}}");
+            Bibliography::parse(&bib)
+                .unwrap()
+                .into_iter()
+                .next()
+                .unwrap()
+        };
+        let meta = PdfMeta {
+            title: Some("Array programming with NumPy".into()),
+            doi: Some("10.1038/s41586-020-2649-2".into()),
+            ..Default::default()
+        };
+        assert!(meta.describes(&entry("Array Programming with {NumPy}")));
+        assert!(!meta.describes(&entry("Deep residual learning for image recognition")));
+        let own = PdfMeta {
+            doi_is_own: true,
+            ..meta.clone()
+        };
+        assert!(own.describes(&entry("Deep residual learning for image recognition")));
+        let untitled = PdfMeta {
+            title: None,
+            ..meta
+        };
+        assert!(untitled.describes(&entry("Deep residual learning for image recognition")));
+    }
+
+    #[test]
+    fn misc_fallback_escapes_authors() {
+        let meta = PdfMeta {
+            title: Some("Notes".into()),
+            authors: vec!["Ada } Lovelace".into()],
+            ..Default::default()
+        };
+        let entry = meta.to_entry("untitled", ("file", "notes.pdf")).unwrap();
+        let author = entry.get("author").map(|c| c.format_verbatim());
+        assert_eq!(author.as_deref(), Some("Ada } Lovelace"));
+    }
+}
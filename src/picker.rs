@@ -0,0 +1,239 @@
+//! Choosing among several candidate items on the terminal.
+
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+
+use biblatex::{ChunksExt, Entry};
+use owo_colors::OwoColorize;
+
+use crate::resolver;
+use crate::search::Candidate;
+
+/// What the picker shows about one item.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Choice {
+    pub title: String,
+    pub authors: Vec<String>,
+    pub year: Option<String>,
+    pub venue: Option<String>,
+    /// Where the item came from (e.g., the listing it was on).
+    pub source: String,
+}
+
+impl Choice {
+    pub fn from_entry(entry: &Entry, source: &str) -> Self {
+        let field = |key: &str| entry.get(key).map(|c| c.format_verbatim());
+        Choice {
+            title: field("title").unwrap_or_else(|| entry.key.clone()),
+            authors: entry
+                .author()
+                .map(|people| people.into_iter().map(|p| p.name).collect())
+                .unwrap_or_default(),
+            year: field("date")
+                .map(|d| d.chars().take(4).collect())
+                .or_else(|| field("year")),
+            venue: [
+                "journaltitle",
+                "journal",
+                "booktitle",
+                "howpublished",
+                "publisher",
+            ]
+            .into_iter()
+            .find_map(field),
+            source: source.to_string(),
+        }
+    }
+
+    pub fn from_candidate(c: &Candidate, source: &str) -> Self {
+        Choice {
+            title: c.title.clone(),
+            authors: c.authors.clone(),
+            year: c.year.clone(),
+            venue: c.venue.clone(),
+            source: source.to_string(),
+        }
+    }
+
+    fn render(&self, n: usize) -> String {
+        let authors = match self.authors.as_slice() {
+            [] => None,
+            [a] => Some(a.clone()),
+            [a, b] => Some(format!("{a} and {b}")),
+            [a, ..] => Some(format!("{a} et al.")),
+        };
+        let about: Vec<String> = [authors, self.year.clone(), self.venue.clone()]
+            .into_iter()
+            .flatten()
+            .collect();
+        format!(
+            "{:>4}  {}\n      {}{}{}",
+            n.to_string().cyan().bold(),
+            self.title.bold(),
+            about.join(" · "),
+            if about.is_empty() { "" } else { " · " },
+            self.source.dimmed()
+        )
+    }
+}
+
+/// Whether a picker can be shown: someone must be able to answer it, and the output it chooses
+/// for must be going to the terminal too (not, say, into a file).
+pub fn available() -> bool {
+    std::io::stdin().is_terminal() && std::io::stdout().is_terminal()
+}
+
+#[derive(Debug, PartialEq)]
+enum Answer {
+    Pick(Vec<usize>),
+    Preview(usize),
+}
+
+/// Interpret an answer to [`pick`]: numbers and ranges (`1 3-5`), `all`, `none`, or `p N` to
+/// preview item N. Indices come back zero-based.
+fn parse_answer(input: &str, total: usize) -> Option<Answer> {
+    let input = input.trim().to_ascii_lowercase();
+    match input.as_str() {
+        "" => return None,
+        "a" | "all" => return Some(Answer::Pick((0..total).collect())),
+        "n" | "none" => return Some(Answer::Pick(Vec::new())),
+        _ => {}
+    }
+    let in_range = |n: usize| (1..=total).contains(&n).then(|| n - 1);
+    if let Some(n) = input
+        .strip_prefix("preview")
+        .or_else(|| input.strip_prefix('p'))
+    {
+        return in_range(n.trim().parse().ok()?).map(Answer::Preview);
+    }
+    let mut picked = Vec::new();
+    for part in input.split([' ', ',']).filter(|p| !p.is_empty()) {
+        let (first, last) = match part.split_once('-') {
+            Some((a, b)) => {
+                let (first, last) = (in_range(a.parse().ok()?)?, in_range(b.parse().ok()?)?);
+                // A reversed range ("5-3") is more likely a typo than a request for nothing.
+                if first > last {
+                    return None;
+                }
+                (first, last)
+            }
+            None => {
+                let n = in_range(part.parse().ok()?)?;
+                (n, n)
+            }
+        };
+        for i in first..=last {
+            if !picked.contains(&i) {
+                picked.push(i);
+            }
+        }
+    }
+    Some(Answer::Pick(picked))
+}
+
+/// List `choices` on the terminal and ask which to keep, showing the BibLaTeX `preview` gives
+/// for an item on request. Returns the chosen indices, in the order given.
+pub fn pick(
+    header: &str,
+    choices: &[Choice],
+    preview: &mut dyn FnMut(usize) -> anyhow::Result<String>,
+) -> Vec<usize> {
+    eprintln!("{}", header.bold());
+    for (i, choice) in choices.iter().enumerate() {
+        eprintln!("{}", choice.render(i + 1));
+    }
+    loop {
+        eprint!(
+            "{} ",
+            "Keep which? [e.g. 1 3-5, all, none; p N to preview]".cyan()
+        );
+        let _ = std::io::stderr().flush();
+        let mut line = String::new();
+        match std::io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => return Vec::new(),
+            Ok(_) => {}
+        }
+        match parse_answer(&line, choices.len()) {
+            Some(Answer::Pick(picked)) => return picked,
+            Some(Answer::Preview(i)) => match preview(i) {
+                Ok(bib) => {
+                    for l in bib.lines() {
+                        eprintln!("      {}", l.dimmed());
+                    }
+                }
+                Err(e) => eprintln!("{} {}", "✗".red().bold(), e.red()),
+            },
+            None if line.trim().is_empty() => {}
+            None => eprintln!("{} not understood: {}", "!".yellow().bold(), line.trim()),
+        }
+    }
+}
+
+/// Pick among search candidates, resolving the chosen ones (and previewed ones) through their
+/// DOIs.
+pub fn pick_candidates(
+    header: &str,
+    ranked: &[(f32, Candidate)],
+    source: &str,
+) -> anyhow::Result<Vec<Entry>> {
+    let choices: Vec<Choice> = ranked
+        .iter()
+        .map(|(score, c)| Choice::from_candidate(c, &format!("{source}, score {score:.2}")))
+        .collect();
+    let mut resolved: HashMap<usize, Entry> = HashMap::new();
+    let mut resolve = |i: usize| -> anyhow::Result<Entry> {
+        if let Some(entry) = resolved.get(&i) {
+            return Ok(entry.clone());
+        }
+        let entry = resolver::resolve(&ranked[i].1.doi)?;
+        resolved.insert(i, entry.clone());
+        Ok(entry)
+    };
+    let picked = pick(header, &choices, &mut |i| {
+        resolve(i).map(|e| e.to_biblatex_string())
+    });
+    picked.into_iter().map(resolve).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use biblatex::Bibliography;
+
+    #[test]
+    fn parse_answers() {
+        assert_eq!(parse_answer("all\n", 3), Some(Answer::Pick(vec![0, 1, 2])));
+        assert_eq!(parse_answer("None", 3), Some(Answer::Pick(vec![])));
+        assert_eq!(
+            parse_answer("3 1-2, 2", 5),
+            Some(Answer::Pick(vec![2, 0, 1]))
+        );
+        assert_eq!(parse_answer("p 2", 3), Some(Answer::Preview(1)));
+        assert_eq!(parse_answer("preview 3", 3), Some(Answer::Preview(2)));
+        assert_eq!(parse_answer("", 3), None);
+        assert_eq!(parse_answer("4", 3), None);
+        assert_eq!(parse_answer("p 0", 3), None);
+        assert_eq!(parse_answer("first", 3), None);
+        assert_eq!(parse_answer("5-3", 5), None);
+    }
+
+    #[test]
+    fn choice_from_entry() {
+        let bib = Bibliography::parse(
+            "@inproceedings{k, title = {A Paper}, author = {Smith, Jane and Jones, John},
+             date = {2020-11-04}, booktitle = {OSDI}}",
+        )
+        .unwrap();
+        let choice = Choice::from_entry(bib.iter().next().unwrap(), "DBLP:x");
+        assert_eq!(
+            choice,
+            Choice {
+                title: "A Paper".to_string(),
+                authors: vec!["Smith".to_string(), "Jones".to_string()],
+                year: Some("2020".to_string()),
+                venue: Some("OSDI".to_string()),
+                source: "DBLP:x".to_string(),
+            }
+        );
+    }
+}
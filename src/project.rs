@@ -0,0 +1,493 @@
+//! Local software projects, cited offline from their metadata files.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use biblatex::Entry;
+use serde_json::Value;
+
+use crate::bibtex::{escape_braces, normalize_ws, person_name};
+use crate::cff::{SoftwareMeta, cff_title, entry_from_cff, repository_url, software_entry};
+
+/// Metadata files we know how to read, most authoritative first.
+pub const METADATA_FILES: &[&str] = &[
+    "CITATION.cff",
+    "codemeta.json",
+    "Cargo.toml",
+    "pyproject.toml",
+    "package.json",
+];
+
+/// Whether `path` is a metadata file or a directory holding at least one that describes it.
+pub fn is_project(path: &Path) -> bool {
+    if path.is_dir() {
+        METADATA_FILES.iter().map(|f| path.join(f)).any(|file| {
+            file.is_file()
+                && (file.ends_with("CITATION.cff")
+                    || std::fs::read_to_string(&file)
+                        .is_ok_and(|text| parse_manifest(&file, &text).is_ok()))
+        })
+    } else {
+        path.file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| METADATA_FILES.contains(&n))
+    }
+}
+
+/// Build an `@software` entry for a project directory or a single metadata file.
+///
+/// In a directory every known file is read and merged, so that e.g. a `CITATION.cff` without
+/// a version still picks one up from `Cargo.toml`. Manifests that describe no package there,
+/// such as a virtual workspace's `Cargo.toml`, are skipped.
+pub fn resolve(path: &Path) -> anyhow::Result<Entry> {
+    let files: Vec<PathBuf> = if path.is_dir() {
+        METADATA_FILES
+            .iter()
+            .map(|f| path.join(f))
+            .filter(|p| p.is_file())
+            .collect()
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    let mut cff = None;
+    let mut meta = SoftwareMeta::default();
+    let mut parsed_any = false;
+    let mut first_error = None;
+    for file in &files {
+        let text = std::fs::read_to_string(file)
+            .with_context(|| format!("failed to read {}", file.display()))?;
+        if file.ends_with("CITATION.cff") {
+            cff = Some(text);
+            continue;
+        }
+        match parse_manifest(file, &text)
+            .with_context(|| format!("failed to parse {}", file.display()))
+        {
+            Ok(found) => {
+                meta = meta.or(found);
+                parsed_any = true;
+            }
+            Err(e) if path.is_dir() => {
+                first_error.get_or_insert(e);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    if cff.is_none()
+        && !parsed_any
+        && let Some(e) = first_error
+    {
+        return Err(e);
+    }
+
+    // A metadata file given on its own is named after the directory holding it.
+    let dir = match path.parent() {
+        _ if path.is_dir() => path,
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let name = cff
+        .as_deref()
+        .and_then(cff_title)
+        .or_else(|| meta.title.clone())
+        .or_else(|| {
+            let dir = dir.canonicalize().ok()?;
+            dir.file_name().and_then(|n| n.to_str()).map(str::to_string)
+        });
+    let key = name
+        .map(|n| citation_key(&n))
+        .filter(|k| !k.is_empty())
+        .unwrap_or_else(|| "software".into());
+    match cff {
+        Some(cff) => entry_from_cff(&cff, meta, &key),
+        None => software_entry(&meta, &key),
+    }
+}
+
+/// Turn a project name into a BibLaTeX key: lowercase alphanumerics and `-_:.`, with any run
+/// of other characters (e.g., spaces) collapsed into one hyphen.
+fn citation_key(name: &str) -> String {
+    let mut key = String::with_capacity(name.len());
+    for ch in name.chars().flat_map(char::to_lowercase) {
+        if ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | ':' | '.') {
+            key.push(ch);
+        } else if !key.is_empty() && !key.ends_with('-') {
+            key.push('-');
+        }
+    }
+    key.trim_end_matches('-').to_string()
+}
+
+fn parse_manifest(file: &Path, text: &str) -> anyhow::Result<SoftwareMeta> {
+    match file.file_name().and_then(|n| n.to_str()).unwrap_or("") {
+        "codemeta.json" => codemeta(text),
+        "Cargo.toml" => cargo_toml(text),
+        "pyproject.toml" => pyproject_toml(text),
+        "package.json" => package_json(text),
+        _ => Err(anyhow::anyhow!(
+            "unsupported project file {}",
+            file.display()
+        )),
+    }
+}
+
+fn nonempty(s: Option<&str>) -> Option<String> {
+    s.map(normalize_ws).filter(|s| !s.is_empty())
+}
+
+fn cargo_toml(text: &str) -> anyhow::Result<SoftwareMeta> {
+    let doc: toml::Table = text.parse()?;
+    let package = doc
+        .get("package")
+        .and_then(|p| p.as_table())
+        .ok_or_else(|| anyhow::anyhow!("no [package] table"))?;
+    // `version.workspace = true` and friends inherit from [workspace.package] of the same file.
+    let inherited = doc
+        .get("workspace")
+        .and_then(|w| w.get("package"))
+        .and_then(|p| p.as_table());
+    let get = |key: &str| {
+        package.get(key).and_then(|v| match v {
+            toml::Value::Table(t) if t.get("workspace").and_then(|w| w.as_bool()) == Some(true) => {
+                inherited.and_then(|i| i.get(key))
+            }
+            v => Some(v),
+        })
+    };
+    let get_str = |key: &str| nonempty(get(key).and_then(|v| v.as_str()));
+    let get_list = |key: &str| -> Vec<String> {
+        get(key)
+            .and_then(|v| v.as_array())
+            .map(|a| {
+                a.iter()
+                    .filter_map(|x| x.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let repository = get_str("repository").map(|r| repository_url(&r));
+    Ok(SoftwareMeta {
+        title: get_str("name"),
+        authors: get_list("authors")
+            .iter()
+            .filter_map(|a| person_name(a))
+            .collect(),
+        version: get_str("version"),
+        url: get_str("homepage").or_else(|| repository.clone()),
+        repository,
+        license: get_str("license"),
+        abstract_: get_str("description"),
+        keywords: get_list("keywords"),
+        ..Default::default()
+    })
+}
+
+fn pyproject_toml(text: &str) -> anyhow::Result<SoftwareMeta> {
+    let doc: toml::Table = text.parse()?;
+    let str_of = |t: Option<&toml::Value>, key: &str| {
+        nonempty(t.and_then(|t| t.get(key)).and_then(|v| v.as_str()))
+    };
+    let list_of = |t: Option<&toml::Value>, key: &str| -> Vec<toml::Value> {
+        t.and_then(|t| t.get(key))
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    // PEP 621 [project] first, then Poetry's own table.
+    let project = doc.get("project");
+    let poetry = doc.get("tool").and_then(|t| t.get("poetry"));
+    let mut metas = Vec::new();
+    for (table, is_pep621) in [(project, true), (poetry, false)] {
+        if table.is_none() {
+            continue;
+        }
+        let authors = list_of(table, "authors")
+            .iter()
+            .filter_map(|a| match a {
+                toml::Value::String(s) => person_name(s),
+                toml::Value::Table(t) => person_name(t.get("name")?.as_str()?),
+                _ => None,
+            })
+            .collect();
+        let license = match table.and_then(|t| t.get("license")) {
+            Some(toml::Value::String(s)) => nonempty(Some(s)),
+            Some(toml::Value::Table(t)) => nonempty(t.get("text").and_then(|v| v.as_str())),
+            _ => None,
+        };
+        let urls = if is_pep621 {
+            table.and_then(|t| t.get("urls"))
+        } else {
+            table
+        };
+        let repository = ["repository", "Repository", "Source", "source", "Code"]
+            .iter()
+            .find_map(|k| str_of(urls, k))
+            .map(|r| repository_url(&r));
+        let homepage = ["homepage", "Homepage", "Documentation"]
+            .iter()
+            .find_map(|k| str_of(urls, k));
+        metas.push(SoftwareMeta {
+            title: str_of(table, "name"),
+            authors,
+            version: str_of(table, "version"),
+            url: homepage.or_else(|| repository.clone()),
+            repository,
+            license,
+            abstract_: str_of(table, "description"),
+            keywords: list_of(table, "keywords")
+                .iter()
+                .filter_map(|k| k.as_str().map(String::from))
+                .collect(),
+            ..Default::default()
+        });
+    }
+    metas
+        .into_iter()
+        .reduce(SoftwareMeta::or)
+        .ok_or_else(|| anyhow::anyhow!("no [project] or [tool.poetry] table"))
+}
+
+fn json_str(v: &Value, key: &str) -> Option<String> {
+    nonempty(v.get(key).and_then(Value::as_str))
+}
+
+fn package_json(text: &str) -> anyhow::Result<SoftwareMeta> {
+    let v: Value = serde_json::from_str(text)?;
+    let person = |p: &Value| match p {
+        Value::String(s) => person_name(s),
+        Value::Object(_) => json_str(p, "name").map(|n| escape_braces(&n)),
+        _ => None,
+    };
+    let mut authors: Vec<String> = v.get("author").and_then(person).into_iter().collect();
+    if let Some(cs) = v.get("contributors").and_then(Value::as_array) {
+        authors.extend(cs.iter().filter_map(person));
+    }
+    let repository = match v.get("repository") {
+        Some(Value::String(s)) => Some(repository_url(s)),
+        Some(r @ Value::Object(_)) => json_str(r, "url").map(|u| repository_url(&u)),
+        _ => None,
+    };
+    let license = match v.get("license") {
+        Some(Value::String(s)) => nonempty(Some(s)),
+        Some(l @ Value::Object(_)) => json_str(l, "type"),
+        _ => None,
+    };
+    Ok(SoftwareMeta {
+        title: json_str(&v, "name"),
+        authors,
+        version: json_str(&v, "version"),
+        url: json_str(&v, "homepage").or_else(|| repository.clone()),
+        repository,
+        license,
+        abstract_: json_str(&v, "description"),
+        keywords: v
+            .get("keywords")
+            .and_then(Value::as_array)
+            .map(|k| {
+                k.iter()
+                    .filter_map(|x| x.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        ..Default::default()
+    })
+}
+
+fn codemeta(text: &str) -> anyhow::Result<SoftwareMeta> {
+    let v: Value = serde_json::from_str(text)?;
+    let one_or_many = |key: &str| -> Vec<Value> {
+        match v.get(key) {
+            Some(Value::Array(a)) => a.clone(),
+            Some(x) => vec![x.clone()],
+            None => Vec::new(),
+        }
+    };
+    let authors = one_or_many("author")
+        .iter()
+        .filter_map(|a| {
+            match (json_str(a, "familyName"), json_str(a, "givenName")) {
+                (Some(f), Some(g)) => Some(format!("{}, {}", escape_braces(&f), escape_braces(&g))),
+                (Some(f), None) => Some(escape_braces(&f)),
+                // Organisations (and persons given only a full name) carry just `name`.
+                _ => json_str(a, "name").map(|n| format!("{{{}}}", escape_braces(&n))),
+            }
+        })
+        .collect();
+    // Licenses are usually SPDX URLs, e.g. https://spdx.org/licenses/MIT.
+    let license = one_or_many("license")
+        .iter()
+        .filter_map(|l| l.as_str())
+        .map(|l| {
+            l.trim_end_matches('/')
+                .rsplit('/')
+                .next()
+                .unwrap_or(l)
+                .to_string()
+        })
+        .reduce(|a, b| format!("{a} OR {b}"));
+    let keywords = match v.get("keywords") {
+        Some(Value::String(s)) => s.split(',').map(normalize_ws).collect(),
+        Some(Value::Array(a)) => a
+            .iter()
+            .filter_map(|x| x.as_str().map(String::from))
+            .collect(),
+        _ => Vec::new(),
+    };
+    let doi = one_or_many("identifier")
+        .iter()
+        .filter_map(|i| i.as_str())
+        .find_map(|i| {
+            i.strip_prefix("https://doi.org/")
+                .or_else(|| i.strip_prefix("doi:"))
+                .map(String::from)
+        });
+    let repository = json_str(&v, "codeRepository").map(|r| repository_url(&r));
+    Ok(SoftwareMeta {
+        title: json_str(&v, "name"),
+        authors,
+        version: json_str(&v, "version").or_else(|| json_str(&v, "softwareVersion")),
+        date: json_str(&v, "datePublished").or_else(|| json_str(&v, "dateModified")),
+        url: json_str(&v, "url").or_else(|| repository.clone()),
+        repository,
+        license,
+        abstract_: json_str(&v, "description"),
+        keywords,
+        doi,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cargo_toml_with_workspace_inheritance() {
+        let text = r#"
+[workspace.package]
+license = "MIT OR Apache-2.0"
+repository = "https://github.com/acme/tool"
+
+[package]
+name = "tool"
+version = "0.3.1"
+authors = ["Jane Doe <jane@example.org>"]
+license.workspace = true
+repository.workspace = true
+description = "Does things"
+"#;
+        let meta = cargo_toml(text).unwrap();
+        assert_eq!(meta.title.as_deref(), Some("tool"));
+        assert_eq!(meta.version.as_deref(), Some("0.3.1"));
+        assert_eq!(meta.authors, vec!["Jane Doe"]);
+        assert_eq!(meta.license.as_deref(), Some("MIT OR Apache-2.0"));
+        assert_eq!(
+            meta.repository.as_deref(),
+            Some("https://github.com/acme/tool")
+        );
+    }
+
+    #[test]
+    fn pyproject_and_package_json() {
+        let py = r#"
+[project]
+name = "tool"
+version = "1.2.0"
+authors = [{ name = "Jane Doe", email = "jane@example.org" }]
+license = { text = "BSD-3-Clause" }
+
+[project.urls]
+Repository = "https://github.com/acme/tool"
+"#;
+        let meta = pyproject_toml(py).unwrap();
+        assert_eq!(meta.authors, vec!["Jane Doe"]);
+        assert_eq!(meta.license.as_deref(), Some("BSD-3-Clause"));
+        assert_eq!(
+            meta.repository.as_deref(),
+            Some("https://github.com/acme/tool")
+        );
+
+        let js = r#"{"name": "tool", "version": "4.0.0", "author": "Jane Doe <jane@example.org>",
+            "license": "ISC", "repository": {"type": "git", "url": "git+https://github.com/acme/tool.git"}}"#;
+        let meta = package_json(js).unwrap();
+        assert_eq!(meta.version.as_deref(), Some("4.0.0"));
+        assert_eq!(meta.authors, vec!["Jane Doe"]);
+        assert_eq!(
+            meta.repository.as_deref(),
+            Some("https://github.com/acme/tool")
+        );
+    }
+
+    #[test]
+    fn directory_merges_cff_with_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("CITATION.cff"),
+            "cff-version: 1.2.0\ntitle: tool\nauthors:\n  - family-names: Doe\n    given-names: Jane\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"tool\"\nversion = \"0.3.1\"\nlicense = \"MIT\"\n",
+        )
+        .unwrap();
+        assert!(is_project(dir.path()));
+        let out = resolve(dir.path()).unwrap().to_biblatex_string();
+        assert!(out.starts_with("@software{tool,"), "{out}");
+        assert!(out.contains("author = {Doe, Jane}"), "{out}");
+        assert!(out.contains("version = {0.3.1}"), "{out}");
+        assert!(out.contains("license = {MIT}"), "{out}");
+    }
+
+    #[test]
+    fn directory_skips_manifests_without_package_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        )
+        .unwrap();
+        assert!(!is_project(dir.path()));
+        assert!(resolve(dir.path()).is_err());
+
+        std::fs::write(
+            dir.path().join("CITATION.cff"),
+            "cff-version: 1.2.0\ntitle: tool\nauthors:\n  - family-names: Doe\n    given-names: Jane\n",
+        )
+        .unwrap();
+        assert!(is_project(dir.path()));
+        let out = resolve(dir.path()).unwrap().to_biblatex_string();
+        assert!(out.starts_with("@software{tool,"), "{out}");
+        assert!(out.contains("title = {tool}"), "{out}");
+        assert!(out.contains("author = {Doe, Jane}"), "{out}");
+    }
+
+    #[test]
+    fn keys_are_slugified_titles() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("codemeta.json"),
+            r#"{"name": "My Great Tool", "version": "1.0"}"#,
+        )
+        .unwrap();
+        let out = resolve(dir.path()).unwrap().to_biblatex_string();
+        assert!(out.starts_with("@software{my-great-tool,"), "{out}");
+        assert!(out.contains("title = {My Great Tool}"), "{out}");
+        assert_eq!(citation_key("Foo (v2) / Bar:baz"), "foo-v2-bar:baz");
+    }
+
+    #[test]
+    fn bare_cff_file_is_keyed_by_its_title() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("CITATION.cff");
+        std::fs::write(
+            &file,
+            "cff-version: 1.2.0\ntitle: Data Kit\nauthors:\n  - name: ACME\n",
+        )
+        .unwrap();
+        let out = resolve(&file).unwrap().to_biblatex_string();
+        assert!(out.starts_with("@software{data-kit,"), "{out}");
+    }
+}
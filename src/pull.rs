@@ -0,0 +1,175 @@
+//! Files behind citation items, such as the source of arXiv papers.
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use flate2::read::GzDecoder;
+
+use crate::identifier::arxiv;
+
+/// Most bytes an e-print may unpack to. Sources are at most a few hundred megabytes, so anything
+/// larger is a gzip bomb rather than a paper.
+const MAX_UNPACKED_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// What `pull --source` left on disk for one paper.
+pub struct SourceFiles {
+    /// Directory the source was unpacked into.
+    pub dir: PathBuf,
+    /// The `.bbl` and `.bib` files found in the source, sorted by path.
+    pub bibliographies: Vec<PathBuf>,
+}
+
+/// Download and unpack the e-print source of an arXiv paper into a directory under `out`
+/// (e.g., `out/arXiv-1810.04805v2/`), and locate the bibliography files it used.
+pub fn pull_arxiv_source(identifier: &str, out: &Path) -> anyhow::Result<SourceFiles> {
+    let (id, bytes) = arxiv::fetch_eprint(identifier)?;
+    // Legacy IDs contain a slash (e.g., "hep-th/9901001").
+    let dir = out.join(format!("arXiv-{}", id.replace('/', "_")));
+    unpack(&bytes, &dir).with_context(|| format!("failed to unpack e-print source of {id}"))?;
+    let mut bibliographies = Vec::new();
+    find_bibliographies(&dir, &mut bibliographies)?;
+    bibliographies.sort();
+    Ok(SourceFiles {
+        dir,
+        bibliographies,
+    })
+}
+
+/// Unpack an e-print into `dir`: a (gzipped) tarball is extracted as is, a lone gzipped file is a
+/// single TeX source, and a PDF means the authors submitted no source at all.
+fn unpack(bytes: &[u8], dir: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(dir)?;
+    if bytes.starts_with(b"%PDF") {
+        fs::write(dir.join("paper.pdf"), bytes)?;
+        return Ok(());
+    }
+    let data = if bytes.starts_with(&[0x1f, 0x8b]) {
+        gunzip(bytes, MAX_UNPACKED_BYTES)?
+    } else {
+        bytes.to_vec()
+    };
+    if is_tar(&data) {
+        // `unpack` refuses entries that would land outside `dir`.
+        tar::Archive::new(data.as_slice()).unpack(dir)?;
+    } else {
+        fs::write(dir.join("main.tex"), &data)?;
+    }
+    Ok(())
+}
+
+fn gunzip(bytes: &[u8], limit: u64) -> anyhow::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    GzDecoder::new(bytes)
+        .take(limit + 1)
+        .read_to_end(&mut data)?;
+    if data.len() as u64 > limit {
+        return Err(anyhow::anyhow!(
+            "e-print unpacks to more than {limit} bytes"
+        ));
+    }
+    Ok(data)
+}
+
+fn is_tar(data: &[u8]) -> bool {
+    data.get(257..262) == Some(b"ustar".as_slice())
+}
+
+/// Collect the `.bbl` and `.bib` files under `dir`. Symlinks are skipped: an e-print may link
+/// outside itself (e.g., to `/`) or back to one of its own directories.
+fn find_bibliographies(dir: &Path, out: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let path = entry.path();
+        if file_type.is_symlink() {
+            continue;
+        } else if file_type.is_dir() {
+            find_bibliographies(&path, out)?;
+        } else if matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("bbl" | "bib")
+        ) {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{Compression, write::GzEncoder};
+    use std::io::Write;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut enc = GzEncoder::new(Vec::new(), Compression::default());
+        enc.write_all(data).unwrap();
+        enc.finish().unwrap()
+    }
+
+    #[test]
+    fn unpack_tarball_and_find_bibliographies() {
+        let mut tarball = tar::Builder::new(Vec::new());
+        for (path, body) in [
+            ("main.tex", "\\bibliography{refs}"),
+            (
+                "main.bbl",
+                "\\begin{thebibliography}{1}\\end{thebibliography}",
+            ),
+            ("sections/refs.bib", "@misc{a, title = {A}}"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(body.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tarball
+                .append_data(&mut header, path, body.as_bytes())
+                .unwrap();
+        }
+        for (path, target) in [
+            ("root", "/"),
+            ("sections/loop", ".."),
+            ("other.bib", "/etc/hosts"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(tar::EntryType::Symlink);
+            header.set_size(0);
+            header.set_cksum();
+            tarball.append_link(&mut header, path, target).unwrap();
+        }
+        let bytes = gzip(&tarball.into_inner().unwrap());
+
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("arXiv-1810.04805");
+        unpack(&bytes, &dir).unwrap();
+        assert!(dir.join("root").is_symlink());
+        let mut found = Vec::new();
+        find_bibliographies(&dir, &mut found).unwrap();
+        found.sort();
+        assert_eq!(
+            found,
+            vec![dir.join("main.bbl"), dir.join("sections/refs.bib")]
+        );
+    }
+
+    #[test]
+    fn unpack_single_file_and_pdf() {
+        let tmp = tempfile::tempdir().unwrap();
+        unpack(&gzip(b"\\documentclass{article}"), tmp.path()).unwrap();
+        assert_eq!(
+            fs::read_to_string(tmp.path().join("main.tex")).unwrap(),
+            "\\documentclass{article}"
+        );
+        unpack(b"%PDF-1.5 ...", tmp.path()).unwrap();
+        assert!(tmp.path().join("paper.pdf").exists());
+    }
+
+    #[test]
+    fn gunzip_stops_at_limit() {
+        let bytes = gzip(&[0; 4096]);
+        assert_eq!(gunzip(&bytes, 4096).unwrap().len(), 4096);
+        assert!(gunzip(&bytes, 4095).is_err());
+    }
+}
@@ -1,7 +1,14 @@
+use std::sync::OnceLock;
+
 use anyhow::anyhow;
 use biblatex::Entry;
 
-use crate::identifier::{Identifier, arxiv::Arxiv, doi::Doi, embedded::Embedded, usenix::Usenix};
+use crate::identifier::{
+    Identifier, acl::AclAnthology, ads::Ads, arxiv::Arxiv, arxiv::ArxivQuery, dblp::Dblp, doi::Doi,
+    embedded::Embedded, figshare::Figshare, huggingface::HuggingFace, inspire::Inspire,
+    openreview::OpenReview, pubmed::Pubmed, registry::Package, repository::Repository, rfc::Rfc,
+    swh::SoftwareHeritage, usenix::Usenix, zenodo::Zenodo,
+};
 
 type ParserFn = for<'a> fn(&'a str) -> Option<Box<dyn Identifier<'a> + 'a>>;
 
@@ -12,6 +19,20 @@ type ParserFn = for<'a> fn(&'a str) -> Option<Box<dyn Identifier<'a> + 'a>>;
 static PARSERS: &[ParserFn] = &[
     erase::<Doi>(),
     erase::<Arxiv>(),
+    erase::<ArxivQuery>(),
+    erase::<Pubmed>(),
+    erase::<Rfc>(),
+    erase::<Dblp>(),
+    erase::<OpenReview>(),
+    erase::<AclAnthology>(),
+    erase::<Ads>(),
+    erase::<Inspire>(),
+    erase::<Repository>(),
+    erase::<Package>(),
+    erase::<Zenodo>(),
+    erase::<Figshare>(),
+    erase::<SoftwareHeritage>(),
+    erase::<HuggingFace>(),
     // More specific before generic embedded translator
     erase::<Usenix>(),
     erase::<Embedded>(),
@@ -41,11 +62,43 @@ const fn erase<F: IdFamily>() -> ParserFn {
     f
 }
 
+/// Run-wide resolution preferences, set once from the command line.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Options {
+    /// Replace preprint records with their published version's metadata when it is known.
+    pub prefer_published: bool,
+    /// Search for inputs that are not identifiers (e.g., titles) instead of rejecting them.
+    pub search: bool,
+}
+
+/// Default for `--max-items`: most items to keep from a source that lists several.
+pub const DEFAULT_MAX_ITEMS: usize = 50;
+
+static OPTIONS: OnceLock<Options> = OnceLock::new();
+
+/// Set the run-wide options; only the first call has an effect.
+pub fn configure(options: Options) {
+    let _ = OPTIONS.set(options);
+}
+
+/// The run-wide options, or the defaults when none were configured (e.g., in tests).
+pub fn options() -> Options {
+    OPTIONS.get().copied().unwrap_or_default()
+}
+
 /// Guess what type `identifier` is
 pub fn parse<'a>(identifier: &'a str) -> Option<Box<dyn Identifier<'a> + 'a>> {
     PARSERS.iter().find_map(|f| f(identifier))
 }
 
+/// Fetch what can be fetched in bulk for a run's identifiers ahead of resolving them one by one.
+///
+/// This only warms translator caches (currently arXiv's batched Atom queries); resolution works
+/// the same without it, just with more requests.
+pub fn prefetch(identifiers: &[&str]) {
+    crate::identifier::arxiv::prefetch(identifiers);
+}
+
 /// Guess what type `iderntifier` is and resolve the metadata.
 pub fn resolve(identifier: &str) -> anyhow::Result<Entry> {
     parse(identifier)
@@ -53,6 +106,19 @@ pub fn resolve(identifier: &str) -> anyhow::Result<Entry> {
         .ok_or_else(|| anyhow!("unrecognised identifier: {identifier}"))?
 }
 
+/// Guess what type `identifier` is and resolve every item it stands for, or at least `limit`
+/// of them when there are more (see [`Identifier::resolve_many`]).
+///
+/// With [`Options::search`], anything that is not an identifier is looked up as a title or
+/// reference string instead.
+pub fn resolve_many(identifier: &str, limit: Option<usize>) -> anyhow::Result<Vec<Entry>> {
+    match parse(identifier) {
+        Some(id) => id.resolve_many(limit),
+        None if options().search => crate::search::find(identifier).map(|e| vec![e]),
+        None => Err(anyhow!("unrecognised identifier: {identifier}")),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -110,11 +176,19 @@ mod tests {
         })
     }
 
+    // Bare RFC numbers ("rfc1", "RFC 42") and legacy ACL Anthology IDs ("P19-1001") are valid
+    // identifiers that random strings can hit.
+    fn looks_like_bare_id(s: &str) -> bool {
+        let s = s.trim();
+        crate::identifier::rfc::RFC_RE.is_match(s)
+            || Regex::new(r"^[A-Z]\d{2}-\d{4}$").unwrap().is_match(s)
+    }
+
     #[test]
     fn resolver_parse_rejects_non_doi() {
         let re = Regex::new(r"(?i)\b(10\.\d{4,9})/([-._;()/:A-Z0-9]+)\b").unwrap();
         proptest::proptest!(|(s in "[A-Za-z0-9 _-]{1,64}")| {
-            proptest::prop_assume!(!re.is_match(&s));
+            proptest::prop_assume!(!re.is_match(&s) && !looks_like_bare_id(&s));
             proptest::prop_assert!(parse(&s).is_none());
         })
     }
@@ -123,7 +197,7 @@ mod tests {
     fn resolver_resolve_errors_for_non_doi() {
         let re = Regex::new(r"(?i)\b(10\.\d{4,9})/([-._;()/:A-Z0-9]+)\b").unwrap();
         proptest::proptest!(|(s in "[A-Za-z0-9 _-]{1,64}")| {
-            proptest::prop_assume!(!re.is_match(&s));
+            proptest::prop_assume!(!re.is_match(&s) && !looks_like_bare_id(&s));
             let err = resolve(&s).unwrap_err();
             proptest::prop_assert!(err.to_string().contains("unrecognised identifier"));
         })
@@ -0,0 +1,340 @@
+//! Bibliographic search, for works known only by their title or a reference string.
+
+use std::collections::HashSet;
+
+use anyhow::Context;
+use biblatex::Entry;
+use serde_json::Value;
+
+use crate::import::refstring::{self, ParsedRef};
+use crate::resolver;
+
+const DEFAULT_BASE_URL: &str = "https://api.crossref.org";
+
+/// Score from which the best search hit is taken to be the work searched for.
+pub const ACCEPT: f32 = 0.85;
+
+/// What is known about a work being searched for.
+#[derive(Clone, Debug, Default)]
+pub struct Query {
+    /// Text sent to the search endpoint: a title, or a whole reference string.
+    pub text: String,
+    /// Title to compare hits against; the whole text when unknown.
+    pub title: Option<String>,
+    pub year: Option<String>,
+    /// Family names of (some of) the authors.
+    pub authors: Vec<String>,
+}
+
+impl Query {
+    /// A query for a title or a reference string, with whatever the reference parser makes of it.
+    pub fn from_text(text: &str) -> Self {
+        Self::from_parsed(text, &refstring::parse(text))
+    }
+
+    pub fn from_parsed(text: &str, parsed: &ParsedRef) -> Self {
+        let authors = parsed
+            .authors
+            .as_ref()
+            .map(|a| {
+                a.value
+                    .iter()
+                    .filter(|n| *n != "others")
+                    .map(|n| match n.split_once(',') {
+                        Some((family, _)) => family.trim().to_string(),
+                        None => n.rsplit(' ').next().unwrap_or(n).to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Query {
+            text: text.to_string(),
+            title: parsed.title.as_ref().map(|t| t.value.clone()),
+            year: parsed.year.as_ref().map(|y| y.value.clone()),
+            authors,
+        }
+    }
+}
+
+/// A work returned by a search.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate {
+    pub doi: String,
+    pub title: String,
+    /// Family names.
+    pub authors: Vec<String>,
+    pub year: Option<String>,
+    pub venue: Option<String>,
+}
+
+/// Query Crossref's `query.bibliographic` search, which takes titles and whole reference strings.
+pub fn crossref(query: &str, rows: usize) -> anyhow::Result<Vec<Candidate>> {
+    let base = std::env::var("BIB_CROSSREF_BASE_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.into());
+    let mut url = url::Url::parse(&format!("{}/works", base.trim_end_matches('/')))?;
+    url.query_pairs_mut()
+        .append_pair("query.bibliographic", query)
+        .append_pair("rows", &rows.to_string())
+        .append_pair("select", "DOI,title,author,issued,container-title,type");
+    let cfg = ureq::Agent::config_builder()
+        .timeout_connect(Some(std::time::Duration::from_secs(5)))
+        .timeout_global(Some(std::time::Duration::from_secs(30)))
+        .build();
+    let agent = ureq::Agent::new_with_config(cfg);
+    let body: String = agent
+        .get(url.as_str())
+        .header(
+            "User-Agent",
+            "Mozilla/5.0 (compatible; bib/0.1; +https://api.crossref.org)",
+        )
+        .call()
+        .with_context(|| format!("failed Crossref search for {query:?}"))?
+        .into_body()
+        .read_to_string()
+        .context("failed to read Crossref response body")?;
+    parse_crossref(&body)
+}
+
+fn parse_crossref(body: &str) -> anyhow::Result<Vec<Candidate>> {
+    let v: Value = serde_json::from_str(body).context("invalid JSON from Crossref")?;
+    let items = v
+        .pointer("/message/items")
+        .and_then(Value::as_array)
+        .ok_or_else(|| anyhow::anyhow!("Crossref response has no items"))?;
+    let first = |v: &Value, key: &str| {
+        v.get(key)
+            .and_then(Value::as_array)
+            .and_then(|a| a.first())
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    Ok(items
+        .iter()
+        .filter_map(|item| {
+            Some(Candidate {
+                doi: item.get("DOI")?.as_str()?.to_string(),
+                title: first(item, "title")?,
+                authors: item
+                    .get("author")
+                    .and_then(Value::as_array)
+                    .map(|a| {
+                        a.iter()
+                            .filter_map(|p| p.get("family").or_else(|| p.get("name")))
+                            .filter_map(Value::as_str)
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                year: item
+                    .pointer("/issued/date-parts/0/0")
+                    .and_then(Value::as_i64)
+                    .map(|y| y.to_string()),
+                venue: first(item, "container-title"),
+            })
+        })
+        .collect())
+}
+
+fn words(s: &str) -> HashSet<String> {
+    s.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// How alike two titles are, from 0 to 1 (the Dice coefficient of their words).
+pub fn title_similarity(a: &str, b: &str) -> f32 {
+    let (a, b) = (words(a), words(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    2.0 * a.intersection(&b).count() as f32 / (a.len() + b.len()) as f32
+}
+
+/// How well a hit matches a query, from 0 to 1: mostly by title, then by year and by the share
+/// of the query's authors it has. Parts the query does not know are left out.
+pub fn score(query: &Query, c: &Candidate) -> f32 {
+    let title = query.title.as_deref().unwrap_or(&query.text);
+    let mut total = 0.7 * title_similarity(title, &c.title);
+    let mut weight = 0.7;
+    if let Some(year) = &query.year {
+        let (a, b) = (
+            year.parse::<i32>().ok(),
+            c.year.as_deref().and_then(|y| y.parse::<i32>().ok()),
+        );
+        total += 0.15
+            * match (a, b) {
+                (Some(a), Some(b)) if a == b => 1.0,
+                // Preprints and proceedings often appear a year apart from the final version.
+                (Some(a), Some(b)) if (a - b).abs() == 1 => 0.5,
+                _ => 0.0,
+            };
+        weight += 0.15;
+    }
+    if !query.authors.is_empty() {
+        let theirs: Vec<String> = c.authors.iter().map(|a| a.to_lowercase()).collect();
+        let shared = query
+            .authors
+            .iter()
+            .filter(|a| theirs.contains(&a.to_lowercase()))
+            .count();
+        total += 0.15 * shared as f32 / query.authors.len() as f32;
+        weight += 0.15;
+    }
+    total / weight
+}
+
+/// Search for a work and rank the hits, best first.
+pub fn search(query: &Query, rows: usize) -> anyhow::Result<Vec<(f32, Candidate)>> {
+    let mut ranked: Vec<(f32, Candidate)> = crossref(&query.text, rows)?
+        .into_iter()
+        .map(|c| (score(query, &c), c))
+        .collect();
+    // A stable sort, so ties keep the endpoint's own relevance order.
+    ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+    Ok(ranked)
+}
+
+/// One line describing a ranked hit, e.g. for listing candidates.
+pub fn describe(score: f32, c: &Candidate) -> String {
+    let mut line = format!("{score:.2}  {}  {}", c.doi, c.title);
+    let mut about: Vec<String> = Vec::new();
+    match c.authors.as_slice() {
+        [] => {}
+        [one] => about.push(one.clone()),
+        [first, ..] => about.push(format!("{first} et al.")),
+    }
+    about.extend(c.year.clone());
+    about.extend(c.venue.clone());
+    if !about.is_empty() {
+        line.push_str(&format!(" ({})", about.join(", ")));
+    }
+    line
+}
+
+/// A search whose best hit scored below [`ACCEPT`], with the ranked candidates so that someone
+/// can choose among them.
+#[derive(Debug)]
+pub struct NoConfidentMatch {
+    pub text: String,
+    pub candidates: Vec<(f32, Candidate)>,
+}
+
+impl std::fmt::Display for NoConfidentMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no confident match for {:?}; candidates:", self.text)?;
+        for (score, c) in self.candidates.iter().take(5) {
+            write!(f, "\n  {}", describe(*score, c))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for NoConfidentMatch {}
+
+/// Find the work a title or reference string stands for. The best hit is resolved through its
+/// DOI only when it scores at least [`ACCEPT`]; otherwise the error is a [`NoConfidentMatch`].
+pub fn find(text: &str) -> anyhow::Result<Entry> {
+    let ranked = search(&Query::from_text(text), 10)?;
+    match ranked.first() {
+        Some((score, c)) if *score >= ACCEPT => resolver::resolve(&c.doi),
+        Some(_) => Err(NoConfidentMatch {
+            text: text.to_string(),
+            candidates: ranked,
+        }
+        .into()),
+        None => Err(anyhow::anyhow!("no search results for {text:?}")),
+    }
+}
+
+/// Look a parsed reference up online, taking the best hit only when it scores at least
+/// [`ACCEPT`]. Returns `None` when nothing matches well enough.
+pub fn confirm(text: &str, parsed: &ParsedRef) -> anyhow::Result<Option<Entry>> {
+    let query = Query::from_parsed(text, parsed);
+    match search(&query, 5)?.into_iter().next() {
+        Some((score, c)) if score >= ACCEPT => resolver::resolve(&c.doi).map(Some),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_crossref_items() {
+        let body = r#"{"status":"ok","message":{"items":[
+          {"DOI":"10.1145/36206.36194","type":"proceedings-article",
+           "title":["Superoptimizer: a look at the smallest program"],
+           "author":[{"given":"Henry","family":"Massalin","sequence":"first"}],
+           "issued":{"date-parts":[[1987,10]]},
+           "container-title":["Proceedings of the second international conference on Architectual support for programming languages and operating systems"]},
+          {"DOI":"10.1000/no-title"}
+        ]}}"#;
+        let items = parse_crossref(body).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].doi, "10.1145/36206.36194");
+        assert_eq!(items[0].authors, vec!["Massalin".to_string()]);
+        assert_eq!(items[0].year.as_deref(), Some("1987"));
+    }
+
+    #[test]
+    fn title_similarity_ignores_case_and_punctuation() {
+        let t = "Superoptimizer: a look at the smallest program";
+        assert_eq!(
+            title_similarity(t, "SUPEROPTIMIZER - A Look at the Smallest Program."),
+            1.0
+        );
+        assert!(title_similarity(t, "Superoptimizers for everyone") < ACCEPT);
+        assert_eq!(title_similarity(t, ""), 0.0);
+    }
+
+    fn candidate(title: &str, authors: &[&str], year: &str) -> Candidate {
+        Candidate {
+            doi: "10.1000/x".to_string(),
+            title: title.to_string(),
+            authors: authors.iter().map(|a| a.to_string()).collect(),
+            year: Some(year.to_string()),
+            venue: None,
+        }
+    }
+
+    #[test]
+    fn score_weighs_title_year_and_authors() {
+        let query = Query::from_text(
+            "Massalin, H. Superoptimizer: a look at the smallest program. ASPLOS 1987.",
+        );
+        assert_eq!(query.authors, vec!["Massalin".to_string()]);
+        assert_eq!(query.year.as_deref(), Some("1987"));
+
+        let exact = candidate(
+            "Superoptimizer: A Look at the Smallest Program",
+            &["Massalin"],
+            "1987",
+        );
+        assert!((score(&query, &exact) - 1.0).abs() < 1e-6);
+        let off_by_one = candidate(
+            "Superoptimizer: a look at the smallest program",
+            &["Massalin"],
+            "1988",
+        );
+        assert!(score(&query, &off_by_one) >= ACCEPT);
+        let other = candidate("Superoptimization of smallest programs", &["Smith"], "2020");
+        assert!(score(&query, &other) < ACCEPT);
+
+        // Title words alone are compared by title only.
+        let query = Query::from_text("superoptimizer a look at the smallest program");
+        assert!(query.authors.is_empty());
+        assert!(score(&query, &other) < score(&query, &exact));
+        assert!(score(&query, &exact) >= ACCEPT);
+    }
+
+    #[test]
+    fn describe_candidate() {
+        let mut c = candidate("A Title", &["Smith", "Jones"], "2020");
+        c.venue = Some("Some Journal".to_string());
+        assert_eq!(
+            describe(0.5, &c),
+            "0.50  10.1000/x  A Title (Smith et al., 2020, Some Journal)"
+        );
+    }
+}